@@ -3,9 +3,20 @@
 COMMAND="turn --listen 0.0.0.0:3478"
 
 if [ $TURN_REALM ]; then COMMAND="${COMMAND} --realm ${TURN_REALM}"; fi
+if [ $TURN_REALM_MAP ]; then COMMAND="${COMMAND} --realm-map ${TURN_REALM_MAP}"; fi
+if [ $TURN_RELAY_MAP ]; then COMMAND="${COMMAND} --relay-map ${TURN_RELAY_MAP}"; fi
 if [ $TURN_NATS ]; then COMMAND="${COMMAND} --nats ${TURN_NATS}"; fi
 if [ $TURN_BUFFER ]; then COMMAND="${COMMAND} --buffer ${TURN_BUFFER}"; fi
 if [ $TURN_THREADS ]; then COMMAND="${COMMAND} --threads ${TURN_THREADS}"; fi
+if [ $TURN_RECV_BUFFER ]; then COMMAND="${COMMAND} --recv-buffer ${TURN_RECV_BUFFER}"; fi
+if [ $TURN_SEND_BUFFER ]; then COMMAND="${COMMAND} --send-buffer ${TURN_SEND_BUFFER}"; fi
+if [ $TURN_BATCH ]; then COMMAND="${COMMAND} --batch ${TURN_BATCH}"; fi
+if [ $TURN_SEND_RETRIES ]; then COMMAND="${COMMAND} --send-retries ${TURN_SEND_RETRIES}"; fi
+if [ $TURN_IDLE_TIMEOUT ]; then COMMAND="${COMMAND} --idle-timeout ${TURN_IDLE_TIMEOUT}"; fi
+if [ $TURN_PEER_ALLOW ]; then COMMAND="${COMMAND} --peer-allow ${TURN_PEER_ALLOW}"; fi
+if [ $TURN_PEER_DENY ]; then COMMAND="${COMMAND} --peer-deny ${TURN_PEER_DENY}"; fi
+if [ $TURN_SOFTWARE ]; then COMMAND="${COMMAND} --software ${TURN_SOFTWARE}"; fi
+if [ $TURN_LOG_LEVEL ]; then COMMAND="${COMMAND} --log-level ${TURN_LOG_LEVEL}"; fi
 if [ $TURN_EXTERNAL ]; then COMMAND="${COMMAND} --external ${TURN_EXTERNAL}"; fi
 
 /bin/bash -c "${COMMAND}"
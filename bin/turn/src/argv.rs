@@ -1,9 +1,69 @@
+use anyhow::anyhow;
 use clap::Clap;
+use log::LevelFilter;
 use std::{
-    net::SocketAddr,
+    net::{
+        SocketAddr,
+        IpAddr,
+        Ipv4Addr,
+        Ipv6Addr
+    },
+    str::FromStr,
     sync::Arc
 };
 
+/// client address realm mapping.
+///
+/// the format is `<ip>=<realm>`, the realm is used 
+/// for the client of the specified ip address.
+#[derive(Debug, Clone)]
+pub struct RealmMap {
+    pub ip: IpAddr,
+    pub realm: String
+}
+
+/// client address relay mapping.
+///
+/// the format is `<cidr>=<ip>`, the relayed transport address 
+/// of the clients in the block uses the specified ip address.
+#[derive(Debug, Clone, Copy)]
+pub struct RelayMap {
+    pub block: Cidr,
+    pub ip: IpAddr
+}
+
+/// ip address block.
+///
+/// the format is `<ip>/<prefix>`, a bare ip address 
+/// is a block of a single address.
+#[derive(Debug, Clone, Copy)]
+pub struct Cidr {
+    pub ip: IpAddr,
+    pub prefix: u8
+}
+
+/// peer addresses that are not relayed to by default.
+///
+/// private, shared (CGNAT), loopback, link-local, multicast, 
+/// reserved and unspecified addresses, relaying to them would 
+/// expose the internal network of the server.
+#[rustfmt::skip]
+const DEFAULT_PEER_DENY: [Cidr; 13] = [
+    Cidr { ip: IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), prefix: 8 },
+    Cidr { ip: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 0)), prefix: 8 },
+    Cidr { ip: IpAddr::V4(Ipv4Addr::new(100, 64, 0, 0)), prefix: 10 },
+    Cidr { ip: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 0)), prefix: 8 },
+    Cidr { ip: IpAddr::V4(Ipv4Addr::new(169, 254, 0, 0)), prefix: 16 },
+    Cidr { ip: IpAddr::V4(Ipv4Addr::new(172, 16, 0, 0)), prefix: 12 },
+    Cidr { ip: IpAddr::V4(Ipv4Addr::new(192, 168, 0, 0)), prefix: 16 },
+    Cidr { ip: IpAddr::V4(Ipv4Addr::new(224, 0, 0, 0)), prefix: 4 },
+    Cidr { ip: IpAddr::V4(Ipv4Addr::new(240, 0, 0, 0)), prefix: 4 },
+    Cidr { ip: IpAddr::V6(Ipv6Addr::UNSPECIFIED), prefix: 127 },
+    Cidr { ip: IpAddr::V6(Ipv6Addr::new(0xfc00, 0, 0, 0, 0, 0, 0, 0)), prefix: 7 },
+    Cidr { ip: IpAddr::V6(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 0)), prefix: 10 },
+    Cidr { ip: IpAddr::V6(Ipv6Addr::new(0xff00, 0, 0, 0, 0, 0, 0, 0)), prefix: 8 },
+];
+
 #[derive(Clap)]
 #[clap(
     name = "TURN (Traversal Using Relays around NAT)",
@@ -19,6 +79,14 @@ pub struct Argv {
     #[clap(default_value = "localhost")]
     #[clap(about = "service realm name")]
     pub realm: String,
+    /// specify the realm of some client addresses. 
+    /// for multi-tenant hosting, different clients can be 
+    /// challenged with different realms, the format is 
+    /// `<ip>=<realm>`, clients without mapping use the 
+    /// default realm.
+    #[clap(long)]
+    #[clap(about = "client address realm mapping")]
+    pub realm_map: Vec<RealmMap>,
     /// specify the node external address and port. 
     /// for the case of exposing the service to the outside, 
     /// you need to manually specify the server external IP 
@@ -27,6 +95,15 @@ pub struct Argv {
     #[clap(default_value = "127.0.0.1:3478")]
     #[clap(about = "service external address and port")]
     pub external: SocketAddr,
+    /// specify the relay address of some client blocks. 
+    /// on a multi-homed host, the relayed transport address 
+    /// must be an address of the interface that carries the 
+    /// traffic of the client, the format is `<cidr>=<ip>`, 
+    /// the first matching block is used, and clients without 
+    /// mapping use the external address.
+    #[clap(long)]
+    #[clap(about = "client address relay mapping")]
+    pub relay_map: Vec<RelayMap>,
     /// the address and port bound by UDP Server. 
     /// currently, it does not support binding multiple 
     /// addresses at the same time. the bound address 
@@ -51,6 +128,8 @@ pub struct Argv {
     /// value to 4096. a larger space will be easier to deal 
     /// with more complex MTU situations, although most of 
     /// the time The space utilization rate is not high.
+    /// the value is clamped to 576..=65535, a datagram 
+    /// that fills the buffer is dropped and the buffer grows.
     #[clap(long)]
     #[clap(default_value = "1280")]
     #[clap(about = "udp cache buffer size")]
@@ -63,10 +142,377 @@ pub struct Argv {
     #[clap(long)]
     #[clap(about = "runtime threads size")]
     pub threads: Option<usize>,
+    /// the size (byte) of the socket receive buffer (SO_RCVBUF). 
+    /// by default the system default is used, which is often 
+    /// too small for a busy relay and causes the kernel to drop 
+    /// datagrams, a value of 4194304 or more is recommended for 
+    /// high throughput relaying. the system may limit or double 
+    /// the value, see net.core.rmem_max on linux.
+    #[clap(long)]
+    #[clap(about = "udp socket receive buffer size")]
+    pub recv_buffer: Option<usize>,
+    /// the size (byte) of the socket send buffer (SO_SNDBUF). 
+    /// by default the system default is used, a value of 
+    /// 4194304 or more is recommended for high throughput 
+    /// relaying. the system may limit or double the value, 
+    /// see net.core.wmem_max on linux.
+    #[clap(long)]
+    #[clap(about = "udp socket send buffer size")]
+    pub send_buffer: Option<usize>,
+    /// allow the bind address to be reused (SO_REUSEADDR), 
+    /// this allows the server to be restarted immediately 
+    /// on the same address.
+    #[clap(long)]
+    #[clap(about = "udp socket reuse address")]
+    pub reuse_address: bool,
+    /// the maximum number of datagrams read and sent together. 
+    /// by default every datagram is read and sent with its own 
+    /// syscall, with batching the datagrams that are already 
+    /// queued in the socket are read with a single recvmmsg and 
+    /// processed in a burst, and the outbound datagrams are sent 
+    /// with a single sendmmsg on linux, which reduces the syscalls 
+    /// at a high packet rate. the value is clamped to 1..=1024.
+    #[clap(long)]
+    #[clap(about = "udp batch size")]
+    pub batch: Option<usize>,
+    /// the number of times a udp send is retried on a transient 
+    /// error, such as a full socket send buffer (WouldBlock), 
+    /// the datagram is dropped and counted when the retries are 
+    /// exhausted or the error is fatal, a send error never 
+    /// stops the server.
+    #[clap(long)]
+    #[clap(default_value = "3")]
+    #[clap(about = "udp send retries")]
+    pub send_retries: usize,
+    /// the maximum number of nonces held by the server. 
+    /// every client that has been challenged holds a nonce, 
+    /// when the limit is reached, the oldest nonce is evicted, 
+    /// and the expired nonces are swept every 60 seconds, 
+    /// this keeps memory bounded under scanner traffic.
+    #[clap(long)]
+    #[clap(default_value = "10240")]
+    #[clap(about = "nonce table capacity")]
+    pub nonce_capacity: usize,
+    /// the maximum number of permissions for each node. 
+    /// when the limit is reached, the least recently 
+    /// refreshed permission is evicted.
+    #[clap(long)]
+    #[clap(default_value = "100")]
+    #[clap(about = "node permission capacity")]
+    pub permission_capacity: usize,
+    /// reclaim an allocation that has had no activity for the 
+    /// specified second, regardless of its lifetime. a refresh, 
+    /// a permission, a channel binding or the data relayed from or 
+    /// to the client is an activity, so the timeout should be longer 
+    /// than the refresh interval of the clients. the allocations 
+    /// are checked every 60 seconds, by default idle allocations 
+    /// live until their lifetime expires.
+    #[clap(long)]
+    #[clap(about = "allocation idle timeout")]
+    pub idle_timeout: Option<u64>,
+    /// require a valid FINGERPRINT attribute in every stun message. 
+    /// the fingerprint is checked before any other work, a message 
+    /// without the attribute or with a wrong fingerprint is dropped, 
+    /// this sheds malformed and spoofed packets cheaply, but not 
+    /// all clients include the attribute.
+    #[clap(long)]
+    #[clap(about = "require stun fingerprint")]
+    pub require_fingerprint: bool,
+    /// the textual description of the server in the 
+    /// SOFTWARE attribute, by default it is the name and 
+    /// the version of the stun crate the server is built with.
+    #[clap(long)]
+    #[clap(about = "stun software description")]
+    pub software: Option<String>,
+    /// the peer address blocks that are always relayed to, 
+    /// the allow list takes precedence over the deny list, 
+    /// the external address of the server is always allowed.
+    #[clap(long)]
+    #[clap(about = "peer address allow list")]
+    pub peer_allow: Vec<Cidr>,
+    /// the peer address blocks that are never relayed to, 
+    /// in addition to the private, shared, loopback, link-local, 
+    /// multicast and reserved blocks that are denied by default.
+    #[clap(long)]
+    #[clap(about = "peer address deny list")]
+    pub peer_deny: Vec<Cidr>,
+    /// the log level, one of off, error, warn, info, debug 
+    /// and trace. the RUST_LOG environment variable overrides 
+    /// it, and RUST_LOG_STYLE controls the colored output. 
+    /// at the trace level every decoded stun message is dumped 
+    /// with its attributes, which helps to diagnose why the 
+    /// request of a client is rejected.
+    #[clap(long)]
+    #[clap(default_value = "info")]
+    #[clap(about = "log level")]
+    pub log_level: LevelFilter,
 }
 
 impl Argv {
     pub fn new() -> Arc<Self> {
         Arc::new(Self::parse())
     }
+
+    /// get the SOFTWARE attribute value.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let c = argv::Argv::new();
+    ///
+    /// // without --software
+    /// assert_eq!(c.get_software(), stun::SOFTWARE);
+    /// ```
+    pub fn get_software(&self) -> &str {
+        self.software
+            .as_deref()
+            .unwrap_or(stun::SOFTWARE)
+    }
+
+    /// get the relay ip address of the client.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// // --external 203.0.113.1:3478 --relay-map 10.0.0.0/8=10.0.0.1
+    /// let c = argv::Argv::new();
+    ///
+    /// assert_eq!(c.get_relay_ip(&"10.1.2.3:8080".parse().unwrap()), "10.0.0.1".parse().unwrap());
+    /// assert_eq!(c.get_relay_ip(&"1.1.1.1:8080".parse().unwrap()), "203.0.113.1".parse().unwrap());
+    /// ```
+    #[rustfmt::skip]
+    pub fn get_relay_ip(&self, client: &SocketAddr) -> IpAddr {
+        let ip = client.ip();
+        self.relay_map
+            .iter()
+            .find(|m| m.block.contains(&ip))
+            .map(|m| m.ip)
+            .unwrap_or_else(|| self.external.ip())
+    }
+
+    /// whether the peer address may be relayed to.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let c = argv::Argv::new();
+    ///
+    /// assert!(!c.is_allowed_peer(&"10.0.0.1:8080".parse().unwrap()));
+    /// assert!(c.is_allowed_peer(&"1.1.1.1:8080".parse().unwrap()));
+    /// ```
+    #[rustfmt::skip]
+    pub fn is_allowed_peer(&self, peer: &SocketAddr) -> bool {
+        let ip = peer.ip().to_canonical();
+        if ip == self.external.ip() || self.relay_map.iter().any(|m| m.ip == ip) {
+            return true
+        }
+
+        if self.peer_allow.iter().any(|c| c.contains(&ip)) {
+            return true
+        }
+
+        !self.peer_deny
+            .iter()
+            .chain(DEFAULT_PEER_DENY.iter())
+            .any(|c| c.contains(&ip))
+    }
+}
+
+impl Cidr {
+    /// whether the block contains the ip address.
+    ///
+    /// an ipv4-mapped ipv6 address is matched as the ipv4 
+    /// address, otherwise it would skip every ipv4 block.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use turn::argv::Cidr;
+    ///
+    /// let cidr = "10.0.0.0/8".parse::<Cidr>().unwrap();
+    /// assert!(cidr.contains(&"10.1.2.3".parse().unwrap()));
+    /// assert!(!cidr.contains(&"11.1.2.3".parse().unwrap()));
+    /// ```
+    #[rustfmt::skip]
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        match (self.ip, ip.to_canonical()) {
+            (IpAddr::V4(n), IpAddr::V4(a)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(n) & mask == u32::from(a) & mask
+            },
+            (IpAddr::V6(n), IpAddr::V6(a)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(n) & mask == u128::from(a) & mask
+            },
+            _ => false
+        }
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = anyhow::Error;
+    /// # Example
+    ///
+    /// ```no_run
+    /// use turn::argv::Cidr;
+    ///
+    /// let cidr = "192.168.0.0/16".parse::<Cidr>().unwrap();
+    /// assert_eq!(cidr.ip, "192.168.0.0".parse().unwrap());
+    /// assert_eq!(cidr.prefix, 16);
+    ///
+    /// let cidr = "::1".parse::<Cidr>().unwrap();
+    /// assert_eq!(cidr.prefix, 128);
+    /// ```
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (ip, prefix) = match value.split_once('/') {
+            Some((ip, prefix)) => (ip.parse::<IpAddr>()?, Some(prefix.parse::<u8>()?)),
+            None => (value.parse::<IpAddr>()?, None)
+        };
+
+        let max = if ip.is_ipv4() { 32 } else { 128 };
+        let prefix = prefix.unwrap_or(max);
+        if prefix > max {
+            return Err(anyhow!("invalid cidr prefix"))
+        }
+
+        Ok(Self {
+            ip,
+            prefix
+        })
+    }
+}
+
+impl FromStr for RealmMap {
+    type Err = anyhow::Error;
+    /// # Example
+    ///
+    /// ```no_run
+    /// use turn::argv::RealmMap;
+    ///
+    /// let map = "192.168.1.2=tenant".parse::<RealmMap>().unwrap();
+    /// assert_eq!(map.ip, "192.168.1.2".parse().unwrap());
+    /// assert_eq!(map.realm, "tenant");
+    /// ```
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (ip, realm) = value.split_once('=')
+            .ok_or_else(|| anyhow!("invalid realm map"))?;
+        Ok(Self {
+            ip: ip.parse()?,
+            realm: realm.to_string()
+        })
+    }
+}
+
+impl FromStr for RelayMap {
+    type Err = anyhow::Error;
+    /// # Example
+    ///
+    /// ```no_run
+    /// use turn::argv::RelayMap;
+    ///
+    /// let map = "10.0.0.0/8=10.0.0.1".parse::<RelayMap>().unwrap();
+    /// assert_eq!(map.block.prefix, 8);
+    /// assert_eq!(map.ip, "10.0.0.1".parse().unwrap());
+    /// ```
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (block, ip) = value.split_once('=')
+            .ok_or_else(|| anyhow!("invalid relay map"))?;
+        Ok(Self {
+            block: block.parse()?,
+            ip: ip.parse()?
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+
+    #[test]
+    fn cidr() {
+        let cidr = "10.0.0.0/8".parse::<Cidr>().unwrap();
+        assert_eq!(cidr.prefix, 8);
+        assert!(cidr.contains(&"10.1.2.3".parse().unwrap()));
+        assert!(!cidr.contains(&"11.1.2.3".parse().unwrap()));
+        assert!(!cidr.contains(&"::1".parse().unwrap()));
+        assert!(cidr.contains(&"::ffff:10.1.2.3".parse().unwrap()));
+
+        let cidr = "::1".parse::<Cidr>().unwrap();
+        assert_eq!(cidr.prefix, 128);
+        assert!(cidr.contains(&"::1".parse().unwrap()));
+        assert!(!cidr.contains(&"::2".parse().unwrap()));
+
+        let cidr = "0.0.0.0/0".parse::<Cidr>().unwrap();
+        assert!(cidr.contains(&"1.1.1.1".parse().unwrap()));
+
+        assert!("10.0.0.0/33".parse::<Cidr>().is_err());
+        assert!("10.0.0.0/x".parse::<Cidr>().is_err());
+    }
+
+    #[test]
+    fn is_allowed_peer() {
+        let c = mock::argv(&[]);
+        assert!(!c.is_allowed_peer(&"10.0.0.1:8080".parse().unwrap()));
+        assert!(!c.is_allowed_peer(&"192.168.1.1:8080".parse().unwrap()));
+        assert!(!c.is_allowed_peer(&"127.0.0.2:8080".parse().unwrap()));
+        assert!(!c.is_allowed_peer(&"224.0.0.1:8080".parse().unwrap()));
+        assert!(!c.is_allowed_peer(&"[fe80::1]:8080".parse().unwrap()));
+        assert!(c.is_allowed_peer(&"1.1.1.1:8080".parse().unwrap()));
+
+        // the external address of the server is a relay.
+        assert!(c.is_allowed_peer(&"127.0.0.1:8080".parse().unwrap()));
+
+        // shared and reserved addresses.
+        assert!(!c.is_allowed_peer(&"100.64.0.1:8080".parse().unwrap()));
+        assert!(!c.is_allowed_peer(&"100.127.255.255:8080".parse().unwrap()));
+        assert!(c.is_allowed_peer(&"100.128.0.1:8080".parse().unwrap()));
+        assert!(!c.is_allowed_peer(&"240.0.0.1:8080".parse().unwrap()));
+        assert!(!c.is_allowed_peer(&"255.255.255.255:8080".parse().unwrap()));
+
+        // the ipv4-mapped addresses are matched as ipv4.
+        assert!(!c.is_allowed_peer(&"[::ffff:10.0.0.1]:8080".parse().unwrap()));
+        assert!(!c.is_allowed_peer(&"[::ffff:127.0.0.2]:8080".parse().unwrap()));
+        assert!(!c.is_allowed_peer(&"[::ffff:192.168.1.1]:8080".parse().unwrap()));
+        assert!(c.is_allowed_peer(&"[::ffff:1.1.1.1]:8080".parse().unwrap()));
+
+        let c = mock::argv(&["--peer-allow", "10.1.0.0/16", "--peer-deny", "1.1.1.0/24"]);
+        assert!(c.is_allowed_peer(&"10.1.2.3:8080".parse().unwrap()));
+        assert!(c.is_allowed_peer(&"[::ffff:10.1.2.3]:8080".parse().unwrap()));
+        assert!(!c.is_allowed_peer(&"10.2.2.3:8080".parse().unwrap()));
+        assert!(!c.is_allowed_peer(&"1.1.1.1:8080".parse().unwrap()));
+        assert!(!c.is_allowed_peer(&"[::ffff:1.1.1.1]:8080".parse().unwrap()));
+    }
+
+    #[test]
+    fn relay_map() {
+        assert!("10.0.0.0/8".parse::<RelayMap>().is_err());
+        assert!("10.0.0.0/8=x".parse::<RelayMap>().is_err());
+        assert!("127.0.0.2".parse::<RealmMap>().is_err());
+        let map = "127.0.0.2=tenant".parse::<RealmMap>().unwrap();
+        assert_eq!(map.ip, "127.0.0.2".parse::<IpAddr>().unwrap());
+        assert_eq!(map.realm, "tenant");
+
+        let c = mock::argv(&[
+            "--external", "203.0.113.1:3478",
+            "--relay-map", "10.1.0.0/16=10.1.0.1", 
+            "--relay-map", "10.0.0.0/8=10.0.0.1",
+            "--relay-map", "::/0=2001:db8::1"
+        ]);
+
+        // the first matching block is used.
+        for (client, relay) in [
+            ("10.1.2.3:8080", "10.1.0.1"),
+            ("10.2.2.3:8080", "10.0.0.1"),
+            ("127.0.0.1:8080", "203.0.113.1"),
+            ("[::1]:8080", "2001:db8::1")
+        ] {
+            let ip = c.get_relay_ip(&client.parse().unwrap());
+            assert_eq!(ip, relay.parse::<IpAddr>().unwrap());
+        }
+
+        // the relay addresses are allowed peers.
+        assert!(c.is_allowed_peer(&"10.0.0.1:8080".parse().unwrap()));
+        assert!(!c.is_allowed_peer(&"10.0.0.2:8080".parse().unwrap()));
+    }
 }
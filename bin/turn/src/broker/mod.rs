@@ -9,6 +9,9 @@ use std::{
     net::SocketAddr,
 };
 
+#[cfg(test)]
+use std::collections::HashMap;
+
 use async_nats::{
     connect,
     Connection
@@ -23,6 +26,16 @@ struct Topic {
     auth: String
 }
 
+/// the source of the user keys.
+enum Backend {
+    /// the control service behind the nats server.
+    Nats(Connection),
+    /// the fixed users of the tests, the username 
+    /// to the password and the group.
+    #[cfg(test)]
+    Static(HashMap<String, (String, u32)>),
+}
+
 /// Broker
 ///
 /// The Broker is the main component of turn. 
@@ -30,7 +43,7 @@ struct Topic {
 /// emits events and communicates with remote nodes. 
 /// You must create a Broker instance on every node.
 pub struct Broker {
-    nats: Connection,
+    backend: Backend,
     topic: Topic
 }
 
@@ -38,12 +51,28 @@ impl Broker {
     /// connect nats server.
     pub async fn new(c: &Arc<Argv>) -> Result<Arc<Self>> {
         Ok(Arc::new(Self { 
-            nats: connect(c.nats.as_str()).await?,
+            backend: Backend::Nats(connect(c.nats.as_str()).await?),
             topic: Topic {
                 auth: format!("auth.{}", c.realm)
             }
         }))
     }
+
+    /// create broker with fixed users, 
+    /// the username, the password and the group.
+    #[cfg(test)]
+    pub fn with_users(c: &Arc<Argv>, users: &[(&str, &str, u32)]) -> Arc<Self> {
+        let users = users
+            .iter()
+            .map(|(u, p, g)| (u.to_string(), (p.to_string(), *g)))
+            .collect();
+        Arc::new(Self {
+            backend: Backend::Static(users),
+            topic: Topic {
+                auth: format!("auth.{}", c.realm)
+            }
+        })
+    }
     
     /// provide the user name and source address, 
     /// request the control service to give the 
@@ -58,8 +87,17 @@ impl Broker {
     /// ```
     #[rustfmt::skip]
     pub async fn auth(&self, a: &SocketAddr, u: &str) -> Result<response::Auth> {
-        let req = request::Auth { username: u.to_string(), addr: *a };
-        let message = self.nats.request(&self.topic.auth, Into::<Vec<u8>>::into(req)).await?;
-        Response::<response::Auth>::try_from(message.data.as_slice())?.into_result()
+        match &self.backend {
+            Backend::Nats(nats) => {
+                let req = request::Auth { username: u.to_string(), addr: *a };
+                let message = nats.request(&self.topic.auth, Into::<Vec<u8>>::into(req)).await?;
+                Response::<response::Auth>::try_from(message.data.as_slice())?.into_result()
+            },
+            #[cfg(test)]
+            Backend::Static(users) => users
+                .get(u)
+                .map(|(password, group)| response::Auth { password: password.clone(), group: *group })
+                .ok_or_else(|| anyhow::anyhow!("user not found"))
+        }
     }
 }
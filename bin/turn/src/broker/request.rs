@@ -1,8 +1,5 @@
 use serde::Serialize;
-use std::{
-    net::SocketAddr,
-    convert::Into
-};
+use std::net::SocketAddr;
 
 /// auth request struct.
 #[derive(Serialize)]
@@ -11,7 +8,7 @@ pub struct Auth {
     pub username: String
 }
 
-impl Into<Vec<u8>> for Auth {
+impl From<Auth> for Vec<u8> {
     /// uncheck input serialization.
     ///
     /// # Example
@@ -22,7 +19,7 @@ impl Into<Vec<u8>> for Auth {
     ///     username: "panda".to_string()
     /// })
     /// ```
-    fn into(self) -> Vec<u8> {
-        serde_json::to_vec(&self).unwrap()
+    fn from(auth: Auth) -> Self {
+        serde_json::to_vec(&auth).unwrap()
     }
 }
@@ -83,3 +83,27 @@ impl<T> Response<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn into_result() {
+        let buf = br#"{"error":null,"data":{"group":0,"password":"panda"}}"#;
+        let auth = Response::<Auth>::try_from(&buf[..])
+            .unwrap()
+            .into_result()
+            .unwrap();
+        assert_eq!(auth.password, "panda");
+        assert_eq!(auth.group, 0);
+
+        let buf = br#"{"error":"not found","data":null}"#;
+        let err = Response::<Auth>::try_from(&buf[..])
+            .unwrap()
+            .into_result()
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "not found");
+    }
+}
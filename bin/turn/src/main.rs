@@ -4,22 +4,64 @@ mod argv;
 mod proto;
 mod broker;
 
+#[cfg(test)]
+mod mock;
+
 use anyhow::Result;
+use env_logger::Env;
+use stun::util::RustCrypto;
 use broker::Broker;
 use state::State;
 use argv::Argv;
 
+/// create logger.
+///
+/// the level is the configured level, 
+/// RUST_LOG and RUST_LOG_STYLE override it.
+fn logger(c: &Argv) -> env_logger::Builder {
+    let mut builder = env_logger::Builder::new();
+    builder
+        .filter_level(c.log_level)
+        .parse_env(Env::default())
+        .format_module_path(false);
+    builder
+}
+
 #[tokio::main]
 #[rustfmt::skip]
 async fn main() -> Result<()> {
-    env_logger::builder()
-        .format_module_path(false)
-        .init();
-    
     let c = Argv::new();
+    logger(&c).init();
+    
     let b = Broker::new(&c).await?;
-    let s = State::new(&c, &b);
+    let (s, mut events) = State::new(&c, &b, RustCrypto);
+    tokio::spawn(async move {
+        while let Some(e) = events.recv().await {
+            log::debug!("state event: {}", e);
+        }
+    });
+
     server::run(c, s.clone()).await?;
     s.run().await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::LevelFilter;
+
+    #[test]
+    fn log_level() {
+        if std::env::var_os("RUST_LOG").is_some() {
+            return
+        }
+
+        let c = mock::argv(&[]);
+        assert_eq!(logger(&c).build().filter(), LevelFilter::Info);
+        let c = mock::argv(&["--log-level", "debug"]);
+        assert_eq!(logger(&c).build().filter(), LevelFilter::Debug);
+        let c = mock::argv(&["--log-level", "off"]);
+        assert_eq!(logger(&c).build().filter(), LevelFilter::Off);
+    }
+}
@@ -0,0 +1,262 @@
+//! test fixtures.
+//!
+//! the state of the tests is backed by a broker with a fixed 
+//! user, and the client encodes the requests of the user and 
+//! hands them to the proto as the server thread does.
+
+use bytes::BytesMut;
+use clap::Clap;
+use tokio::sync::mpsc;
+use std::{
+    convert::TryFrom,
+    net::SocketAddr,
+    sync::{
+        Arc,
+        Mutex,
+        Once
+    }
+};
+
+use stun::{
+    Kind,
+    MessageReader,
+    MessageWriter
+};
+
+use stun::util::{
+    RustCrypto,
+    long_key
+};
+
+use stun::attribute::{
+    ErrorCode,
+    UserName,
+    Realm,
+    Nonce,
+    ReqeestedTransport,
+    XorRelayedAddress,
+    XorPeerAddress
+};
+
+use super::{
+    argv::Argv,
+    broker::Broker,
+    server::ThreadLocal,
+    proto::{
+        Proto,
+        Via
+    },
+    state::{
+        State,
+        Event
+    }
+};
+
+/// the username of the test user.
+pub const USERNAME: &str = "panda";
+
+/// the password of the test user.
+pub const PASSWORD: &str = "panda";
+
+/// the username of the other test user, with the same password.
+pub const OTHER_USERNAME: &str = "raspberry";
+
+/// the records of the test logger.
+static RECORDS: Mutex<Vec<(log::Level, String)>> = Mutex::new(Vec::new());
+
+/// test logger.
+///
+/// the logger is global, every record of every test is kept, 
+/// a test looks for the records of its own client address.
+struct Logger;
+
+impl log::Log for Logger {
+    fn enabled(&self, _: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        let message = record.args().to_string();
+        RECORDS.lock().unwrap().push((record.level(), message));
+    }
+
+    fn flush(&self) {}
+}
+
+/// install the test logger.
+pub fn capture() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        log::set_logger(&Logger).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+    });
+}
+
+/// the records that contain the pattern.
+pub fn logs(pattern: &str) -> Vec<(log::Level, String)> {
+    RECORDS.lock()
+        .unwrap()
+        .iter()
+        .filter(|(_, m)| m.contains(pattern))
+        .cloned()
+        .collect()
+}
+
+/// parse the command line arguments.
+pub fn argv(args: &[&str]) -> Arc<Argv> {
+    let args = std::iter::once("turn").chain(args.iter().copied());
+    Arc::new(Argv::parse_from(args))
+}
+
+/// create state with the test user.
+pub fn state(c: &Arc<Argv>) -> (Arc<State>, mpsc::Receiver<Event>) {
+    let broker = Broker::with_users(c, &[(USERNAME, PASSWORD, 0), (OTHER_USERNAME, PASSWORD, 0)]);
+    State::new(c, &broker, RustCrypto)
+}
+
+/// create thread local with the test user.
+pub fn local(args: &[&str]) -> (ThreadLocal, mpsc::Receiver<Event>) {
+    let conf = argv(args);
+    let (state, events) = state(&conf);
+    (ThreadLocal { state, conf }, events)
+}
+
+/// parse the socket address.
+pub fn addr(a: &str) -> Arc<SocketAddr> {
+    Arc::new(a.parse().unwrap())
+}
+
+/// create a node of the test user.
+pub async fn node(state: &State, a: &str) -> Arc<SocketAddr> {
+    node_with(state, a, USERNAME).await
+}
+
+/// create a node of the user.
+pub async fn node_with(state: &State, a: &str, u: &str) -> Arc<SocketAddr> {
+    let a = addr(a);
+    state.get_key(&a, u).await.unwrap();
+    a
+}
+
+/// outbound message of the proto.
+pub struct Reply {
+    pub payload: Vec<u8>,
+    pub dest: SocketAddr,
+    pub via: Via,
+}
+
+impl Reply {
+    /// decode the stun message.
+    pub fn message(&self) -> MessageReader<'_> {
+        MessageReader::try_from(&self.payload[..]).unwrap()
+    }
+
+    /// the error code of the stun message.
+    pub fn code(&self) -> Option<u16> {
+        self.message()
+            .get::<ErrorCode>()
+            .map(|e| e.unwrap().code)
+    }
+}
+
+/// client of the test user.
+///
+/// the requests carry the username, the realm and the nonce 
+/// of the client address, and are signed with the long-term 
+/// key, as a client does after the 401 challenge, the nonce 
+/// issued to the client address is used unless it is set.
+pub struct Client {
+    pub addr: SocketAddr,
+    pub nonce: Option<String>,
+    proto: Proto,
+}
+
+impl Client {
+    pub fn new(local: &ThreadLocal, a: &str) -> Self {
+        Self {
+            addr: a.parse().unwrap(),
+            proto: Proto::builder(local.clone()),
+            nonce: None,
+        }
+    }
+
+    /// encode a signed request, the attributes 
+    /// of the request are appended by `f`.
+    #[rustfmt::skip]
+    pub async fn encode<F>(&self, kind: Kind, f: F) -> BytesMut
+    where
+        F: FnOnce(&mut MessageWriter<'_>)
+    {
+        let state = &self.proto.local.state;
+        let a = Arc::new(self.addr);
+        let realm = state.get_realm(&a).to_string();
+        let nonce = match &self.nonce {
+            Some(n) => n.clone(),
+            None => state.get_nonce(&a).await.to_string()
+        };
+
+        let key = long_key(USERNAME, PASSWORD, &realm);
+        let mut buf = BytesMut::with_capacity(1280);
+        let mut message = MessageWriter::new(kind, &mut buf);
+        message.append::<UserName>(USERNAME);
+        message.append::<Realm>(&realm);
+        message.append::<Nonce>(&nonce);
+        f(&mut message);
+        message.try_into(Some(&key)).unwrap();
+        buf
+    }
+
+    /// send a signed request.
+    pub async fn request<F>(&self, kind: Kind, f: F) -> Option<Reply>
+    where
+        F: FnOnce(&mut MessageWriter<'_>)
+    {
+        let buf = self.encode(kind, f).await;
+        self.send(&buf).await
+    }
+
+    /// allocate a relayed port, returns the relayed address.
+    pub async fn allocate(&self) -> SocketAddr {
+        self.request(Kind::AllocateRequest, |m| {
+            m.append::<ReqeestedTransport>(17);
+        })
+        .await
+        .unwrap()
+        .message()
+        .get::<XorRelayedAddress>()
+        .unwrap()
+        .unwrap()
+    }
+
+    /// create a permission for the peer.
+    pub async fn create_permission(&self, peer: SocketAddr) -> Reply {
+        self.request(Kind::CreatePermissionRequest, |m| {
+            m.append::<XorPeerAddress>(peer);
+        })
+        .await
+        .unwrap()
+    }
+
+    /// send a request without credentials.
+    pub async fn anonymous(&self, kind: Kind) -> Option<Reply> {
+        let mut buf = BytesMut::with_capacity(1280);
+        MessageWriter::new(kind, &mut buf)
+            .try_into(None)
+            .unwrap();
+        self.send(&buf).await
+    }
+
+    /// send the datagram to the proto.
+    pub async fn send(&self, b: &[u8]) -> Option<Reply> {
+        let mut w = BytesMut::with_capacity(1280);
+        let outbound = self.proto
+            .handler(b, &mut w, self.addr)
+            .await
+            .unwrap()?;
+        Some(Reply {
+            payload: outbound.payload.to_vec(),
+            dest: *outbound.dest,
+            via: outbound.via
+        })
+    }
+}
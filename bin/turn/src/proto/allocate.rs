@@ -1,8 +1,11 @@
 use anyhow::Result;
 use bytes::BytesMut;
-use super::{ 
+use super::{
     Context, 
-    Response
+    log_reject,
+    Response,
+    Outbound,
+    DEFAULT_LIFETIME
 };
 
 use std::{
@@ -27,12 +30,16 @@ use stun::attribute::{
     XorRelayedAddress,
     ResponseOrigin,
     Lifetime,
-    UserName
+    UserName,
+    EvenPort,
+    ReservationToken
 };
 
 use stun::attribute::ErrKind::{
+    BadRequest,
     Unauthorized,
-    ServerError
+    ServerError,
+    InsufficientCapacity
 };
 
 /// return allocate error response
@@ -44,12 +51,13 @@ async fn reject<'a>(
     e: ErrKind, 
 ) -> Result<Response<'a>> {
     let nonce = ctx.state.get_nonce(&ctx.addr).await;
+    log_reject(&ctx, &m, e);
     let mut pack = MessageWriter::derive(Kind::AllocateError, &m, w);
     pack.append::<ErrorCode>(Error::from(e));
-    pack.append::<Realm>(&ctx.conf.realm);
+    pack.append::<Realm>(ctx.state.get_realm(&ctx.addr));
     pack.append::<Nonce>(&nonce);
     pack.try_into(None)?;
-    Ok(Some((w, ctx.addr)))
+    Ok(Some(Outbound::client(w, ctx.addr)))
 }
 
 /// return allocate ok response
@@ -68,16 +76,22 @@ async fn resolve<'a>(
     m: &MessageReader<'a>,
     p: &[u8; 16],
     port: u16,
+    token: Option<u64>,
     w: &'a mut BytesMut,
 ) -> Result<Response<'a>> {
-    let alloc_addr = Arc::new(SocketAddr::new(ctx.conf.external.ip(), port));
+    let relay_ip = ctx.conf.get_relay_ip(&ctx.addr);
+    let alloc_addr = Arc::new(SocketAddr::new(relay_ip, port));
     let mut pack = MessageWriter::derive(Kind::AllocateResponse, m, w);
     pack.append::<XorRelayedAddress>(*alloc_addr.as_ref());
     pack.append::<XorMappedAddress>(*ctx.addr.as_ref());
     pack.append::<ResponseOrigin>(ctx.conf.external);
-    pack.append::<Lifetime>(600);
-    pack.try_into(Some(p))?;
-    Ok(Some((w, ctx.addr.clone())))
+    pack.append::<Lifetime>(DEFAULT_LIFETIME);
+    if let Some(t) = token {
+        pack.append::<ReservationToken>(t);
+    }
+
+    pack.try_into_with(Some(p), ctx.state.get_crypto())?;
+    Ok(Some(Outbound::client(w, ctx.addr.clone())))
 }
 
 /// process allocate request
@@ -96,6 +110,16 @@ async fn resolve<'a>(
 /// server SHOULD NOT allocate ports in the range 0 - 1023 (the Well-
 /// Known Port range) to discourage clients from using TURN to run
 /// standard services.
+///
+/// If the request contains both an EVEN-PORT and a RESERVATION-TOKEN
+/// attribute, the server rejects the request with a 400 (Bad Request).
+/// If the request contains a RESERVATION-TOKEN, the server uses the
+/// previously reserved transport address corresponding to the included
+/// token, if the token is not valid, the server rejects the request
+/// with a 508 (Insufficient Capacity).  If the request contains an
+/// EVEN-PORT attribute, the relayed transport address has an even port,
+/// and if the R bit is set, the next-higher port is reserved and the
+/// success response contains a RESERVATION-TOKEN.
 #[rustfmt::skip]
 pub async fn process<'a>(ctx: Context, m: MessageReader<'a>, w: &'a mut BytesMut) -> Result<Response<'a>> {
     let u = match m.get::<UserName>() {
@@ -112,9 +136,39 @@ pub async fn process<'a>(ctx: Context, m: MessageReader<'a>, w: &'a mut BytesMut
         Some(p) => p,
     };
 
-    let port = match ctx.state.alloc_port(&ctx.addr).await {
-        None => return reject(ctx, m, w, Unauthorized).await,
-        Some(p) => p,
+    if m.integrity_with(&key[..], ctx.state.get_crypto()).is_err() {
+        return reject(ctx, m, w, Unauthorized).await
+    }
+
+    let even_port = match m.get::<EvenPort>() {
+        Some(e) => Some(e?),
+        None => None
+    };
+
+    let reservation = match m.get::<ReservationToken>() {
+        Some(t) => Some(t?),
+        None => None
+    };
+
+    if even_port.is_some() && reservation.is_some() {
+        return reject(ctx, m, w, BadRequest).await
+    }
+
+    let (port, token) = if let Some(t) = reservation {
+        match ctx.state.claim_port(&ctx.addr, t).await {
+            None => return reject(ctx, m, w, InsufficientCapacity).await,
+            Some(p) => (p, None),
+        }
+    } else if let Some(r) = even_port {
+        match ctx.state.alloc_even_port(&ctx.addr, r).await {
+            None => return reject(ctx, m, w, InsufficientCapacity).await,
+            Some(p) => p,
+        }
+    } else {
+        match ctx.state.allocate(&ctx.addr).await {
+            None => return reject(ctx, m, w, Unauthorized).await,
+            Some(p) => (p, None),
+        }
     };
     
     log::info!(
@@ -124,8 +178,134 @@ pub async fn process<'a>(ctx: Context, m: MessageReader<'a>, w: &'a mut BytesMut
         port,
     );
 
-    match m.integrity(&key) {
-        Err(_) => reject(ctx, m, w, Unauthorized).await,
-        Ok(_) => resolve(&ctx, &m, &key, port, w).await,
+    if log::log_enabled!(log::Level::Debug) {
+        if let Some(allocation) = ctx.state.get_allocation(&ctx.addr).await {
+            log::debug!("allocation: {}", allocation);
+        }
+    }
+
+    resolve(&ctx, &m, &key, port, token, w).await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock;
+    use crate::proto::Via;
+    use crate::state::Event;
+    use stun::Kind;
+    use stun::attribute::{
+        ErrKind,
+        Error,
+        Realm,
+        Nonce,
+        ReqeestedTransport,
+        XorRelayedAddress,
+        EvenPort,
+        ReservationToken
+    };
+
+    #[tokio::test]
+    async fn allocate() {
+        let (local, mut events) = mock::local(&[]);
+        let client = mock::Client::new(&local, "127.0.0.1:8080");
+        let reply = client.request(Kind::AllocateRequest, |m| {
+            m.append::<ReqeestedTransport>(17);
+        }).await.unwrap();
+
+        let message = reply.message();
+        assert_eq!(message.kind, Kind::AllocateResponse);
+        assert_eq!(reply.via, Via::Client);
+        assert_eq!(reply.dest, client.addr);
+
+        let relayed = message.get::<XorRelayedAddress>().unwrap().unwrap();
+        assert_eq!(relayed.ip(), local.conf.external.ip());
+        assert_eq!(events.recv().await, Some(Event::Allocated { 
+            addr: mock::addr("127.0.0.1:8080"), 
+            group: 0, 
+            port: relayed.port() 
+        }));
+    }
+
+    #[tokio::test]
+    async fn challenge_realm() {
+        let (local, _) = mock::local(&["--realm", "localhost", "--realm-map", "127.0.0.2=tenant"]);
+        for (a, realm) in [("127.0.0.1:8080", "localhost"), ("127.0.0.2:8080", "tenant")] {
+            let client = mock::Client::new(&local, a);
+            let reply = client.anonymous(Kind::AllocateRequest).await.unwrap();
+            assert_eq!(reply.message().kind, Kind::AllocateError);
+            assert_eq!(reply.code(), Some(Error::from(ErrKind::Unauthorized).code));
+            assert_eq!(reply.message().get::<Realm>().unwrap().unwrap(), realm);
+            assert!(reply.message().get::<Nonce>().is_some());
+
+            // the key of the client is derived from its realm.
+            let reply = client.request(Kind::AllocateRequest, |m| {
+                m.append::<ReqeestedTransport>(17);
+            }).await.unwrap();
+            assert_eq!(reply.message().kind, Kind::AllocateResponse);
+        }
+    }
+
+    #[tokio::test]
+    async fn reserve_then_claim() {
+        let (local, _) = mock::local(&[]);
+        let client = mock::Client::new(&local, "127.0.0.1:8080");
+        let reply = client.request(Kind::AllocateRequest, |m| {
+            m.append::<ReqeestedTransport>(17);
+            m.append::<EvenPort>(true);
+        }).await.unwrap();
+
+        let message = reply.message();
+        let relayed = message.get::<XorRelayedAddress>().unwrap().unwrap();
+        let token = message.get::<ReservationToken>().unwrap().unwrap();
+        assert_eq!(relayed.port() % 2, 0);
+
+        let local = &local;
+        let claim = |a| async move {
+            mock::Client::new(local, a).request(Kind::AllocateRequest, |m| {
+                m.append::<ReqeestedTransport>(17);
+                m.append::<ReservationToken>(token);
+            }).await.unwrap()
+        };
+
+        let reply = claim("127.0.0.1:8081").await;
+        let claimed = reply.message().get::<XorRelayedAddress>().unwrap().unwrap();
+        assert_eq!(claimed.port(), relayed.port() + 1);
+        assert!(reply.message().get::<ReservationToken>().is_none());
+
+        // the token is used up.
+        let reply = claim("127.0.0.1:8082").await;
+        assert_eq!(reply.message().kind, Kind::AllocateError);
+        assert_eq!(reply.code(), Some(Error::from(ErrKind::InsufficientCapacity).code));
+    }
+
+    #[tokio::test]
+    async fn reject_bad_reservation() {
+        let (local, _) = mock::local(&[]);
+        let client = mock::Client::new(&local, "127.0.0.1:8080");
+        let reply = client.request(Kind::AllocateRequest, |m| {
+            m.append::<ReqeestedTransport>(17);
+            m.append::<ReservationToken>(1);
+        }).await.unwrap();
+        assert_eq!(reply.code(), Some(Error::from(ErrKind::InsufficientCapacity).code));
+
+        let reply = client.request(Kind::AllocateRequest, |m| {
+            m.append::<ReqeestedTransport>(17);
+            m.append::<EvenPort>(false);
+            m.append::<ReservationToken>(1);
+        }).await.unwrap();
+        assert_eq!(reply.code(), Some(Error::from(ErrKind::BadRequest).code));
+    }
+
+    #[tokio::test]
+    async fn relay_map() {
+        let (local, _) = mock::local(&[
+            "--external", "203.0.113.1:3478", 
+            "--relay-map", "10.0.0.0/8=10.0.0.1"
+        ]);
+
+        for (a, ip) in [("10.1.2.3:8080", "10.0.0.1"), ("127.0.0.1:8080", "203.0.113.1")] {
+            let client = mock::Client::new(&local, a);
+            assert_eq!(client.allocate().await.ip(), ip.parse::<std::net::IpAddr>().unwrap());
+        }
     }
 }
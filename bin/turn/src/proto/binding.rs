@@ -9,7 +9,7 @@ use stun::{
 use super::{
     Context, 
     Response,
-    SOFTWARE
+    Outbound
 };
 
 use stun::attribute::{
@@ -48,7 +48,7 @@ pub fn process<'a>(ctx: Context, payload: MessageReader, w: &'a mut BytesMut) ->
     pack.append::<XorMappedAddress>(*ctx.addr.as_ref());
     pack.append::<MappedAddress>(*ctx.addr.as_ref());
     pack.append::<ResponseOrigin>(ctx.conf.external);
-    pack.append::<Software>(SOFTWARE);
+    pack.append::<Software>(ctx.conf.get_software());
     pack.try_into(None)?;
-    Ok(Some((w, ctx.addr)))
+    Ok(Some(Outbound::client(w, ctx.addr)))
 }
@@ -2,7 +2,10 @@ use anyhow::Result;
 use bytes::BytesMut;
 use super::{
     Context, 
-    Response
+    log_reject,
+    Response,
+    Outbound,
+    ChannelBindError
 };
 
 use stun::{
@@ -24,7 +27,9 @@ use stun::attribute::{
 use stun::attribute::ErrKind::{
     BadRequest,
     Unauthorized,
+    Forbidden,
     InsufficientCapacity,
+    PeerAddressFamilyMismatch,
 };
 
 /// return channel binding error response
@@ -35,11 +40,12 @@ fn reject<'a>(
     w: &'a mut BytesMut,
     e: ErrKind, 
 ) -> Result<Response<'a>> {
+    log_reject(&ctx, &m, e);
     let mut pack = MessageWriter::derive(Kind::ChannelBindError, &m, w);
     pack.append::<ErrorCode>(Error::from(e));
-    pack.append::<Realm>(&ctx.conf.realm);
+    pack.append::<Realm>(ctx.state.get_realm(&ctx.addr));
     pack.try_into(None)?;
-    Ok(Some((w, ctx.addr)))
+    Ok(Some(Outbound::client(w, ctx.addr)))
 }
 
 /// return channel binding ok response
@@ -51,8 +57,8 @@ fn resolve<'a>(
     w: &'a mut BytesMut
 ) -> Result<Response<'a>> {
     MessageWriter::derive(Kind::ChannelBindResponse, m, w)
-        .try_into(Some(p))?;
-    Ok(Some((w, ctx.addr.clone())))
+        .try_into_with(Some(p), ctx.state.get_crypto())?;
+    Ok(Some(Outbound::client(w, ctx.addr.clone())))
 }
 
 /// process channel binding request
@@ -61,6 +67,11 @@ fn resolve<'a>(
 /// allowed in the XOR-PEER-ADDRESS attribute; if a value is not allowed,
 /// the server rejects the request with a 403 (Forbidden) error.
 ///
+/// If the XOR-PEER-ADDRESS attribute contains an address of an address
+/// family that is not the same as that of a relayed transport address
+/// for the allocation, the server MUST generate an error response with
+/// the 443 (Peer Address Family Mismatch) response code.
+///
 /// If the request is valid, but the server is unable to fulfill the
 /// request due to some capacity limit or similar, the server replies
 /// with a 508 (Insufficient Capacity) error.
@@ -97,8 +108,8 @@ pub async fn process<'a>(ctx: Context, m: MessageReader<'a>, w: &'a mut BytesMut
         _ => return reject(ctx, m, w, BadRequest),
     };
     
-    let p = match m.get::<XorPeerAddress>() {
-        Some(a) => a?.port(),
+    let peer = match m.get::<XorPeerAddress>() {
+        Some(a) => a?,
         _ => return reject(ctx, m, w, BadRequest)
     };
 
@@ -111,20 +122,148 @@ pub async fn process<'a>(ctx: Context, m: MessageReader<'a>, w: &'a mut BytesMut
         Some(a) => a,
     };
 
-    if m.integrity(&key).is_err() {
+    if m.integrity_with(&key[..], ctx.state.get_crypto()).is_err() {
         return reject(ctx, m, w, Unauthorized);
     }
-    
-    if ctx.state.bind_channel(&ctx.addr, p, c).await.is_none() {
-        return reject(ctx, m, w, InsufficientCapacity);
+
+    if peer.is_ipv4() != ctx.conf.get_relay_ip(&ctx.addr).is_ipv4() {
+        return reject(ctx, m, w, PeerAddressFamilyMismatch)
     }
+
+    if !ctx.conf.is_allowed_peer(&peer) {
+        return reject(ctx, m, w, Forbidden);
+    }
+    
+    let bind = match ctx.state.bind_channel(&ctx.addr, peer.port(), c).await {
+        Err(ChannelBindError::Conflict) => return reject(ctx, m, w, BadRequest),
+        Err(ChannelBindError::Unavailable) => return reject(ctx, m, w, InsufficientCapacity),
+        Ok(b) => b
+    };
     
     log::info!(
-        "{:?} [{:?}] bind channel={}", 
+        "{:?} [{:?}] bind channel={} {:?} expiry={:?}", 
         &ctx.addr,
         u,
-        c
+        c,
+        bind,
+        ctx.state.get_channel_expiry(&ctx.addr, c).await
     );
 
     resolve(&ctx, &m, &key, w)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+    use std::net::SocketAddr;
+
+    #[tokio::test]
+    async fn peer_family_mismatch() {
+        let (local, _) = mock::local(&[]);
+        let client = mock::Client::new(&local, "127.0.0.1:8080");
+        let peer = mock::Client::new(&local, "127.0.0.1:8081");
+        client.allocate().await;
+        let relayed = peer.allocate().await;
+
+        let v6 = SocketAddr::new("::1".parse().unwrap(), relayed.port());
+        let reply = client.request(Kind::ChannelBindRequest, |m| {
+            m.append::<ChannelNumber>(0x4000);
+            m.append::<XorPeerAddress>(v6);
+        }).await.unwrap();
+        assert_eq!(reply.message().kind, Kind::ChannelBindError);
+        assert_eq!(reply.code(), Some(Error::from(PeerAddressFamilyMismatch).code));
+
+        let reply = client.request(Kind::ChannelBindRequest, |m| {
+            m.append::<ChannelNumber>(0x4000);
+            m.append::<XorPeerAddress>(relayed);
+        }).await.unwrap();
+        assert_eq!(reply.message().kind, Kind::ChannelBindResponse);
+    }
+
+    #[tokio::test]
+    async fn forbidden_peer() {
+        let (local, _) = mock::local(&[]);
+        let client = mock::Client::new(&local, "127.0.0.1:8080");
+        client.allocate().await;
+
+        let reply = client.request(Kind::ChannelBindRequest, |m| {
+            m.append::<ChannelNumber>(0x4000);
+            m.append::<XorPeerAddress>("10.0.0.1:8080".parse().unwrap());
+        }).await.unwrap();
+        assert_eq!(reply.message().kind, Kind::ChannelBindError);
+        assert_eq!(reply.code(), Some(Error::from(Forbidden).code));
+    }
+
+    #[tokio::test]
+    async fn rebind_conflict() {
+        let (local, _) = mock::local(&[]);
+        let client = mock::Client::new(&local, "127.0.0.1:8080");
+        let peer = mock::Client::new(&local, "127.0.0.1:8081");
+        let other = mock::Client::new(&local, "127.0.0.1:8082");
+        client.allocate().await;
+        let peer_relayed = peer.allocate().await;
+        let other_relayed = other.allocate().await;
+
+        let bind = |c: u16, p: SocketAddr| client.request(Kind::ChannelBindRequest, move |m| {
+            m.append::<ChannelNumber>(c);
+            m.append::<XorPeerAddress>(p);
+        });
+
+        assert_eq!(bind(0x4000, peer_relayed).await.unwrap().message().kind, Kind::ChannelBindResponse);
+        assert_eq!(bind(0x4000, peer_relayed).await.unwrap().message().kind, Kind::ChannelBindResponse);
+        for (c, p) in [(0x4000, other_relayed), (0x4001, peer_relayed), (0x3FFF, other_relayed)] {
+            let reply = bind(c, p).await.unwrap();
+            assert_eq!(reply.message().kind, Kind::ChannelBindError);
+            assert_eq!(reply.code(), Some(Error::from(BadRequest).code));
+        }
+    }
+
+    #[tokio::test]
+    async fn log_reject() {
+        mock::capture();
+        let (local, _) = mock::local(&[]);
+        let client = mock::Client::new(&local, "127.0.0.1:46401");
+        client.allocate().await;
+
+        client.anonymous(Kind::ChannelBindRequest).await.unwrap();
+        let logs = mock::logs("127.0.0.1:46401 [None] reject ChannelBindRequest: code=401");
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].0, log::Level::Debug);
+
+        client.request(Kind::ChannelBindRequest, |m| {
+            m.append::<ChannelNumber>(0x3000);
+            m.append::<XorPeerAddress>(client.addr);
+        }).await.unwrap();
+        let logs = mock::logs(&format!(
+            "127.0.0.1:46401 [Some({:?})] reject ChannelBindRequest: code=400",
+            mock::USERNAME
+        ));
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].0, log::Level::Warn);
+    }
+
+    #[tokio::test]
+    async fn relay_family() {
+        let (local, _) = mock::local(&["--relay-map", "::/0=::1"]);
+        let client = mock::Client::new(&local, "[::1]:8080");
+        let peer = mock::Client::new(&local, "[::1]:8081");
+        assert!(client.allocate().await.is_ipv6());
+        let relayed = peer.allocate().await;
+        assert_eq!(relayed.ip(), "::1".parse::<std::net::IpAddr>().unwrap());
+
+        // the family of the peer is checked against the relay address.
+        let v4 = SocketAddr::new(local.conf.external.ip(), relayed.port());
+        let reply = client.request(Kind::ChannelBindRequest, |m| {
+            m.append::<ChannelNumber>(0x4000);
+            m.append::<XorPeerAddress>(v4);
+        }).await.unwrap();
+        assert_eq!(reply.code(), Some(Error::from(PeerAddressFamilyMismatch).code));
+
+        let reply = client.request(Kind::ChannelBindRequest, |m| {
+            m.append::<ChannelNumber>(0x4000);
+            m.append::<XorPeerAddress>(relayed);
+        }).await.unwrap();
+        assert_eq!(reply.message().kind, Kind::ChannelBindResponse);
+    }
+}
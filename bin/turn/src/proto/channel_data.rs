@@ -1,7 +1,8 @@
 use stun::ChannelData;
 use super::{
     Context, 
-    Response
+    Response,
+    Outbound
 };
 
 /// process channel data
@@ -19,27 +20,100 @@ use super::{
 /// application data to the peer by forming a UDP datagram as follows:
 ///
 /// * the source transport address is the relayed transport address of
-/// the allocation, where the allocation is determined by the 5-tuple
-/// on which the ChannelData message arrived;
+///   the allocation, where the allocation is determined by the 5-tuple
+///   on which the ChannelData message arrived;
 ///
 /// * the destination transport address is the transport address to
-/// which the channel is bound;
+///   which the channel is bound;
 ///
 /// * the data following the UDP header is the contents of the data
-/// field of the ChannelData message.
+///   field of the ChannelData message.
 ///
 /// The resulting UDP datagram is then sent to the peer.  Note that if
 /// the Length field in the ChannelData message is 0, then there will be
 /// no data in the UDP datagram, but the UDP datagram is still formed and
 /// sent [(Section 4.1 of [RFC6263])](https://tools.ietf.org/html/rfc6263#section-4.1).
+///
+/// the peer is also a client of the current server, so the 
+/// message is relayed as ChannelData, without the trailing 
+/// bytes of the received datagram.
+///
+/// the message is forwarded only if the peer has a permission 
+/// or a channel binding towards the client, unsolicited data 
+/// is silently discarded.
 #[rustfmt::skip]
 pub async fn process(ctx: Context, data: ChannelData<'_>) -> Response<'_> {
     let n = data.number;
-    Some((
-        data.buf,
-        match ctx.state.get_channel_bond(&ctx.addr, n).await {
-            Some(x) => x,
-            None => return None,
-        },
-    ))
+    let peer = ctx.state.get_channel_bond(&ctx.addr, n).await?;
+    if !ctx.state.is_permitted(&ctx.addr, &peer, n).await {
+        return None
+    }
+
+    ctx.state.add_relayed(&ctx.addr, &peer, data.body.len()).await;
+    Some(Outbound::relay(data.buf, peer))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock;
+    use crate::proto::Via;
+    use stun::Kind;
+    use stun::attribute::{
+        ChannelNumber,
+        XorPeerAddress
+    };
+
+    /// a ChannelData message of the channel number.
+    fn channel_data(number: u16) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(9);
+        buf.extend_from_slice(&number.to_be_bytes());
+        buf.extend_from_slice(&5u16.to_be_bytes());
+        buf.extend_from_slice(b"hello");
+        buf
+    }
+
+    #[tokio::test]
+    async fn drop_unpermitted() {
+        let (local, _) = mock::local(&[]);
+        let client = mock::Client::new(&local, "127.0.0.1:8080");
+        let peer = mock::Client::new(&local, "127.0.0.1:8081");
+        let relayed = client.allocate().await;
+        let peer_relayed = peer.allocate().await;
+        let reply = client.request(Kind::ChannelBindRequest, |m| {
+            m.append::<ChannelNumber>(0x4000);
+            m.append::<XorPeerAddress>(peer_relayed);
+        }).await.unwrap();
+        assert_eq!(reply.message().kind, Kind::ChannelBindResponse);
+
+        // the peer has no permission for the client.
+        assert!(client.send(&channel_data(0x4000)).await.is_none());
+        assert!(client.send(&channel_data(0x4001)).await.is_none());
+
+        peer.create_permission(relayed).await;
+        let reply = client.send(&channel_data(0x4000)).await.unwrap();
+        assert_eq!(reply.via, Via::Relay);
+        assert_eq!(reply.dest, peer.addr);
+        assert_eq!(reply.payload, channel_data(0x4000));
+    }
+
+    #[tokio::test]
+    async fn forward_bound_channel() {
+        let (local, _) = mock::local(&[]);
+        let client = mock::Client::new(&local, "127.0.0.1:8080");
+        let peer = mock::Client::new(&local, "127.0.0.1:8081");
+        let relayed = client.allocate().await;
+        let peer_relayed = peer.allocate().await;
+        for (c, p) in [(&client, peer_relayed), (&peer, relayed)] {
+            c.request(Kind::ChannelBindRequest, |m| {
+                m.append::<ChannelNumber>(0x4000);
+                m.append::<XorPeerAddress>(p);
+            }).await.unwrap();
+        }
+
+        // a channel binding of the peer to the client is a permission.
+        let reply = client.send(&channel_data(0x4000)).await.unwrap();
+        assert_eq!(reply.dest, peer.addr);
+        let reply = peer.send(&channel_data(0x4000)).await.unwrap();
+        assert_eq!(reply.dest, client.addr);
+    }
 }
@@ -2,7 +2,9 @@ use anyhow::Result;
 use bytes::BytesMut;
 use super::{
     Context, 
-    Response
+    log_reject,
+    Response,
+    Outbound
 };
 
 use stun::{
@@ -23,6 +25,7 @@ use stun::attribute::{
 use stun::attribute::ErrKind::{
     BadRequest,
     Unauthorized,
+    Forbidden,
     AllocationMismatch,
 };
 
@@ -34,11 +37,12 @@ fn reject<'a>(
     w: &'a mut BytesMut,
     e: ErrKind,
 ) -> Result<Response<'a>> {
+    log_reject(&ctx, &m, e);
     let mut pack = MessageWriter::derive(Kind::CreatePermissionError, &m, w);
     pack.append::<ErrorCode>(Error::from(e));
-    pack.append::<Realm>(&ctx.conf.realm);
+    pack.append::<Realm>(ctx.state.get_realm(&ctx.addr));
     pack.try_into(None)?;
-    Ok(Some((w, ctx.addr)))
+    Ok(Some(Outbound::client(w, ctx.addr)))
 }
 
 /// return create permission ok response
@@ -50,8 +54,8 @@ fn resolve<'a>(
     w: &'a mut BytesMut
 ) -> Result<Response<'a>> {
     MessageWriter::derive(Kind::CreatePermissionResponse, m, w)
-        .try_into(Some(p))?;
-    Ok(Some((w, ctx.addr.clone())))
+        .try_into_with(Some(p), ctx.state.get_crypto())?;
+    Ok(Some(Outbound::client(w, ctx.addr.clone())))
 }
 
 /// process create permission request
@@ -89,9 +93,9 @@ fn resolve<'a>(
 /// There are no mandatory attributes in the success response.
 ///
 /// > NOTE: A server need not do anything special to implement
-/// idempotency of CreatePermission requests over UDP using the
-/// "stateless stack approach".  Retransmitted CreatePermission
-/// requests will simply refresh the permissions.
+/// > idempotency of CreatePermission requests over UDP using the
+/// > "stateless stack approach".  Retransmitted CreatePermission
+/// > requests will simply refresh the permissions.
 #[rustfmt::skip]
 pub async fn process<'a>(ctx: Context, m: MessageReader<'a>, w: &'a mut BytesMut) -> Result<Response<'a>> {
     let u = match m.get::<UserName>() {
@@ -99,8 +103,8 @@ pub async fn process<'a>(ctx: Context, m: MessageReader<'a>, w: &'a mut BytesMut
         _ => return reject(ctx, m, w, Unauthorized),
     };
 
-    let p = match m.get::<XorPeerAddress>() {
-        Some(a) => a?.port(),
+    let peer = match m.get::<XorPeerAddress>() {
+        Some(a) => a?,
         _ => return reject(ctx, m, w, BadRequest)
     };
 
@@ -109,10 +113,15 @@ pub async fn process<'a>(ctx: Context, m: MessageReader<'a>, w: &'a mut BytesMut
         Some(a) => a,
     };
 
-    if m.integrity(&key).is_err() {
+    if m.integrity_with(&key[..], ctx.state.get_crypto()).is_err() {
         return reject(ctx, m, w, Unauthorized);
     }
 
+    if !ctx.conf.is_allowed_peer(&peer) {
+        return reject(ctx, m, w, Forbidden);
+    }
+
+    let p = peer.port();
     if ctx.state.bind_port(&ctx.addr, p).await.is_none() {
         return reject(ctx, m, w, AllocationMismatch);
     }
@@ -126,3 +135,29 @@ pub async fn process<'a>(ctx: Context, m: MessageReader<'a>, w: &'a mut BytesMut
 
     resolve(&ctx, &m, &key, w)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+
+    #[tokio::test]
+    async fn forbidden_peer() {
+        let (local, _) = mock::local(&[]);
+        let client = mock::Client::new(&local, "127.0.0.1:8080");
+        let peer = mock::Client::new(&local, "127.0.0.1:8081");
+        client.allocate().await;
+        let relayed = peer.allocate().await;
+
+        let reply = client.create_permission("10.0.0.1:8080".parse().unwrap()).await;
+        assert_eq!(reply.message().kind, Kind::CreatePermissionError);
+        assert_eq!(reply.code(), Some(Error::from(Forbidden).code));
+
+        // a public peer passes the filter, but it is not an allocation.
+        let reply = client.create_permission("1.1.1.1:8080".parse().unwrap()).await;
+        assert_eq!(reply.code(), Some(Error::from(AllocationMismatch).code));
+
+        let reply = client.create_permission(relayed).await;
+        assert_eq!(reply.message().kind, Kind::CreatePermissionResponse);
+    }
+}
@@ -7,7 +7,8 @@ use std::{
 
 use super::{
     Context, 
-    Response
+    Response,
+    Outbound
 };
 
 use stun::{ 
@@ -51,14 +52,14 @@ use stun::attribute::{
 /// If everything is OK, then the server forms a UDP datagram as follows:
 /// 
 /// *  the source transport address is the relayed transport address of
-/// the allocation, where the allocation is determined by the 5-tuple
-/// on which the Send indication arrived;
+///    the allocation, where the allocation is determined by the 5-tuple
+///    on which the Send indication arrived;
 /// 
 /// *  the destination transport address is taken from the XOR-PEER-
-/// ADDRESS attribute;
+///    ADDRESS attribute;
 /// 
 /// *  the data following the UDP header is the contents of the value
-/// field of the DATA attribute.
+///    field of the DATA attribute.
 /// 
 /// The handling of the DONT-FRAGMENT attribute (if present), is
 /// described in Sections [14](https://tools.ietf.org/html/rfc8656#section-14) 
@@ -67,17 +68,22 @@ use stun::attribute::{
 /// The resulting UDP datagram is then sent to the peer.
 #[rustfmt::skip]
 pub async fn process<'a>(ctx: Context, m: MessageReader<'a>, w: &'a mut BytesMut) -> Result<Response<'a>> {
-    let pp = match m.get::<XorPeerAddress>() {
-        Some(x) => x?.port(),
+    let peer = match m.get::<XorPeerAddress>() {
+        Some(x) => x?,
         _ => return Ok(None),
     };
+
+    if !ctx.conf.is_allowed_peer(&peer) {
+        return Ok(None)
+    }
+
     
     let d = match m.get::<Data>() {
         Some(x) => x?,
         _ => return Ok(None),
     };
 
-    let a = match ctx.state.get_port_bond(&ctx.addr, pp).await {
+    let a = match ctx.state.get_port_bond(&ctx.addr, peer.port()).await {
         None => return Ok(None),
         Some(a) => a,
     };
@@ -87,10 +93,38 @@ pub async fn process<'a>(ctx: Context, m: MessageReader<'a>, w: &'a mut BytesMut
         Some(p) => p,
     };
 
-    let s = Arc::new(SocketAddr::new(ctx.conf.external.ip(), p));
+    ctx.state.add_relayed(&ctx.addr, &a, d.len()).await;
+    let s = Arc::new(SocketAddr::new(ctx.conf.get_relay_ip(&ctx.addr), p));
     let mut pack = MessageWriter::derive(Kind::DataIndication, &m, w);
     pack.append::<XorPeerAddress>(*s.as_ref());
     pack.append::<Data>(d);
     pack.try_into(None)?;
-    Ok(Some((w, a)))
+    Ok(Some(Outbound::relay(w, a)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+
+    #[tokio::test]
+    async fn drop_forbidden_peer() {
+        let (local, _) = mock::local(&[]);
+        let client = mock::Client::new(&local, "127.0.0.1:8080");
+        let peer = mock::Client::new(&local, "127.0.0.1:8081");
+        let relayed = client.allocate().await;
+        let peer_relayed = peer.allocate().await;
+        peer.create_permission(relayed).await;
+
+        let send = |peer: SocketAddr| client.request(Kind::SendIndication, move |m| {
+            m.append::<XorPeerAddress>(peer);
+            m.append::<Data>(b"hello");
+        });
+
+        // the port of the peer is an allocation, 
+        // but the address is not allowed.
+        let forbidden = SocketAddr::new("10.0.0.1".parse().unwrap(), peer_relayed.port());
+        assert!(send(forbidden).await.is_none());
+        assert!(send(peer_relayed).await.is_some());
+    }
 }
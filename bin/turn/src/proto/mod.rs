@@ -10,7 +10,11 @@ use anyhow::Result;
 use bytes::BytesMut;
 use super::{
     argv::Argv,
-    state::State,
+    state::{
+        State,
+        ChannelBindError,
+        DEFAULT_LIFETIME
+    },
     server::ThreadLocal
 };
 
@@ -26,18 +30,72 @@ use stun::{
     MessageReader as Message,
 };
 
-#[rustfmt::skip]
-static SOFTWARE: &str = concat!(
-    env!("CARGO_PKG_NAME"), 
-    "-",
-    env!("CARGO_PKG_VERSION")
-);
+use stun::attribute::{
+    ErrKind,
+    Error,
+    UserName
+};
 
-#[rustfmt::skip]
-pub(crate) type Response<'a> = Option<(
-    &'a [u8],
-    Arc<SocketAddr>
-)>;
+/// outbound message path.
+#[derive(Debug, PartialEq, Eq)]
+#[derive(Copy, Clone)]
+pub enum Via {
+    /// reply to the client that sent the request.
+    Client,
+    /// relay to the peer of the client.
+    Relay,
+}
+
+/// outbound message.
+///
+/// the payload, the destination address and the path 
+/// of the message, the path tells the server whether the 
+/// message is a reply or relayed data.
+pub struct Outbound<'a> {
+    pub payload: &'a [u8],
+    pub dest: Arc<SocketAddr>,
+    pub via: Via,
+}
+
+pub(crate) type Response<'a> = Option<Outbound<'a>>;
+
+impl<'a> Outbound<'a> {
+    /// create a reply to the client.
+    ///
+    /// ```no_run
+    /// use std::net::SocketAddr;
+    /// use std::sync::Arc;
+    ///
+    /// let addr = Arc::new("127.0.0.1:8080".parse::<SocketAddr>().unwrap());
+    /// let outbound = Outbound::client(&[], addr);
+    /// assert_eq!(outbound.via, Via::Client);
+    /// ```
+    pub fn client(payload: &'a [u8], dest: Arc<SocketAddr>) -> Self {
+        Self {
+            via: Via::Client,
+            payload,
+            dest
+        }
+    }
+
+    /// create a relay to the peer.
+    ///
+    /// ```no_run
+    /// use std::net::SocketAddr;
+    /// use std::sync::Arc;
+    ///
+    /// let peer = Arc::new("127.0.0.1:8081".parse::<SocketAddr>().unwrap());
+    /// let outbound = Outbound::relay(&[], peer);
+    /// assert_eq!(outbound.via, Via::Relay);
+    /// ```
+    pub fn relay(payload: &'a [u8], dest: Arc<SocketAddr>) -> Self {
+        Self {
+            via: Via::Relay,
+            payload,
+            dest
+        }
+    }
+}
 
 /// message context
 pub struct Context {
@@ -46,6 +104,38 @@ pub struct Context {
     pub addr: Arc<SocketAddr>,
 }
 
+/// log a rejected request.
+///
+/// the reason code, the source address and the username of 
+/// the request are logged at warn level, which tells why a 
+/// client is turned away. the 401 challenge of a request 
+/// without a username is a normal step of the authentication, 
+/// it is logged at debug level.
+///
+/// ```no_run
+/// // WARN 127.0.0.1:8080 [Some("panda")] reject ChannelBindRequest: code=401 reason="Unauthorized"
+/// log_reject(&ctx, &m, ErrKind::Unauthorized);
+/// ```
+#[rustfmt::skip]
+pub fn log_reject(ctx: &Context, m: &Message, e: ErrKind) {
+    let username = m.get::<UserName>().and_then(|u| u.ok());
+    let level = match (username, e) {
+        (None, ErrKind::Unauthorized) => log::Level::Debug,
+        _ => log::Level::Warn
+    };
+
+    let error = Error::from(e);
+    log::log!(
+        level,
+        "{} [{:?}] reject {:?}: code={} reason={:?}",
+        &ctx.addr,
+        username,
+        m.kind,
+        error.code,
+        error.message
+    );
+}
+
 /// process udp message 
 /// and return message + address.
 pub struct Proto {
@@ -299,6 +389,12 @@ impl Proto {
     #[rustfmt::skip]
     #[inline(always)]
     async fn message_process<'a>(ctx: Context, m: Message<'a>, w: &'a mut BytesMut) -> Result<Response<'a>> {
+        log::trace!("{:?} {}", &ctx.addr, m);
+        if ctx.conf.require_fingerprint && m.assert_fingerprint_with(ctx.state.get_crypto()).is_err() {
+            log::debug!("fingerprint check failed: addr={:?}", &ctx.addr);
+            return Ok(None)
+        }
+
         match m.kind {
             Kind::BindingRequest => binding::process(ctx, m, w),
             Kind::AllocateRequest => allocate::process(ctx, m, w).await,
@@ -319,3 +415,104 @@ impl Proto {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+    use stun::attribute::{
+        ChannelNumber,
+        XorPeerAddress,
+        Data
+    };
+
+    #[test]
+    fn outbound_via() {
+        let addr = Arc::new("127.0.0.1:8080".parse::<SocketAddr>().unwrap());
+        assert_eq!(Outbound::client(&[], addr.clone()).via, Via::Client);
+        assert_eq!(Outbound::relay(&[], addr).via, Via::Relay);
+    }
+
+    #[tokio::test]
+    async fn relay_indication() {
+        let (local, _) = mock::local(&[]);
+        let client = mock::Client::new(&local, "127.0.0.1:8080");
+        let peer = mock::Client::new(&local, "127.0.0.1:8081");
+        let relayed = client.allocate().await;
+        let peer_relayed = peer.allocate().await;
+
+        let reply = peer.create_permission(relayed).await;
+        assert_eq!(reply.message().kind, Kind::CreatePermissionResponse);
+        assert_eq!(reply.via, Via::Client);
+        assert_eq!(reply.dest, peer.addr);
+
+        let reply = client.request(Kind::SendIndication, |m| {
+            m.append::<XorPeerAddress>(peer_relayed);
+            m.append::<Data>(b"hello");
+        }).await.unwrap();
+        assert_eq!(reply.via, Via::Relay);
+        assert_eq!(reply.dest, peer.addr);
+
+        let message = reply.message();
+        assert_eq!(message.kind, Kind::DataIndication);
+        assert_eq!(message.get::<XorPeerAddress>().unwrap().unwrap(), relayed);
+        assert_eq!(message.get::<Data>().unwrap().unwrap(), b"hello");
+    }
+
+    #[tokio::test]
+    async fn require_fingerprint() {
+        let (local, _) = mock::local(&["--require-fingerprint"]);
+        let client = mock::Client::new(&local, "127.0.0.1:8080");
+
+        let mut buf = client.encode(Kind::BindingRequest, |_| ()).await;
+        let reply = client.send(&buf).await.unwrap();
+        assert_eq!(reply.message().kind, Kind::BindingResponse);
+
+        // a wrong fingerprint or no fingerprint at all is dropped.
+        let last = buf.len() - 1;
+        buf[last] ^= 0xFF;
+        assert!(client.send(&buf).await.is_none());
+        assert!(client.anonymous(Kind::BindingRequest).await.is_none());
+
+        let (local, _) = mock::local(&[]);
+        let client = mock::Client::new(&local, "127.0.0.1:8080");
+        let reply = client.anonymous(Kind::BindingRequest).await.unwrap();
+        assert_eq!(reply.message().kind, Kind::BindingResponse);
+    }
+
+    #[tokio::test]
+    async fn distinct_source_port() {
+        let (local, _) = mock::local(&[]);
+        let client = mock::Client::new(&local, "127.0.0.1:8080");
+        let peer = mock::Client::new(&local, "127.0.0.1:8081");
+        let other = mock::Client::new(&local, "127.0.0.1:9090");
+        let relayed = client.allocate().await;
+        let peer_relayed = peer.allocate().await;
+        peer.create_permission(relayed).await;
+        let reply = client.request(Kind::ChannelBindRequest, |m| {
+            m.append::<ChannelNumber>(0x4000);
+            m.append::<XorPeerAddress>(peer_relayed);
+        }).await.unwrap();
+        assert_eq!(reply.message().kind, Kind::ChannelBindResponse);
+
+        // the same ip with another source port is another client.
+        let state = &local.state;
+        let a = Arc::new(client.addr);
+        let b = Arc::new(other.addr);
+        assert!(state.get_allocation(&b).await.is_none());
+        assert_ne!(state.get_nonce(&a).await, state.get_nonce(&b).await);
+
+        let mut data = vec![0x40, 0x00, 0x00, 0x05];
+        data.extend_from_slice(b"hello");
+        assert!(other.send(&data).await.is_none());
+        for kind in [Kind::ChannelBindRequest, Kind::RefreshRequest, Kind::CreatePermissionRequest] {
+            let reply = other.anonymous(kind).await.unwrap();
+            assert_eq!(reply.code(), Some(Error::from(ErrKind::Unauthorized).code));
+        }
+
+        let allocation = state.get_allocation(&a).await.unwrap();
+        assert_eq!(allocation.channels.len(), 1);
+        assert_eq!(allocation.ports.len(), 1);
+        assert_eq!(client.send(&data).await.unwrap().dest, peer.addr);
+    }
+}
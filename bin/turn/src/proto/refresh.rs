@@ -2,7 +2,10 @@ use bytes::BytesMut;
 use anyhow::Result;
 use super::{
     Context, 
-    Response
+    log_reject,
+    Response,
+    Outbound,
+    DEFAULT_LIFETIME
 };
 
 use stun::{
@@ -17,7 +20,8 @@ use stun::attribute::{
     Error,
     ErrorCode,
     Lifetime,
-    UserName
+    UserName,
+    Nonce
 };
 
 /// return refresh error response
@@ -28,10 +32,11 @@ fn reject<'a>(
     w: &'a mut BytesMut, 
     e: ErrKind
 ) -> Result<Response<'a>> {
+    log_reject(&ctx, &m, e);
     let mut pack = MessageWriter::derive(Kind::RefreshError, &m, w);
     pack.append::<ErrorCode>(Error::from(e));
     pack.try_into(None)?;
-    Ok(Some((w, ctx.addr)))
+    Ok(Some(Outbound::client(w, ctx.addr)))
 }
 
 /// return refresh ok response
@@ -45,8 +50,8 @@ pub fn resolve<'a>(
 ) -> Result<Response<'a>> {
     let mut pack = MessageWriter::derive(Kind::RefreshResponse, m , w);
     pack.append::<Lifetime>(lifetime);
-    pack.try_into(Some(p))?;
-    Ok(Some((w, ctx.addr.clone())))
+    pack.try_into_with(Some(p), ctx.state.get_crypto())?;
+    Ok(Some(Outbound::client(w, ctx.addr.clone())))
 }
 
 /// process refresh request
@@ -68,17 +73,17 @@ pub fn resolve<'a>(
 /// Subsequent processing depends on the "desired lifetime" value:
 ///
 /// * If the "desired lifetime" is zero, then the request succeeds and
-/// the allocation is deleted.
+///   the allocation is deleted.
 ///
 /// * If the "desired lifetime" is non-zero, then the request succeeds
-/// and the allocation's time-to-expiry is set to the "desired
-/// lifetime".
+///   and the allocation's time-to-expiry is set to the "desired
+///   lifetime".
 ///
 /// If the request succeeds, then the server sends a success response
 /// containing:
 ///
 /// * A LIFETIME attribute containing the current value of the time-to-
-/// expiry timer.
+///   expiry timer.
 ///
 /// NOTE: A server need not do anything special to implement
 /// idempotency of Refresh requests over UDP using the "stateless
@@ -97,7 +102,7 @@ pub async fn process<'a>(ctx: Context, m: MessageReader<'a>, w: &'a mut BytesMut
 
     let l = match m.get::<Lifetime>() {
         Some(l) => l?,
-        _ => 600,
+        _ => DEFAULT_LIFETIME,
     };
 
     let key = match ctx.state.get_key(&ctx.addr, u).await {
@@ -105,17 +110,78 @@ pub async fn process<'a>(ctx: Context, m: MessageReader<'a>, w: &'a mut BytesMut
         Some(a) => a,
     };
 
-    if m.integrity(&key).is_err() {
+    if m.integrity_with(&key[..], ctx.state.get_crypto()).is_err() {
         return reject(ctx, m, w, Unauthorized);
     }
     
+    let nonce = match m.get::<Nonce>() {
+        Some(n) => n?,
+        _ => ""
+    };
+
+    if let Some(old) = ctx.state.migrate(&ctx.addr, u, nonce).await {
+        log::info!(
+            "{:?} [{:?}] migrate from={:?}", 
+            &ctx.addr,
+            u,
+            old,
+        );
+    }
+
+    let lifetime = ctx.state.refresh(&ctx.addr, l).await;
     log::info!(
-        "{:?} [{:?}] refresh timeout={}", 
+        "{:?} [{:?}] refresh timeout={} granted={}", 
         &ctx.addr,
         u,
         l,
+        lifetime,
     );
 
-    ctx.state.refresh(&ctx.addr, l).await;
-    resolve(&ctx, &m, l, &key, w)
+    resolve(&ctx, &m, lifetime, &key, w)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+
+    #[tokio::test]
+    async fn migrate() {
+        let (local, _) = mock::local(&[]);
+        let client = mock::Client::new(&local, "127.0.0.1:8080");
+        let relayed = client.allocate().await;
+
+        // a request with a nonce that was not issued 
+        // to the address does not move the allocation.
+        let mut roamed = mock::Client::new(&local, "127.0.0.1:9090");
+        roamed.nonce = Some(local.state.get_nonce(&mock::addr("127.0.0.1:8080")).await.to_string());
+        let reply = roamed.request(Kind::RefreshRequest, |_| ()).await.unwrap();
+        assert_eq!(reply.message().kind, Kind::RefreshResponse);
+        assert!(local.state.get_allocation(&mock::addr("127.0.0.1:8080")).await.is_some());
+
+        roamed.nonce = None;
+        let reply = roamed.request(Kind::RefreshRequest, |_| ()).await.unwrap();
+        assert_eq!(reply.message().kind, Kind::RefreshResponse);
+        assert!(local.state.get_allocation(&mock::addr("127.0.0.1:8080")).await.is_none());
+
+        let allocation = local.state.get_allocation(&mock::addr("127.0.0.1:9090")).await.unwrap();
+        assert_eq!(allocation.ports, vec![relayed.port()]);
+    }
+
+    #[tokio::test]
+    async fn granted_lifetime() {
+        let (local, _) = mock::local(&[]);
+        let client = mock::Client::new(&local, "127.0.0.1:8080");
+        client.allocate().await;
+
+        for (requested, granted) in [(86400, 3600), (1200, 1200), (60, 600), (0, 0)] {
+            let reply = client.request(Kind::RefreshRequest, |m| {
+                m.append::<Lifetime>(requested);
+            }).await.unwrap();
+            assert_eq!(reply.message().kind, Kind::RefreshResponse);
+            assert_eq!(reply.message().get::<Lifetime>().unwrap().unwrap(), granted);
+        }
+
+        assert!(local.state.get_allocation(&mock::addr("127.0.0.1:8080")).await.is_none());
+    }
 }
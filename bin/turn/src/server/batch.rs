@@ -0,0 +1,548 @@
+use super::retry::Retry;
+use tokio::net::UdpSocket;
+use std::{
+    net::SocketAddr,
+    io
+};
+
+/// the maximum number of datagrams sent by a single
+/// syscall, this is the limit of sendmmsg on linux.
+pub const MAX_BATCH_SIZE: usize = 1024;
+
+/// outbound datagram batch.
+///
+/// the datagrams handled in one burst of reads are queued
+/// and sent together, on linux a single sendmmsg sends the
+/// whole batch, otherwise every datagram is sent by itself.
+/// the buffers are reused between batches.
+pub struct Batch {
+    buffers: Vec<Vec<u8>>,
+    addrs: Vec<SocketAddr>,
+}
+
+impl Batch {
+    /// create batch.
+    ///
+    /// the capacity is clamped to 1..=1024.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let batch = Batch::new(64);
+    /// assert!(batch.is_empty());
+    /// ```
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.clamp(1, MAX_BATCH_SIZE);
+        Self {
+            buffers: Vec::with_capacity(capacity),
+            addrs: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// whether the batch has no queued datagram.
+    pub fn is_empty(&self) -> bool {
+        self.addrs.is_empty()
+    }
+
+    /// queue the datagram, the payload is copied.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let mut batch = Batch::new(1);
+    /// batch.push(b"hello", "127.0.0.1:8080".parse().unwrap());
+    /// assert!(!batch.is_empty());
+    /// ```
+    pub fn push(&mut self, payload: &[u8], addr: SocketAddr) {
+        let index = self.addrs.len();
+        if index == self.buffers.len() {
+            self.buffers.push(Vec::with_capacity(payload.len()));
+        }
+
+        let buffer = &mut self.buffers[index];
+        buffer.clear();
+        buffer.extend_from_slice(payload);
+        self.addrs.push(addr);
+    }
+
+    /// send the queued datagrams.
+    ///
+    /// returns the number of syscalls used to send the batch,
+    /// the batch is empty afterwards. a datagram that fails to 
+    /// send is retried with the retry policy, and skipped when 
+    /// it is dropped, the rest of the batch is still sent, only 
+    /// the dropped datagrams are counted.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let socket = UdpSocket::bind("127.0.0.1:0").await?;
+    /// let addr = socket.local_addr()?;
+    /// let retry = Retry::new(3);
+    /// let mut batch = Batch::new(64);
+    /// for _ in 0..64 {
+    ///     batch.push(b"hello", addr);
+    /// }
+    ///
+    /// let syscalls = batch.flush(&socket, &retry).await;
+    /// assert!(batch.is_empty());
+    /// assert_eq!(retry.dropped(), 0);
+    /// if cfg!(target_os = "linux") {
+    ///     assert!(syscalls < 64);
+    /// }
+    /// ```
+    pub async fn flush(&mut self, socket: &UdpSocket, retry: &Retry) -> usize {
+        let syscalls = self.send(socket, retry).await;
+        self.addrs.clear();
+        syscalls
+    }
+
+    #[rustfmt::skip]
+    #[cfg(target_os = "linux")]
+    async fn send(&self, socket: &UdpSocket, retry: &Retry) -> usize {
+        use tokio::io::Interest;
+
+        let (mut sent, mut syscalls, mut attempts) = (0, 0, 0);
+        while sent < self.addrs.len() {
+            if let Err(e) = socket.writable().await {
+                retry.discard(self.addrs.len() - sent, &"batch", 1, &e);
+                break
+            }
+
+            match socket.try_io(Interest::WRITABLE, || {
+                sendmmsg(socket, &self.buffers[sent..self.addrs.len()], &self.addrs[sent..])
+            }) {
+                Ok(n) => {
+                    sent += n;
+                    syscalls += 1;
+                    attempts = 0;
+                },
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => (),
+                Err(e) if retry.is_retryable(&e, attempts) => {
+                    attempts += 1;
+                    syscalls += 1;
+                    tokio::task::yield_now().await;
+                },
+                // sendmmsg fails on the first datagram only, the
+                // datagrams before it have been sent by the last call.
+                Err(e) => {
+                    retry.discard(1, &self.addrs[sent], attempts + 1, &e);
+                    sent += 1;
+                    syscalls += 1;
+                    attempts = 0;
+                }
+            }
+        }
+
+        syscalls
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    async fn send(&self, socket: &UdpSocket, retry: &Retry) -> usize {
+        for (buffer, addr) in self.buffers.iter().zip(self.addrs.iter()) {
+            retry.send(|| socket.send_to(buffer, addr), addr).await;
+        }
+
+        self.addrs.len()
+    }
+}
+
+/// inbound datagram batch.
+///
+/// on linux a single recvmmsg fills the buffers with the 
+/// datagrams that are queued in the socket, otherwise the 
+/// datagrams are read one by one until the socket is drained.
+pub struct Inbound {
+    buffers: Vec<Vec<u8>>,
+    /// the buffer index, the size and the source address.
+    received: Vec<(usize, usize, SocketAddr)>,
+}
+
+impl Inbound {
+    /// create batch.
+    ///
+    /// the capacity is clamped to 1..=1024, every 
+    /// buffer has the specified size.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let inbound = Inbound::new(64, 1280);
+    /// assert_eq!(inbound.buffer_size(), 1280);
+    /// assert_eq!(inbound.datagrams().count(), 0);
+    /// ```
+    pub fn new(capacity: usize, size: usize) -> Self {
+        let capacity = capacity.clamp(1, MAX_BATCH_SIZE);
+        Self {
+            buffers: vec![vec![0u8; size]; capacity],
+            received: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// the size of every buffer.
+    pub fn buffer_size(&self) -> usize {
+        self.buffers[0].len()
+    }
+
+    /// resize every buffer.
+    pub fn resize(&mut self, size: usize) {
+        for buffer in &mut self.buffers {
+            buffer.resize(size, 0);
+        }
+    }
+
+    /// the datagrams received by the last read.
+    ///
+    /// the size of a datagram is the size reported by the socket, 
+    /// a truncated datagram has the size of the buffer.
+    pub fn datagrams(&self) -> impl Iterator<Item = (&[u8], usize, SocketAddr)> {
+        self.received
+            .iter()
+            .map(move |(index, size, addr)| (&self.buffers[*index][..*size], *size, *addr))
+    }
+
+    /// read a batch of datagrams.
+    ///
+    /// wait for at least one datagram, and read the datagrams 
+    /// that are already queued without waiting, returns the 
+    /// number of datagrams read.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let socket = UdpSocket::bind("127.0.0.1:0").await?;
+    /// let addr = socket.local_addr()?;
+    /// for _ in 0..64 {
+    ///     socket.send_to(b"hello", addr).await?;
+    /// }
+    ///
+    /// let mut inbound = Inbound::new(64, 1280);
+    /// assert_eq!(inbound.recv(&socket).await?, 64);
+    /// assert_eq!(inbound.datagrams().count(), 64);
+    /// ```
+    #[cfg(target_os = "linux")]
+    pub async fn recv(&mut self, socket: &UdpSocket) -> io::Result<usize> {
+        use tokio::io::Interest;
+
+        self.received.clear();
+        loop {
+            socket.readable().await?;
+            match socket.try_io(Interest::READABLE, || {
+                recvmmsg(socket, &mut self.buffers, &mut self.received)
+            }) {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => (),
+                ret => return ret
+            }
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub async fn recv(&mut self, socket: &UdpSocket) -> io::Result<usize> {
+        self.received.clear();
+        let (size, addr) = socket.recv_from(&mut self.buffers[0]).await?;
+        self.received.push((0, size, addr));
+
+        while self.received.len() < self.buffers.len() {
+            let index = self.received.len();
+            match socket.try_recv_from(&mut self.buffers[index]) {
+                Ok((size, addr)) => self.received.push((index, size, addr)),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(_) => ()
+            }
+        }
+
+        Ok(self.received.len())
+    }
+}
+
+/// receive multiple datagrams with a single syscall.
+///
+/// returns the number of datagrams received, the buffer index, 
+/// the size and the source address of the datagrams are pushed 
+/// to received, a datagram of an unknown address family is 
+/// skipped, so the indexes may have gaps.
+#[cfg(target_os = "linux")]
+fn recvmmsg(
+    socket: &UdpSocket, 
+    buffers: &mut [Vec<u8>], 
+    received: &mut Vec<(usize, usize, SocketAddr)>
+) -> io::Result<usize> {
+    use std::os::unix::io::AsRawFd;
+    use std::mem::size_of;
+
+    // SAFETY: sockaddr_storage is a plain C struct, all zero is a valid value.
+    let mut addrs = vec![unsafe { std::mem::zeroed::<libc::sockaddr_storage>() }; buffers.len()];
+    let mut iovecs = buffers
+        .iter_mut()
+        .map(|b| libc::iovec {
+            iov_base: b.as_mut_ptr() as *mut libc::c_void,
+            iov_len: b.len()
+        })
+        .collect::<Vec<_>>();
+    let mut messages = iovecs
+        .iter_mut()
+        .zip(addrs.iter_mut())
+        .map(|(iovec, addr)| {
+            // SAFETY: mmsghdr is a plain C struct, all zero is a valid value.
+            let mut message: libc::mmsghdr = unsafe { std::mem::zeroed() };
+            message.msg_hdr.msg_name = addr as *mut _ as *mut libc::c_void;
+            message.msg_hdr.msg_namelen = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+            message.msg_hdr.msg_iov = iovec;
+            message.msg_hdr.msg_iovlen = 1;
+            message
+        })
+        .collect::<Vec<_>>();
+
+    // SAFETY: the messages point to the addresses and buffers,
+    // which outlive the call, the count is the length of the messages.
+    let n = unsafe {
+        libc::recvmmsg(
+            socket.as_raw_fd(),
+            messages.as_mut_ptr(),
+            messages.len() as libc::c_uint,
+            libc::MSG_DONTWAIT,
+            std::ptr::null_mut()
+        )
+    };
+
+    if n < 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    for (index, (message, addr)) in messages.iter().zip(addrs.iter()).take(n as usize).enumerate() {
+        let size = match message.msg_hdr.msg_flags & libc::MSG_TRUNC != 0 {
+            true => unsafe { (*message.msg_hdr.msg_iov).iov_len },
+            false => message.msg_len as usize
+        };
+
+        if let Some(addr) = socket_addr(addr) {
+            received.push((index, size, addr));
+        }
+    }
+
+    Ok(received.len())
+}
+
+/// convert the socket address of the syscall.
+#[cfg(target_os = "linux")]
+fn socket_addr(addr: &libc::sockaddr_storage) -> Option<SocketAddr> {
+    use std::net::{
+        SocketAddrV4,
+        SocketAddrV6
+    };
+
+    // SAFETY: the family tells the type of the address,
+    // sockaddr_storage is large enough for both types.
+    match addr.ss_family as libc::c_int {
+        libc::AF_INET => {
+            let a = unsafe { &*(addr as *const _ as *const libc::sockaddr_in) };
+            Some(SocketAddr::V4(SocketAddrV4::new(
+                u32::from_be(a.sin_addr.s_addr).into(), 
+                u16::from_be(a.sin_port)
+            )))
+        },
+        libc::AF_INET6 => {
+            let a = unsafe { &*(addr as *const _ as *const libc::sockaddr_in6) };
+            Some(SocketAddr::V6(SocketAddrV6::new(
+                a.sin6_addr.s6_addr.into(), 
+                u16::from_be(a.sin6_port),
+                a.sin6_flowinfo,
+                a.sin6_scope_id
+            )))
+        },
+        _ => None
+    }
+}
+
+/// send multiple datagrams with a single syscall.
+///
+/// returns the number of datagrams sent, which may be
+/// less than the number of datagrams given.
+#[cfg(target_os = "linux")]
+fn sendmmsg(socket: &UdpSocket, buffers: &[Vec<u8>], addrs: &[SocketAddr]) -> io::Result<usize> {
+    use std::os::unix::io::AsRawFd;
+    use socket2::SockAddr;
+
+    let addrs = addrs
+        .iter()
+        .map(|a| SockAddr::from(*a))
+        .collect::<Vec<_>>();
+    let mut iovecs = buffers
+        .iter()
+        .map(|b| libc::iovec {
+            iov_base: b.as_ptr() as *mut libc::c_void,
+            iov_len: b.len()
+        })
+        .collect::<Vec<_>>();
+    let mut messages = iovecs
+        .iter_mut()
+        .zip(addrs.iter())
+        .map(|(iovec, addr)| {
+            // SAFETY: mmsghdr is a plain C struct, all zero is a valid value.
+            let mut message: libc::mmsghdr = unsafe { std::mem::zeroed() };
+            message.msg_hdr.msg_name = addr.as_ptr() as *mut libc::c_void;
+            message.msg_hdr.msg_namelen = addr.len();
+            message.msg_hdr.msg_iov = iovec;
+            message.msg_hdr.msg_iovlen = 1;
+            message
+        })
+        .collect::<Vec<_>>();
+
+    // SAFETY: the messages point to the addresses and buffers,
+    // which outlive the call, the count is the length of the messages.
+    let n = unsafe {
+        libc::sendmmsg(
+            socket.as_raw_fd(),
+            messages.as_mut_ptr(),
+            messages.len() as libc::c_uint,
+            0
+        )
+    };
+
+    match n < 0 {
+        true => Err(io::Error::last_os_error()),
+        false => Ok(n as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::{
+        timeout,
+        Duration
+    };
+
+    async fn socket() -> (UdpSocket, SocketAddr) {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        (socket, addr)
+    }
+
+    async fn recv(socket: &UdpSocket, count: usize) -> Vec<Vec<u8>> {
+        let mut buf = [0u8; 1500];
+        let mut datagrams = Vec::new();
+        for _ in 0..count {
+            let size = timeout(Duration::from_secs(5), socket.recv(&mut buf))
+                .await
+                .unwrap()
+                .unwrap();
+            datagrams.push(buf[..size].to_vec());
+        }
+
+        datagrams
+    }
+
+    #[test]
+    fn capacity() {
+        assert_eq!(Batch::new(0).addrs.capacity(), 1);
+        assert_eq!(Batch::new(4096).addrs.capacity(), MAX_BATCH_SIZE);
+        assert_eq!(Inbound::new(0, 1500).buffers.len(), 1);
+        assert_eq!(Inbound::new(4096, 1500).buffers.len(), MAX_BATCH_SIZE);
+    }
+
+    #[tokio::test]
+    async fn flush() {
+        let (sender, _) = socket().await;
+        let (receiver, addr) = socket().await;
+        let retry = Retry::new(3);
+
+        let mut batch = Batch::new(64);
+        assert!(batch.is_empty());
+        for i in 0..64u8 {
+            batch.push(&[i; 16], addr);
+        }
+
+        let syscalls = batch.flush(&sender, &retry).await;
+        assert!(batch.is_empty());
+        assert_eq!(retry.dropped(), 0);
+        if cfg!(target_os = "linux") {
+            assert!(syscalls < 64);
+        }
+
+        let datagrams = recv(&receiver, 64).await;
+        for (i, datagram) in datagrams.iter().enumerate() {
+            assert_eq!(datagram, &vec![i as u8; 16]);
+        }
+
+        // the buffers are reused by the next batch.
+        batch.push(&[0xFF; 4], addr);
+        batch.flush(&sender, &retry).await;
+        assert_eq!(recv(&receiver, 1).await, vec![vec![0xFF; 4]]);
+    }
+
+    #[tokio::test]
+    async fn skip_dropped() {
+        let (sender, _) = socket().await;
+        let (receiver, addr) = socket().await;
+        let retry = Retry::new(3);
+
+        // a v6 destination can not be reached from a v4 socket.
+        let mut batch = Batch::new(3);
+        batch.push(&[1], addr);
+        batch.push(&[2], "[::1]:9".parse().unwrap());
+        batch.push(&[3], addr);
+        batch.flush(&sender, &retry).await;
+
+        assert!(batch.is_empty());
+        assert_eq!(retry.dropped(), 1);
+        assert_eq!(recv(&receiver, 2).await, vec![vec![1], vec![3]]);
+    }
+
+    #[tokio::test]
+    async fn recv_batch() {
+        let (sender, from) = socket().await;
+        let (receiver, addr) = socket().await;
+        for i in 0..64u8 {
+            sender.send_to(&[i; 16], addr).await.unwrap();
+        }
+
+        let mut inbound = Inbound::new(64, 1500);
+        let mut datagrams = Vec::new();
+        while datagrams.len() < 64 {
+            let n = timeout(Duration::from_secs(5), inbound.recv(&receiver))
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(inbound.datagrams().count(), n);
+            for (payload, size, source) in inbound.datagrams() {
+                assert_eq!((size, source), (16, from));
+                datagrams.push(payload.to_vec());
+            }
+        }
+
+        for (i, datagram) in datagrams.iter().enumerate() {
+            assert_eq!(datagram, &vec![i as u8; 16]);
+        }
+    }
+
+    #[test]
+    fn skipped_datagram() {
+        let from = "127.0.0.1:8080".parse().unwrap();
+        let mut inbound = Inbound::new(3, 4);
+        inbound.buffers[0].copy_from_slice(b"aaaa");
+        inbound.buffers[2].copy_from_slice(b"cccc");
+
+        // the datagram of the second buffer was skipped.
+        inbound.received = vec![(0, 4, from), (2, 2, from)];
+        let datagrams = inbound.datagrams()
+            .map(|(payload, size, _)| (payload.to_vec(), size))
+            .collect::<Vec<_>>();
+        assert_eq!(datagrams, vec![(b"aaaa".to_vec(), 4), (b"cc".to_vec(), 2)]);
+    }
+
+    #[tokio::test]
+    async fn recv_truncated() {
+        let (sender, _) = socket().await;
+        let (receiver, addr) = socket().await;
+        sender.send_to(&[0u8; 100], addr).await.unwrap();
+
+        // the size of a truncated datagram is the size of the buffer.
+        let mut inbound = Inbound::new(4, 64);
+        assert_eq!(inbound.recv(&receiver).await.unwrap(), 1);
+        assert_eq!(inbound.datagrams().next().unwrap().1, 64);
+
+        inbound.resize(128);
+        assert_eq!(inbound.buffer_size(), 128);
+    }
+}
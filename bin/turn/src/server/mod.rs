@@ -1,13 +1,22 @@
 mod thread;
+mod batch;
+mod retry;
 
 use tokio::net::UdpSocket;
 use anyhow::Result;
 use std::sync::Arc;
+use socket2::{
+    Socket,
+    Domain,
+    Type
+};
+
 use super::{
     argv::Argv,
     state::State
 };
 
+pub use retry::Retry;
 pub use thread::{
     Thread,
     ThreadLocal
@@ -24,6 +33,37 @@ fn get_threads(threads: Option<usize>) -> usize {
     threads.unwrap_or_else(num_cpus::get)
 }
 
+/// bind udp socket.
+///
+/// the socket options are applied before binding, 
+/// options that are not specified use the system default.
+///
+/// # Example
+///
+/// ```no_run
+/// let c = argv::Argv::new();
+/// let s = bind(&c)?;
+///
+/// let r = socket2::SockRef::from(&s);
+/// assert!(r.recv_buffer_size()? >= c.recv_buffer.unwrap_or(0));
+/// assert!(r.send_buffer_size()? >= c.send_buffer.unwrap_or(0));
+/// ```
+fn bind(f: &Argv) -> Result<UdpSocket> {
+    let s = Socket::new(Domain::for_address(f.listen), Type::DGRAM, None)?;
+    if let Some(size) = f.recv_buffer {
+        s.set_recv_buffer_size(size)?;
+    }
+
+    if let Some(size) = f.send_buffer {
+        s.set_send_buffer_size(size)?;
+    }
+
+    s.set_reuse_address(f.reuse_address)?;
+    s.set_nonblocking(true)?;
+    s.bind(&f.listen.into())?;
+    Ok(UdpSocket::from_std(s.into())?)
+}
+
 /// start udp server.
 ///
 /// create a specified number of threads, 
@@ -40,7 +80,8 @@ fn get_threads(threads: Option<usize>) -> usize {
 /// ```
 #[rustfmt::skip]
 pub async fn run(f: Arc<Argv>, c: Arc<State>) -> Result<()> {
-    let s = Arc::new(UdpSocket::bind(f.listen).await?);
+    let s = Arc::new(bind(&f)?);
+    let r = Arc::new(Retry::new(f.send_retries));
     let threads = get_threads(f.threads);
     let tl = ThreadLocal {
         state: c.clone(),
@@ -48,7 +89,7 @@ pub async fn run(f: Arc<Argv>, c: Arc<State>) -> Result<()> {
     };
     
     for _ in 0..threads {
-        let mut cx = Thread::builder(tl.clone(), &s);
+        let mut cx = Thread::builder(tl.clone(), &s, &r);
         tokio::spawn(async move {
             loop { cx.poll().await; }
         });
@@ -66,3 +107,40 @@ pub async fn run(f: Arc<Argv>, c: Arc<State>) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+
+    #[tokio::test]
+    async fn log_startup() {
+        mock::capture();
+        let (local, _) = mock::local(&["--listen", "127.0.0.1:0", "--threads", "1"]);
+        run(local.conf.clone(), local.state.clone()).await.unwrap();
+
+        let logs = mock::logs("udp bind to 127.0.0.1:0");
+        assert!(logs.iter().any(|(level, _)| *level == log::Level::Info));
+    }
+
+    #[tokio::test]
+    async fn bind_options() {
+        let c = mock::argv(&[
+            "--listen", "127.0.0.1:0", 
+            "--recv-buffer", "65536", 
+            "--send-buffer", "65536", 
+            "--reuse-address"
+        ]);
+
+        // the system may double the value.
+        let s = bind(&c).unwrap();
+        let r = socket2::SockRef::from(&s);
+        assert!(r.recv_buffer_size().unwrap() >= 65536);
+        assert!(r.send_buffer_size().unwrap() >= 65536);
+        assert!(r.reuse_address().unwrap());
+
+        let c = mock::argv(&["--listen", "127.0.0.1:0"]);
+        let s = bind(&c).unwrap();
+        assert!(!socket2::SockRef::from(&s).reuse_address().unwrap());
+    }
+}
@@ -0,0 +1,206 @@
+use std::{
+    future::Future,
+    fmt,
+    sync::atomic::{
+        AtomicU64,
+        Ordering
+    },
+    io
+};
+
+/// udp send retry policy.
+///
+/// a transient error, the socket buffer is full (WouldBlock)
+/// or the syscall is interrupted, is retried a bounded number
+/// of times, the datagram is dropped when the retries are
+/// exhausted or the error is fatal. a dropped datagram never
+/// stops the server, it is counted instead.
+pub struct Retry {
+    retries: usize,
+    dropped: AtomicU64,
+}
+
+impl Retry {
+    /// create policy.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let retry = Retry::new(3);
+    /// assert_eq!(retry.dropped(), 0);
+    /// ```
+    pub fn new(retries: usize) -> Self {
+        Self {
+            dropped: AtomicU64::new(0),
+            retries,
+        }
+    }
+
+    /// the number of datagrams dropped on send errors.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// count the dropped datagrams.
+    pub fn discard(&self, count: usize, via: &(dyn fmt::Debug + Sync), attempts: usize, e: &io::Error) {
+        self.dropped.fetch_add(count as u64, Ordering::Relaxed);
+        log::warn!(
+            "udp send dropped: via={:?}, count={}, attempts={}, dropped={}, {}",
+            via,
+            count,
+            attempts,
+            self.dropped(),
+            e
+        );
+    }
+
+    /// whether the send error is worth retrying.
+    pub fn is_transient(e: &io::Error) -> bool {
+        matches!(
+            e.kind(),
+            io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted
+        )
+    }
+
+    /// whether the failed attempt is retried.
+    pub fn is_retryable(&self, e: &io::Error, attempts: usize) -> bool {
+        Self::is_transient(e) && attempts < self.retries
+    }
+
+    /// send with the retry policy.
+    ///
+    /// returns whether the send succeeded, the other tasks
+    /// are given a chance to run between the attempts so the
+    /// socket buffer can drain.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::io::{Error, ErrorKind};
+    /// use std::cell::Cell;
+    ///
+    /// let retry = Retry::new(3);
+    ///
+    /// // WouldBlock twice, then succeeds.
+    /// let attempts = Cell::new(0);
+    /// assert!(retry.send(|| async {
+    ///     attempts.set(attempts.get() + 1);
+    ///     match attempts.get() {
+    ///         1 | 2 => Err(Error::from(ErrorKind::WouldBlock)),
+    ///         _ => Ok(())
+    ///     }
+    /// }, &"test").await);
+    /// assert_eq!(attempts.get(), 3);
+    /// assert_eq!(retry.dropped(), 0);
+    ///
+    /// // a fatal error is dropped without retry.
+    /// let attempts = Cell::new(0);
+    /// assert!(!retry.send(|| async {
+    ///     attempts.set(attempts.get() + 1);
+    ///     Err::<(), _>(Error::from(ErrorKind::PermissionDenied))
+    /// }, &"test").await);
+    /// assert_eq!(attempts.get(), 1);
+    /// assert_eq!(retry.dropped(), 1);
+    /// ```
+    #[rustfmt::skip]
+    pub async fn send<T, F, R>(&self, mut f: F, via: &(dyn fmt::Debug + Sync)) -> bool
+    where
+        F: FnMut() -> R,
+        R: Future<Output = io::Result<T>>
+    {
+        let mut attempts = 0;
+        loop {
+            let e = match f().await {
+                Ok(_) => return true,
+                Err(e) => e
+            };
+
+            if self.is_retryable(&e, attempts) {
+                attempts += 1;
+                tokio::task::yield_now().await;
+                continue
+            }
+
+            self.discard(1, via, attempts + 1, &e);
+            return false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn error(kind: io::ErrorKind) -> io::Error {
+        io::Error::from(kind)
+    }
+
+    #[test]
+    fn is_retryable() {
+        let retry = Retry::new(2);
+        assert!(Retry::is_transient(&error(io::ErrorKind::WouldBlock)));
+        assert!(Retry::is_transient(&error(io::ErrorKind::Interrupted)));
+        assert!(!Retry::is_transient(&error(io::ErrorKind::PermissionDenied)));
+
+        assert!(retry.is_retryable(&error(io::ErrorKind::WouldBlock), 1));
+        assert!(!retry.is_retryable(&error(io::ErrorKind::WouldBlock), 2));
+        assert!(!retry.is_retryable(&error(io::ErrorKind::PermissionDenied), 0));
+    }
+
+    #[test]
+    fn discard() {
+        let retry = Retry::new(2);
+        retry.discard(3, &"batch", 1, &error(io::ErrorKind::PermissionDenied));
+        retry.discard(1, &"batch", 1, &error(io::ErrorKind::PermissionDenied));
+        assert_eq!(retry.dropped(), 4);
+    }
+
+    #[tokio::test]
+    async fn retry_would_block() {
+        let retry = Retry::new(3);
+        let attempts = Cell::new(0);
+        let sent = retry.send(|| {
+            attempts.set(attempts.get() + 1);
+            let ret = match attempts.get() {
+                1 | 2 => Err(error(io::ErrorKind::WouldBlock)),
+                _ => Ok(())
+            };
+
+            async move { ret }
+        }, &"test").await;
+
+        assert!(sent);
+        assert_eq!(attempts.get(), 3);
+        assert_eq!(retry.dropped(), 0);
+    }
+
+    #[tokio::test]
+    async fn drop_fatal() {
+        let retry = Retry::new(3);
+        let attempts = Cell::new(0);
+        let sent = retry.send(|| {
+            attempts.set(attempts.get() + 1);
+            async { Err::<(), _>(error(io::ErrorKind::PermissionDenied)) }
+        }, &"test").await;
+
+        // a fatal error is not retried.
+        assert!(!sent);
+        assert_eq!(attempts.get(), 1);
+        assert_eq!(retry.dropped(), 1);
+    }
+
+    #[tokio::test]
+    async fn retries_exhausted() {
+        let retry = Retry::new(3);
+        let attempts = Cell::new(0);
+        let sent = retry.send(|| {
+            attempts.set(attempts.get() + 1);
+            async { Err::<(), _>(error(io::ErrorKind::WouldBlock)) }
+        }, &"test").await;
+
+        assert!(!sent);
+        assert_eq!(attempts.get(), 4);
+        assert_eq!(retry.dropped(), 1);
+    }
+}
@@ -1,8 +1,17 @@
 use tokio::net::UdpSocket;
 use bytes::BytesMut;
+use super::{
+    retry::Retry,
+    batch::{
+        Inbound,
+        Batch
+    }
+};
+
 use std::{
     net::SocketAddr, 
-    sync::Arc
+    sync::Arc,
+    io
 };
 
 use crate::{
@@ -11,6 +20,16 @@ use crate::{
     state::State
 };
 
+/// the minimum receive buffer size.
+///
+/// every IPv4 host must accept datagrams of 576 bytes, 
+/// a smaller buffer would truncate common STUN messages.
+const MIN_BUFFER_SIZE: usize = 576;
+
+/// the maximum receive buffer size, a UDP payload 
+/// can never be larger than this.
+const MAX_BUFFER_SIZE: usize = 65535;
+
 /// thread local context.
 pub struct ThreadLocal {
     pub state: Arc<State>,
@@ -22,17 +41,24 @@ pub struct Thread {
     socket: Arc<UdpSocket>,
     writer: BytesMut,
     reader: Vec<u8>,
+    inbound: Option<Inbound>,
+    batch: Option<Batch>,
+    retry: Arc<Retry>,
     proto: Proto,
 }
 
 impl Thread {
     #[rustfmt::skip]
-    pub fn builder(local: ThreadLocal, socket: &Arc<UdpSocket>) -> Self {
+    pub fn builder(local: ThreadLocal, socket: &Arc<UdpSocket>, retry: &Arc<Retry>) -> Self {
+        let size = local.conf.buffer.clamp(MIN_BUFFER_SIZE, MAX_BUFFER_SIZE);
         Self {
-            writer: BytesMut::with_capacity(local.conf.buffer),
-            reader: vec![0u8; local.conf.buffer],
+            writer: BytesMut::with_capacity(size),
+            reader: vec![0u8; size],
+            inbound: local.conf.batch.map(|c| Inbound::new(c, size)),
+            batch: local.conf.batch.map(Batch::new),
             proto: Proto::builder(local),
             socket: socket.clone(),
+            retry: retry.clone(),
         }
     }
     
@@ -42,6 +68,10 @@ impl Thread {
     /// it to the proto for processing, and send the processed 
     /// data packet to the specified address.
     ///
+    /// with batching, the datagrams that are already queued in 
+    /// the socket are read together and processed without 
+    /// waiting, and the processed data packets are sent together.
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -55,19 +85,24 @@ impl Thread {
     /// };
     ///
     /// let s = Arc::new(UdpSocket::bind(c.listen).await?);
+    /// let r = Arc::new(Retry::new(c.send_retries));
     /// tokio::spawn(async move {
-    ///     let mut tr = Thread::builder(thread_local, &s);
+    ///     let mut tr = Thread::builder(thread_local, &s, &r);
     ///     loop { tr.poll().await.unwrap() }
     /// });
     /// ```
     #[rustfmt::skip]
     pub async fn poll(&mut self) {
+        if self.inbound.is_some() {
+            return self.poll_batch().await
+        }
+
         let (s, a) = match self.read().await {
             Some(x) => x,
             None => return
         };
 
-        let (b, p) = match self.proto.handler(
+        let outbound = match self.proto.handler(
             &self.reader[..s], 
             &mut self.writer, 
             a
@@ -76,9 +111,61 @@ impl Thread {
             _ => return
         };
 
-        if let Err(e) = self.socket.send_to(b, p.as_ref()).await {
-            log::error!("udp io error: {}", e);
-            std::process::abort();
+        let socket = &self.socket;
+        self.retry.send(
+            || socket.send_to(outbound.payload, outbound.dest.as_ref()),
+            &outbound.via
+        ).await;
+    }
+
+    /// process a burst of datagrams and send the batch.
+    #[rustfmt::skip]
+    async fn poll_batch(&mut self) {
+        let inbound = match self.inbound.as_mut() {
+            Some(x) => x,
+            None => return
+        };
+
+        // reading errors are ignored, see `read`.
+        if inbound.recv(&self.socket).await.is_err() {
+            return
+        }
+
+        let mut truncated = false;
+        if let Some(inbound) = &self.inbound {
+            for (b, size, a) in inbound.datagrams() {
+                if size >= inbound.buffer_size() {
+                    log::warn!(
+                        "udp datagram truncated: addr={}, buffer={}", 
+                        a, 
+                        size
+                    );
+
+                    truncated = true;
+                    continue
+                }
+
+                if size < 4 {
+                    continue
+                }
+
+                let outbound = match self.proto.handler(b, &mut self.writer, a).await {
+                    Ok(Some(x)) => x,
+                    _ => continue
+                };
+
+                if let Some(batch) = &mut self.batch {
+                    batch.push(outbound.payload, *outbound.dest);
+                }
+            }
+        }
+
+        if truncated {
+            self.grow();
+        }
+
+        if let Some(batch) = self.batch.as_mut().filter(|b| !b.is_empty()) {
+            batch.flush(&self.socket, &self.retry).await;
         }
     }
 
@@ -90,6 +177,11 @@ impl Thread {
     /// so any reading errors are ignored here. \ 
     /// this is a last resort.
     ///
+    /// UDP silently truncates a datagram that is larger than the 
+    /// receive buffer, so a read that fills the whole buffer is 
+    /// treated as truncated, the datagram is dropped instead of 
+    /// being parsed, and the buffer is doubled for the next read.
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -103,13 +195,47 @@ impl Thread {
     /// };
     ///
     /// let s = Arc::new(UdpSocket::bind(c.listen).await?);
-    /// let mut tr = Thread::builder(thread_local, &s);
+    /// let r = Arc::new(Retry::new(c.send_retries));
+    /// let mut tr = Thread::builder(thread_local, &s, &r);
     /// // tr.read().await
     /// ```
     async fn read(&mut self) -> Option<(usize, SocketAddr)> {
-        match self.socket.recv_from(&mut self.reader[..]).await {
-            Ok(r) if r.0 >= 4 => Some(r), 
-            _ => None
+        let ret = self.socket.recv_from(&mut self.reader[..]).await;
+        self.check(ret)
+    }
+
+    /// check the result of a read, see `read`.
+    fn check(&mut self, ret: io::Result<(usize, SocketAddr)>) -> Option<(usize, SocketAddr)> {
+        let (size, addr) = match ret {
+            Ok(r) => r, 
+            _ => return None
+        };
+
+        if size >= self.reader.len() {
+            log::warn!(
+                "udp datagram truncated: addr={}, buffer={}", 
+                addr, 
+                size
+            );
+
+            self.grow();
+            return None
+        }
+
+        match size >= 4 {
+            true => Some((size, addr)),
+            false => None
+        }
+    }
+
+    /// grow the receive buffer.
+    ///
+    /// double the receive buffers, up to the maximum UDP payload.
+    fn grow(&mut self) {
+        let size = (self.reader.len() * 2).min(MAX_BUFFER_SIZE);
+        self.reader.resize(size, 0);
+        if let Some(inbound) = &mut self.inbound {
+            inbound.resize(size);
         }
     }
 }
@@ -122,3 +248,107 @@ impl Clone for ThreadLocal {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+    use stun::{
+        Kind,
+        MessageReader,
+        MessageWriter
+    };
+
+    use stun::attribute::Data;
+    use std::convert::TryFrom;
+
+    async fn create(args: &[&str]) -> (Thread, UdpSocket) {
+        let (local, _) = mock::local(args);
+        let socket = Arc::new(UdpSocket::bind("127.0.0.1:0").await.unwrap());
+        let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        client.connect(socket.local_addr().unwrap()).await.unwrap();
+        (Thread::builder(local, &socket, &Arc::new(Retry::new(3))), client)
+    }
+
+    /// a binding request of the specified size.
+    fn binding_request(size: usize) -> BytesMut {
+        let data = vec![0u8; size - 24];
+        let mut buf = BytesMut::with_capacity(size);
+        let mut message = MessageWriter::new(Kind::BindingRequest, &mut buf);
+        message.append::<Data>(&data);
+        message.try_into(None).unwrap();
+        buf
+    }
+
+    #[tokio::test]
+    async fn check_size() {
+        let (mut thread, client) = create(&["--buffer", "0"]).await;
+        let addr = client.local_addr().unwrap();
+        assert_eq!(thread.reader.len(), MIN_BUFFER_SIZE);
+
+        assert_eq!(thread.check(Ok((MIN_BUFFER_SIZE, addr))), None);
+        assert_eq!(thread.reader.len(), MIN_BUFFER_SIZE * 2);
+        assert_eq!(thread.check(Ok((3, addr))), None);
+        assert_eq!(thread.check(Ok((20, addr))), Some((20, addr)));
+        assert_eq!(thread.check(Err(io::ErrorKind::ConnectionReset.into())), None);
+
+        let (mut thread, _) = create(&["--buffer", "100000"]).await;
+        assert_eq!(thread.check(Ok((MAX_BUFFER_SIZE, addr))), None);
+        assert_eq!(thread.reader.len(), MAX_BUFFER_SIZE);
+    }
+
+    #[tokio::test]
+    async fn drop_truncated() {
+        let (mut thread, client) = create(&["--buffer", "576"]).await;
+        let request = binding_request(1000);
+        let mut buf = [0u8; 1280];
+
+        // the truncated request is dropped, not parsed.
+        client.send(&request).await.unwrap();
+        thread.poll().await;
+        assert!(client.try_recv(&mut buf).is_err());
+        assert_eq!(thread.reader.len(), 1152);
+
+        client.send(&request).await.unwrap();
+        thread.poll().await;
+        let size = client.recv(&mut buf).await.unwrap();
+        let message = MessageReader::try_from(&buf[..size]).unwrap();
+        assert_eq!(message.kind, Kind::BindingResponse);
+    }
+
+    #[tokio::test]
+    async fn drop_truncated_batch() {
+        let (mut thread, client) = create(&["--buffer", "576", "--batch", "8"]).await;
+        let request = binding_request(1000);
+        let mut buf = [0u8; 1280];
+
+        client.send(&request).await.unwrap();
+        thread.poll().await;
+        assert!(client.try_recv(&mut buf).is_err());
+        assert_eq!(thread.inbound.as_ref().unwrap().buffer_size(), 1152);
+
+        client.send(&request).await.unwrap();
+        thread.poll().await;
+        let size = client.recv(&mut buf).await.unwrap();
+        let message = MessageReader::try_from(&buf[..size]).unwrap();
+        assert_eq!(message.kind, Kind::BindingResponse);
+    }
+
+    #[tokio::test]
+    async fn poll_batch() {
+        let (mut thread, client) = create(&["--batch", "8"]).await;
+        let request = binding_request(100);
+        let mut buf = [0u8; 1280];
+        for _ in 0..8 {
+            client.send(&request).await.unwrap();
+        }
+
+        // the queued requests are handled by a single poll.
+        thread.poll().await;
+        for _ in 0..8 {
+            let size = client.recv(&mut buf).await.unwrap();
+            let message = MessageReader::try_from(&buf[..size]).unwrap();
+            assert_eq!(message.kind, Kind::BindingResponse);
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use tokio::time::Instant;
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::Arc,
+    fmt
+};
+
+type Addr = Arc<SocketAddr>;
+
+/// turn allocation.
+///
+/// a snapshot of everything the state holds for a client,
+/// assembled from the node, the ports, the permissions and
+/// the channel bindings of the client.
+///
+/// * the client address and the group.
+/// * the username of the authentication.
+/// * the relayed transport ports.
+/// * the time-to-expiry deadline.
+/// * the peer ports with a permission.
+/// * the channel numbers and the bound peers.
+/// * the bytes relayed from and to the client.
+#[derive(Debug, Clone)]
+pub struct Allocation {
+    pub addr: Addr,
+    pub group: u32,
+    pub username: String,
+    pub ports: Vec<u16>,
+    pub deadline: Instant,
+    pub permissions: Vec<u16>,
+    pub channels: HashMap<u16, Addr>,
+    pub sent: u64,
+    pub received: u64,
+}
+
+impl Allocation {
+    /// the remaining lifetime (second) of the allocation.
+    ///
+    /// ```no_run
+    /// // let allocation = state.get_allocation(&addr).unwrap();
+    /// assert!(allocation.remaining() <= 600);
+    /// ```
+    pub fn remaining(&self) -> u64 {
+        self.deadline
+            .saturating_duration_since(Instant::now())
+            .as_secs()
+    }
+}
+
+impl fmt::Display for Allocation {
+    /// ```no_run
+    /// // let allocation = state.get_allocation(&addr).unwrap();
+    /// assert_eq!(
+    ///     format!("{}", allocation),
+    ///     "addr=127.0.0.1:8080, group=0, username=panda, ports=[49152], \
+    ///     lifetime=600, permissions=[], channels=0, sent=0, received=0"
+    /// );
+    /// ```
+    #[rustfmt::skip]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "addr={}, group={}, username={}, ports={:?}, lifetime={}, \
+            permissions={:?}, channels={}, sent={}, received={}",
+            self.addr,
+            self.group,
+            self.username,
+            self.ports,
+            self.remaining(),
+            self.permissions,
+            self.channels.len(),
+            self.sent,
+            self.received
+        )
+    }
+}
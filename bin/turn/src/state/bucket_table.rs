@@ -40,6 +40,25 @@ impl BucketTable {
             .alloc()
     }
 
+    /// allocate an even port to the bucket.
+    ///
+    /// if reserve is true, the next-higher port is 
+    /// also allocated.
+    /// 
+    /// ```no_run
+    /// let buckets = BucketTable::new();
+    /// let port = buckets.alloc_even(0, true).await.unwrap();
+    /// assert_eq!(port % 2, 0);
+    /// ```
+    pub async fn alloc_even(&self, group: u32, reserve: bool) -> Option<u16> {
+        self.raw
+            .lock()
+            .await
+            .entry(group)
+            .or_insert_with(Bucket::new)
+            .alloc_even(reserve)
+    }
+
     /// remove an allocated from the bucket.
     /// 
     /// ```no_run
@@ -86,6 +105,24 @@ impl Bucket {
         port
     }
 
+    /// allocated an even port to the bucket.
+    ///
+    /// if the allocation is successful, add the reference 
+    /// count, the reserved port is also counted.
+    /// 
+    /// ```no_run
+    /// let mut bucket = Bucket::new();
+    /// // bucket.alloc_even(true).is_some()
+    /// ```
+    pub fn alloc_even(&mut self, reserve: bool) -> Option<u16> {
+        let port = self.port.alloc_even(None, reserve);
+        if port.is_some() {
+            self.num += if reserve { 2 } else { 1 };
+        }
+
+        port
+    }
+
     /// remove an allocated from the bucket.
     ///
     /// if the remove is successful, 
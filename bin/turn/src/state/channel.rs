@@ -5,6 +5,9 @@ use std::iter::{
     Iterator
 };
 
+/// the lifetime (second) of a channel binding.
+const CHANNEL_LIFETIME: u64 = 600;
+
 /// channels iterator.
 pub struct Iter {
     index: usize,
@@ -110,6 +113,25 @@ impl Channel {
         self.bond[1] = Some(a.clone())
     }
 
+    /// replace the bound addr.
+    /// 
+    /// ```no_run
+    /// use std::net::SocketAddr;
+    /// use std::sync::Arc;
+    ///
+    /// let addr = "127.0.0.1:8080".parse::<SocketAddr>().unwrap();
+    /// let new_addr = "127.0.0.1:8082".parse::<SocketAddr>().unwrap();
+    /// let mut channel = Channel::new(&addr);
+    /// // channel.replace(&addr, &new_addr)
+    /// ```
+    pub fn replace(&mut self, from: &Addr, to: &Addr) {
+        for bond in self.bond.iter_mut() {
+            if bond.as_ref() == Some(from) {
+                *bond = Some(to.clone());
+            }
+        }
+    }
+
     /// refresh channel lifetime.
     /// 
     /// ```no_run
@@ -124,6 +146,20 @@ impl Channel {
         self.timer = Instant::now();
     }
     
+    /// the remaining lifetime (second) of the channel.
+    /// 
+    /// ```no_run
+    /// use std::net::SocketAddr;
+    /// use std::sync::Arc;
+    ///
+    /// let addr = "127.0.0.1:8080".parse::<SocketAddr>().unwrap();
+    /// let channel = Channel::new(&addr);
+    /// // channel.remaining()
+    /// ```
+    pub fn remaining(&self) -> u64 {
+        CHANNEL_LIFETIME.saturating_sub(self.timer.elapsed().as_secs())
+    }
+
     /// whether the channel lifetime has ended.
     /// 
     /// ```no_run
@@ -136,7 +172,7 @@ impl Channel {
     /// ```
     #[rustfmt::skip]
     pub fn is_death(&self) -> bool {
-        self.timer.elapsed().as_secs() >= 600
+        self.timer.elapsed().as_secs() >= CHANNEL_LIFETIME
     }
 }
 
@@ -189,3 +225,45 @@ impl IntoIterator for Channel {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use tokio::time::Duration;
+
+    fn addr(port: u16) -> Addr {
+        Arc::new(SocketAddr::from(([127, 0, 0, 1], port)))
+    }
+
+    #[test]
+    fn bond() {
+        let mut channel = Channel::new(&addr(8080));
+        assert!(channel.includes(&addr(8080)));
+        assert!(channel.is_half());
+
+        channel.up(&addr(8081));
+        assert!(channel.includes(&addr(8081)));
+        assert!(!channel.is_half());
+
+        channel.replace(&addr(8080), &addr(9090));
+        assert!(!channel.includes(&addr(8080)));
+        assert_eq!(channel.into_iter().collect::<Vec<_>>(), vec![addr(9090), addr(8081)]);
+    }
+
+    #[test]
+    fn refresh() {
+        let mut channel = Channel::new(&addr(8080));
+        assert_eq!(channel.remaining(), CHANNEL_LIFETIME);
+
+        channel.timer -= Duration::from_secs(100);
+        assert_eq!(channel.remaining(), CHANNEL_LIFETIME - 100);
+        channel.refresh();
+        assert_eq!(channel.remaining(), CHANNEL_LIFETIME);
+
+        channel.timer -= Duration::from_secs(CHANNEL_LIFETIME);
+        assert!(channel.is_death());
+        assert_eq!(channel.remaining(), 0);
+    }
+}
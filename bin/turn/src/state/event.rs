@@ -0,0 +1,92 @@
+use std::{
+    net::SocketAddr,
+    sync::Arc,
+    fmt
+};
+
+type Addr = Arc<SocketAddr>;
+
+/// the capacity of the event channel.
+///
+/// events are dropped while the channel is full,
+/// a slow subscriber never blocks the state.
+pub const EVENT_CAPACITY: usize = 1024;
+
+/// allocation event.
+///
+/// the events are emitted from the state as the allocations
+/// change, an external subscriber, for monitoring or billing,
+/// receives them instead of polling the state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// a relayed port was allocated to the node.
+    Allocated {
+        addr: Addr,
+        group: u32,
+        port: u16,
+    },
+    /// the allocation lifetime (second) was refreshed.
+    Refreshed {
+        addr: Addr,
+        lifetime: u32,
+    },
+    /// a permission for the peer port was created or refreshed.
+    PermissionCreated {
+        addr: Addr,
+        group: u32,
+        port: u16,
+    },
+    /// a channel number was bound to the peer port.
+    ChannelBound {
+        addr: Addr,
+        group: u32,
+        port: u16,
+        channel: u16,
+    },
+    /// the allocation was deleted.
+    Deleted {
+        addr: Addr,
+        group: u32,
+    },
+}
+
+impl fmt::Display for Event {
+    /// ```no_run
+    /// let addr = Arc::new("127.0.0.1:8080".parse::<SocketAddr>().unwrap());
+    /// let event = Event::Deleted { addr, group: 0 };
+    /// assert_eq!(format!("{}", event), "deleted: addr=127.0.0.1:8080, group=0");
+    /// ```
+    #[rustfmt::skip]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Allocated { addr, group, port } =>
+                write!(f, "allocated: addr={}, group={}, port={}", addr, group, port),
+            Self::Refreshed { addr, lifetime } =>
+                write!(f, "refreshed: addr={}, lifetime={}", addr, lifetime),
+            Self::PermissionCreated { addr, group, port } =>
+                write!(f, "permission created: addr={}, group={}, port={}", addr, group, port),
+            Self::ChannelBound { addr, group, port, channel } =>
+                write!(f, "channel bound: addr={}, group={}, port={}, channel={}", addr, group, port, channel),
+            Self::Deleted { addr, group } =>
+                write!(f, "deleted: addr={}, group={}", addr, group),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        let addr = Arc::new("127.0.0.1:8080".parse::<SocketAddr>().unwrap());
+        let event = Event::Allocated { addr: addr.clone(), group: 0, port: 49152 };
+        assert_eq!(event.to_string(), "allocated: addr=127.0.0.1:8080, group=0, port=49152");
+
+        let event = Event::ChannelBound { addr: addr.clone(), group: 0, port: 49153, channel: 0x4000 };
+        assert_eq!(event.to_string(), "channel bound: addr=127.0.0.1:8080, group=0, port=49153, channel=16384");
+
+        let event = Event::Deleted { addr, group: 0 };
+        assert_eq!(event.to_string(), "deleted: addr=127.0.0.1:8080, group=0");
+    }
+}
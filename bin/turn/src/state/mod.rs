@@ -1,23 +1,33 @@
 mod bucket_table;
 mod random_port;
 mod nonce_table;
+mod reservation;
 mod channel;
 mod node;
+mod event;
+mod allocation;
 
 use node::Node;
 use channel::Channel;
+use event::EVENT_CAPACITY;
+use reservation::Reservation;
 use nonce_table::NonceTable;
 use bucket_table::BucketTable;
-use stun::util::long_key;
+use tokio::sync::mpsc;
+use stun::util::Crypto;
 use tokio::sync::RwLock;
 use tokio::time::{
     Duration,
+    Instant,
     sleep
 };
 
 use std::{
     collections::HashMap,
-    net::SocketAddr,
+    net::{
+        SocketAddr,
+        IpAddr
+    },
     sync::Arc
 };
 
@@ -26,7 +36,45 @@ use super::{
     broker::Broker
 };
 
+pub use event::Event;
+pub use allocation::Allocation;
+
 type Addr = Arc<SocketAddr>;
+type Permissions = HashMap<Addr, (u16, Instant)>;
+
+/// the default lifetime (second) of the allocation.
+pub const DEFAULT_LIFETIME: u32 = 600;
+
+/// the maximum allowed lifetime (second) of the allocation.
+///
+/// It is RECOMMENDED that the server use a maximum allowed 
+/// lifetime value of no more than 3600 seconds (1 hour).
+pub const MAX_LIFETIME: u32 = 3600;
+
+/// the time (second) after a channel binding expires during which 
+/// the channel number cannot be bound to a different peer.
+pub const CHANNEL_COOLDOWN: u64 = 300;
+
+/// channel bind result.
+#[derive(Debug, PartialEq, Eq)]
+#[derive(Copy, Clone)]
+pub enum ChannelBind {
+    /// the channel binding was created.
+    Created,
+    /// an existing channel binding was refreshed.
+    Refreshed,
+}
+
+/// channel bind failure.
+#[derive(Debug, PartialEq, Eq)]
+#[derive(Copy, Clone)]
+pub enum ChannelBindError {
+    /// the channel number is bound to a different peer, 
+    /// or the peer is bound to a different channel number.
+    Conflict,
+    /// the node, the peer or the channel is not available.
+    Unavailable,
+}
 
 /// Single State Tree.
 ///
@@ -38,16 +86,36 @@ type Addr = Arc<SocketAddr>;
 /// it should be noted that the node key only supports 
 /// long-term valid passwords，does not support short-term 
 /// valid passwords.
+///
+/// the nodes, nonces, permissions and channel bindings are 
+/// keyed by the full source address of the client, the ip 
+/// address and the port, which is the 5-tuple of an udp 
+/// allocation. a datagram from another port of the same ip 
+/// address is a different client, it is challenged and 
+/// authenticated on its own and never reaches the allocation, 
+/// only `migrate` moves an allocation after authentication.
+///
+/// the tables are always locked in the order of the fields 
+/// below, from `ports` to `channel_cooldowns`, an operation 
+/// that needs several tables takes them in this order, so 
+/// two operations never wait for each other. the nonces and 
+/// the buckets lock internally and are never held across 
+/// another table.
 pub struct State {
     conf: Arc<Argv>,
     broker: Arc<Broker>,
+    crypto: Box<dyn Crypto>,
+    realms: HashMap<IpAddr, String>,
     nonces: NonceTable,
     buckets: BucketTable,
-    nodes: RwLock<HashMap<Addr, Node>>,
     ports: RwLock<HashMap<(u32, u16), Addr>>,
-    port_bonds: RwLock<HashMap<Addr, HashMap<Addr, u16>>>,
     channels: RwLock<HashMap<(u32, u16), Channel>>,
+    nodes: RwLock<HashMap<Addr, Node>>,
+    reservations: RwLock<HashMap<u64, Reservation>>,
+    port_bonds: RwLock<HashMap<Addr, Permissions>>,
     channel_bonds: RwLock<HashMap<(Addr, u16), Addr>>,
+    channel_cooldowns: RwLock<HashMap<(Addr, u16), (Addr, Instant)>>,
+    events: mpsc::Sender<Event>,
 }
 
 impl State {
@@ -70,6 +138,51 @@ impl State {
         self.nonces.get(a).await
     }
 
+    /// get the realm of the node SocketAddr.
+    ///
+    /// the realm is resolved from the client address realm 
+    /// mapping, the default realm is used if there is no 
+    /// mapping for the client address.
+    ///
+    /// ```no_run
+    /// use std::net::SocketAddr;
+    /// use std::sync::Arc;
+    /// use turn::argv::Argv;
+    /// use turn::broker::Broker;
+    ///
+    /// // --realm localhost --realm-map 127.0.0.2=tenant
+    /// let addr = Arc::new("127.0.0.1:8080".parse::<SocketAddr>().unwrap());
+    /// let peer = Arc::new("127.0.0.2:8080".parse::<SocketAddr>().unwrap());
+    /// let argvure = Argv::generate().unwrap();
+    /// let broker = Broker::new(&argvure);
+    /// let state = State::new(&argvure, &broker);
+    ///
+    /// assert_eq!(state.get_realm(&addr), "localhost");
+    /// assert_eq!(state.get_realm(&peer), "tenant");
+    /// ```
+    pub fn get_realm(&self, a: &Addr) -> &str {
+        self.realms
+            .get(&a.ip())
+            .unwrap_or(&self.conf.realm)
+    }
+
+    /// get the hashing backend.
+    ///
+    /// ```no_run
+    /// use turn::argv::Argv;
+    /// use turn::broker::Broker;
+    /// use stun::util::RustCrypto;
+    ///
+    /// let argvure = Argv::generate().unwrap();
+    /// let broker = Broker::new(&argvure);
+    /// let state = State::new(&argvure, &broker, RustCrypto);
+    ///
+    /// let key = state.get_crypto().long_key("panda", "panda", "raspberry");
+    /// ```
+    pub fn get_crypto(&self) -> &dyn Crypto {
+        self.crypto.as_ref()
+    }
+
     /// get the password of the node SocketAddr.
     ///
     /// require remote control service to distribute keys.
@@ -85,7 +198,13 @@ impl State {
     /// let broker = Broker::new(&argvure);
     /// let state = State::new(&argvure, &broker);
     ///
-    /// // state.get_key(&addr, "panda")
+    /// // the same ip address with another source port 
+    /// // is a distinct and unauthenticated client.
+    /// let other = "127.0.0.1:8081".parse::<SocketAddr>().unwrap();
+    /// state.get_key(&addr, "panda");
+    /// state.allocate(&addr).unwrap();
+    /// assert!(state.get_allocation(&other).is_none());
+    /// assert_ne!(state.get_nonce(&addr), state.get_nonce(&other));
     /// ```
     pub async fn get_key(&self, a: &Addr, u: &str) -> Option<Arc<[u8; 16]>> {
         let key = self.nodes
@@ -104,10 +223,11 @@ impl State {
         
         let node = Node::new(
             auth.group, 
-            long_key(
+            u,
+            self.crypto.long_key(
                 u, 
                 &auth.password, 
-                &self.conf.realm
+                self.get_realm(a)
             )
         );
 
@@ -119,6 +239,106 @@ impl State {
         Some(key)
     }
 
+    /// get the allocation of the node.
+    ///
+    /// the allocation is a snapshot, it does not 
+    /// follow later changes of the state.
+    ///
+    /// ```no_run
+    /// use std::net::SocketAddr;
+    /// use std::sync::Arc;
+    /// use turn::argv::Argv;
+    /// use turn::broker::Broker;
+    /// use stun::util::RustCrypto;
+    ///
+    /// let addr = Arc::new("127.0.0.1:8080".parse::<SocketAddr>().unwrap());
+    /// let peer = Arc::new("127.0.0.1:8081".parse::<SocketAddr>().unwrap());
+    ///
+    /// let argvure = Argv::generate().unwrap();
+    /// let broker = Broker::new(&argvure);
+    /// let (state, _) = State::new(&argvure, &broker, RustCrypto);
+    ///
+    /// state.get_key(&addr, "panda");
+    /// state.get_key(&peer, "panda");
+    /// assert!(state.get_allocation(&addr).unwrap().ports.is_empty());
+    ///
+    /// let addr_port = state.allocate(&addr).unwrap();
+    /// let peer_port = state.allocate(&peer).unwrap();
+    /// state.bind_port(&addr, peer_port);
+    /// state.bind_channel(&addr, peer_port, 0x4000);
+    /// state.add_relayed(&addr, &peer, 100);
+    ///
+    /// let allocation = state.get_allocation(&addr).unwrap();
+    /// assert_eq!(allocation.username, "panda");
+    /// assert_eq!(allocation.ports, vec![addr_port]);
+    /// assert_eq!(allocation.permissions, vec![peer_port]);
+    /// assert_eq!(allocation.channels.get(&0x4000), Some(&peer));
+    /// assert_eq!(allocation.remaining(), DEFAULT_LIFETIME as u64);
+    /// assert_eq!(allocation.sent, 100);
+    /// assert_eq!(state.get_allocation(&peer).unwrap().received, 100);
+    ///
+    /// state.remove_allocation(&addr);
+    /// assert!(state.get_allocation(&addr).is_none());
+    /// ```
+    #[rustfmt::skip]
+    pub async fn get_allocation(&self, a: &Addr) -> Option<Allocation> {
+        let nodes = self.nodes.read().await;
+        let node = nodes.get(a)?;
+        let permissions = self.port_bonds
+            .read()
+            .await
+            .get(a)
+            .map(|bonds| bonds.values().map(|(p, _)| *p).collect())
+            .unwrap_or_default();
+        let channel_bonds = self.channel_bonds.read().await;
+        let channels = node.channels
+            .iter()
+            .filter_map(|c| channel_bonds.get(&(a.clone(), *c)).map(|p| (*c, p.clone())))
+            .collect();
+        Some(Allocation {
+            addr: a.clone(),
+            group: node.group,
+            username: node.username.clone(),
+            ports: node.ports.clone(),
+            deadline: node.deadline(),
+            received: node.get_received(),
+            sent: node.get_sent(),
+            permissions,
+            channels,
+        })
+    }
+
+    /// count the bytes relayed from the node to the peer.
+    ///
+    /// ```no_run
+    /// use std::net::SocketAddr;
+    /// use std::sync::Arc;
+    /// use turn::argv::Argv;
+    /// use turn::broker::Broker;
+    /// use stun::util::RustCrypto;
+    ///
+    /// let addr = Arc::new("127.0.0.1:8080".parse::<SocketAddr>().unwrap());
+    /// let peer = Arc::new("127.0.0.1:8081".parse::<SocketAddr>().unwrap());
+    ///
+    /// let argvure = Argv::generate().unwrap();
+    /// let broker = Broker::new(&argvure);
+    /// let (state, _) = State::new(&argvure, &broker, RustCrypto);
+    ///
+    /// state.get_key(&addr, "panda");
+    /// state.add_relayed(&addr, &peer, 100);
+    /// assert_eq!(state.get_allocation(&addr).unwrap().sent, 100);
+    /// ```
+    pub async fn add_relayed(&self, a: &Addr, peer: &Addr, size: usize) {
+        let nodes = self.nodes.read().await;
+        if let Some(node) = nodes.get(a) {
+            node.add_sent(size);
+        }
+
+        if let Some(node) = nodes.get(peer) {
+            node.add_received(size);
+        }
+    }
+
     /// obtain the peer address bound to the current 
     /// node according to the channel number.
     ///
@@ -138,8 +358,8 @@ impl State {
     /// state.get_key(&addr, "panda");
     /// state.get_key(&peer, "panda");
     ///
-    /// let addr_port = state.alloc_port(&addr).unwrap();
-    /// let peer_port = state.alloc_port(&peer).unwrap();
+    /// let addr_port = state.allocate(&addr).unwrap();
+    /// let peer_port = state.allocate(&peer).unwrap();
     ///
     /// state.bind_channel(&addr, peer_port, 0x4000);
     /// state.bind_channel(&peer, addr_port, 0x4000);
@@ -154,6 +374,57 @@ impl State {
             .cloned()
     }
 
+    /// whether the peer accepts data from the node.
+    ///
+    /// data relayed to a peer is dropped unless the peer has 
+    /// installed a permission towards the node, or has bound 
+    /// the channel to the node, otherwise any client could use 
+    /// the relay to reach any other client.
+    ///
+    /// ```no_run
+    /// use std::net::SocketAddr;
+    /// use std::sync::Arc;
+    /// use turn::argv::Argv;
+    /// use turn::broker::Broker;
+    ///
+    /// let addr = "127.0.0.1:8080".parse::<SocketAddr>().unwrap();
+    /// let peer = "127.0.0.1:8081".parse::<SocketAddr>().unwrap();
+    ///
+    /// let argvure = Argv::generate().unwrap();
+    /// let broker = Broker::new(&argvure);
+    /// let state = State::new(&argvure, &broker);
+    ///
+    /// state.get_key(&addr, "panda");
+    /// state.get_key(&peer, "panda");
+    ///
+    /// let addr_port = state.allocate(&addr).unwrap();
+    /// let peer_port = state.allocate(&peer).unwrap();
+    ///
+    /// state.bind_channel(&addr, peer_port, 0x4000);
+    /// assert!(!state.is_permitted(&addr, &peer, 0x4000));
+    ///
+    /// state.bind_channel(&peer, addr_port, 0x4000);
+    /// assert!(state.is_permitted(&addr, &peer, 0x4000));
+    /// ```
+    pub async fn is_permitted(&self, a: &Addr, peer: &Addr, c: u16) -> bool {
+        let has_permission = self.port_bonds
+            .read()
+            .await
+            .get(peer)
+            .map(|bonds| bonds.contains_key(a))
+            .unwrap_or(false);
+        if has_permission {
+            return true
+        }
+
+        self.channel_bonds
+            .read()
+            .await
+            .get(&(peer.clone(), c))
+            .map(|source| source == a)
+            .unwrap_or(false)
+    }
+
     /// obtain the peer address bound to the current
     /// node according to the port number.
     ///
@@ -173,8 +444,8 @@ impl State {
     /// state.get_key(&addr, "panda");
     /// state.get_key(&peer, "panda");
     ///
-    /// let addr_port = state.alloc_port(&addr).unwrap();
-    /// let peer_port = state.alloc_port(&peer).unwrap();
+    /// let addr_port = state.allocate(&addr).unwrap();
+    /// let peer_port = state.allocate(&peer).unwrap();
     ///
     /// state.bind_port(&peer, addr_port);
     /// state.bind_port(&addr, peer_port);
@@ -213,8 +484,8 @@ impl State {
     /// state.get_key(&addr, "panda");
     /// state.get_key(&peer, "panda");
     ///
-    /// let addr_port = state.alloc_port(&addr).unwrap();
-    /// let peer_port = state.alloc_port(&peer).unwrap();
+    /// let addr_port = state.allocate(&addr).unwrap();
+    /// let peer_port = state.allocate(&peer).unwrap();
     ///
     /// state.bind_port(&peer, addr_port);
     /// state.bind_port(&addr, peer_port);
@@ -228,7 +499,7 @@ impl State {
             .await
             .get(p)?
             .get(a)
-            .copied()
+            .map(|(port, _)| *port)
     }
    
     /// alloc a port from State.
@@ -288,24 +559,125 @@ impl State {
     /// state.get_key(&addr, "panda");
     /// state.get_key(&peer, "panda");
     ///
-    /// assert!(state.alloc_port(&addr).unwrap().is_some());
-    /// assert!(state.alloc_port(&peer).unwrap().is_some());
+    /// assert!(state.allocate(&addr).unwrap().is_some());
+    /// assert!(state.allocate(&peer).unwrap().is_some());
     /// ```
     #[rustfmt::skip]
-    pub async fn alloc_port(&self, a: &Addr) -> Option<u16> {
+    pub async fn allocate(&self, a: &Addr) -> Option<u16> {
+        let mut ports = self.ports.write().await;
         let mut nodes = self.nodes.write().await;
         let node = nodes.get_mut(a)?;
         let port = self.buckets
             .alloc(node.group)
             .await?;
-        self.ports
+        ports.insert((node.group, port), a.clone());
+        if !node.ports.contains(&port) {
+            node.ports.push(port);    
+        }
+        
+        self.emit(Event::Allocated { addr: a.clone(), group: node.group, port });
+        Some(port)
+    }
+    
+    /// alloc an even port from State.
+    ///
+    /// If the request contains an EVEN-PORT attribute, the server 
+    /// allocates an even port, and if the R bit is set, it also 
+    /// reserves the next-higher port and returns a RESERVATION-TOKEN 
+    /// that identifies the reservation.
+    ///
+    /// ```no_run
+    /// use std::net::SocketAddr;
+    /// use std::sync::Arc;
+    /// use turn::argv::Argv;
+    /// use turn::broker::Broker;
+    ///
+    /// let addr = "127.0.0.1:8080".parse::<SocketAddr>().unwrap();
+    /// let peer = "127.0.0.1:8081".parse::<SocketAddr>().unwrap();
+    ///
+    /// let argvure = Argv::generate().unwrap();
+    /// let broker = Broker::new(&argvure);
+    /// let state = State::new(&argvure, &broker);
+    ///
+    /// state.get_key(&addr, "panda");
+    /// state.get_key(&peer, "panda");
+    ///
+    /// let (port, token) = state.alloc_even_port(&addr, true).unwrap();
+    /// assert_eq!(port % 2, 0);
+    /// assert_eq!(state.claim_port(&peer, token.unwrap()), Some(port + 1));
+    /// assert_eq!(state.claim_port(&peer, token.unwrap()), None);
+    /// ```
+    #[rustfmt::skip]
+    pub async fn alloc_even_port(&self, a: &Addr, reserve: bool) -> Option<(u16, Option<u64>)> {
+        let mut ports = self.ports.write().await;
+        let mut nodes = self.nodes.write().await;
+        let node = nodes.get_mut(a)?;
+        let port = self.buckets
+            .alloc_even(node.group, reserve)
+            .await?;
+        ports.insert((node.group, port), a.clone());
+        if !node.ports.contains(&port) {
+            node.ports.push(port);    
+        }
+
+        self.emit(Event::Allocated { addr: a.clone(), group: node.group, port });
+        if !reserve {
+            return Some((port, None))
+        }
+
+        let token = rand::random::<u64>();
+        self.reservations
             .write()
             .await
-            .insert((node.group, port), a.clone());
+            .insert(token, Reservation::new(node.group, port + 1));
+        Some((port, Some(token)))
+    }
+
+    /// claim the reserved port from State.
+    ///
+    /// If the request contains a RESERVATION-TOKEN, the server uses the
+    /// previously reserved transport address corresponding to the included
+    /// token (if it is still available).  Note that the reservation is a
+    /// server-wide reservation and is not specific to a particular
+    /// allocation, since the Allocate request containing the RESERVATION-
+    /// TOKEN uses a different 5-tuple than the Allocate request that made
+    /// the reservation.
+    ///
+    /// the token is only valid in the group of the reserving 
+    /// node, an expired or unknown token returns None.
+    ///
+    /// ```no_run
+    /// use std::net::SocketAddr;
+    /// use std::sync::Arc;
+    /// use turn::argv::Argv;
+    /// use turn::broker::Broker;
+    ///
+    /// let addr = "127.0.0.1:8080".parse::<SocketAddr>().unwrap();
+    /// let argvure = Argv::generate().unwrap();
+    /// let broker = Broker::new(&argvure);
+    /// let state = State::new(&argvure, &broker);
+    ///
+    /// state.get_key(&addr, "panda");
+    /// assert!(state.claim_port(&addr, 0).is_none());
+    /// ```
+    #[rustfmt::skip]
+    pub async fn claim_port(&self, a: &Addr, token: u64) -> Option<u16> {
+        let mut ports = self.ports.write().await;
+        let mut nodes = self.nodes.write().await;
+        let node = nodes.get_mut(a)?;
+        let mut reservations = self.reservations.write().await;
+        match reservations.get(&token) {
+            Some(r) if r.group == node.group && !r.is_death() => (),
+            _ => return None
+        }
+
+        let port = reservations.remove(&token)?.port;
+        ports.insert((node.group, port), a.clone());
         if !node.ports.contains(&port) {
             node.ports.push(port);    
         }
-        
+
+        self.emit(Event::Allocated { addr: a.clone(), group: node.group, port });
         Some(port)
     }
     
@@ -316,6 +688,10 @@ impl State {
     /// "stateless stack approach".  Retransmitted CreatePermission
     /// requests will simply refresh the permissions.
     ///
+    /// the number of permissions of each node is limited, 
+    /// the least recently refreshed permission is evicted 
+    /// when the limit is reached.
+    ///
     /// ```no_run
     /// use std::net::SocketAddr;
     /// use std::sync::Arc;
@@ -332,31 +708,41 @@ impl State {
     /// state.get_key(&addr, "panda");
     /// state.get_key(&peer, "panda");
     ///
-    /// let addr_port = state.alloc_port(&addr).unwrap();
-    /// let peer_port = state.alloc_port(&peer).unwrap();
+    /// let addr_port = state.allocate(&addr).unwrap();
+    /// let peer_port = state.allocate(&peer).unwrap();
     ///
     /// assert!(state.bind_port(&peer, addr_port).is_some());
     /// assert!(state.bind_port(&addr, peer_port).is_some());
     /// ```
     #[rustfmt::skip]
     pub async fn bind_port(&self, a: &Addr, port: u16) -> Option<()> {
-        let g = self.nodes
-            .read()
-            .await
-            .get(a)?
-            .group;
+        let g = {
+            let nodes = self.nodes.read().await;
+            let node = nodes.get(a)?;
+            node.touch();
+            node.group
+        };
+
         let p = self.ports
             .read()
             .await
             .get(&(g, port))?
             .clone();
-        self.port_bonds
-            .write()
-            .await
+        let mut port_bonds = self.port_bonds.write().await;
+        let bonds = port_bonds
             .entry(a.clone())
-            .or_insert_with(|| HashMap::with_capacity(10))
-            .entry(p)
-            .or_insert(port);
+            .or_insert_with(|| HashMap::with_capacity(10));
+        if !bonds.contains_key(&p) && bonds.len() >= self.conf.permission_capacity {
+            let oldest = bonds.iter()
+                .min_by_key(|(_, (_, t))| *t)
+                .map(|(k, _)| k.clone());
+            if let Some(k) = oldest {
+                bonds.remove(&k);
+            }
+        }
+
+        bonds.insert(p, (port, Instant::now()));
+        self.emit(Event::PermissionCreated { addr: a.clone(), group: g, port });
         Some(())
     }
 
@@ -372,6 +758,12 @@ impl State {
     /// transaction would initially fail but succeed on a
     /// retransmission.
     ///
+    /// binding the same channel number to the same peer refreshes 
+    /// the channel, binding it to a different peer, or binding the 
+    /// peer to a different channel number is a conflict, as well as 
+    /// rebinding an expired channel number to a different peer 
+    /// within 5 minutes.
+    ///
     /// ```no_run
     /// use std::net::SocketAddr;
     /// use std::sync::Arc;
@@ -380,6 +772,7 @@ impl State {
     ///
     /// let addr = "127.0.0.1:8080".parse::<SocketAddr>().unwrap();
     /// let peer = "127.0.0.1:8081".parse::<SocketAddr>().unwrap();
+    /// let other = "127.0.0.1:8082".parse::<SocketAddr>().unwrap();
     ///
     /// let argvure = Argv::generate().unwrap();
     /// let broker = Broker::new(&argvure);
@@ -387,21 +780,69 @@ impl State {
     ///
     /// state.get_key(&addr, "panda");
     /// state.get_key(&peer, "panda");
+    /// state.get_key(&other, "panda");
+    ///
+    /// let addr_port = state.allocate(&addr).unwrap();
+    /// let peer_port = state.allocate(&peer).unwrap();
+    /// let other_port = state.allocate(&other).unwrap();
+    ///
+    /// assert_eq!(state.bind_channel(&peer, addr_port, 0x4000), Ok(ChannelBind::Created));
+    /// assert_eq!(state.bind_channel(&addr, peer_port, 0x4000), Ok(ChannelBind::Created));
+    ///
+    /// // refresh extends the expiry.
+    /// assert_eq!(state.bind_channel(&addr, peer_port, 0x4000), Ok(ChannelBind::Refreshed));
+    /// assert_eq!(state.get_channel_expiry(&addr, 0x4000), Some(600));
     ///
-    /// let addr_port = state.alloc_port(&addr).unwrap();
-    /// let peer_port = state.alloc_port(&peer).unwrap();
+    /// // the channel number is bound to a different peer.
+    /// assert_eq!(
+    ///     state.bind_channel(&addr, other_port, 0x4000), 
+    ///     Err(ChannelBindError::Conflict)
+    /// );
     ///
-    /// assert!(state.bind_channel(&peer, addr_port, 0x4000).is_some());
-    /// assert!(state.bind_channel(&addr, peer_port, 0x4000).is_some());
+    /// // expired, the channel number cannot be rebound too soon,
+    /// state.remove_channel(0, 0x4000);
+    /// assert_eq!(
+    ///     state.bind_channel(&addr, other_port, 0x4000), 
+    ///     Err(ChannelBindError::Conflict)
+    /// );
+    ///
+    /// // rebinding it to the same peer is allowed.
+    /// assert_eq!(state.bind_channel(&addr, peer_port, 0x4000), Ok(ChannelBind::Created));
     /// ```
     #[rustfmt::skip]
-    pub async fn bind_channel(&self, a: &Addr, p: u16, c: u16) -> Option<()> {
+    pub async fn bind_channel(&self, a: &Addr, p: u16, c: u16) -> Result<ChannelBind, ChannelBindError> {
         let ports = self.ports.read().await;
         let mut channels = self.channels.write().await;
         let mut nodes = self.nodes.write().await;
+        let mut channel_bonds = self.channel_bonds.write().await;
         let mut is_empty = false;
 
-        let node = nodes.get_mut(a)?;
+        let node = nodes.get_mut(a).ok_or(ChannelBindError::Unavailable)?;
+        node.touch();
+        let source = ports.get(&(node.group, p)).ok_or(ChannelBindError::Unavailable)?;
+        let bond = channel_bonds.get(&(a.clone(), c));
+        if bond.map(|b| b != source).unwrap_or(false) {
+            return Err(ChannelBindError::Conflict)
+        }
+
+        if node.channels
+            .iter()
+            .filter(|n| **n != c)
+            .any(|n| channel_bonds.get(&(a.clone(), *n)) == Some(source)) {
+            return Err(ChannelBindError::Conflict)
+        }
+
+        if let Some((peer, timer)) = self.channel_cooldowns.read().await.get(&(a.clone(), c)) {
+            if peer != source && timer.elapsed().as_secs() < CHANNEL_COOLDOWN {
+                return Err(ChannelBindError::Conflict)
+            }
+        }
+
+        let result = match bond {
+            Some(_) => ChannelBind::Refreshed,
+            None => ChannelBind::Created
+        };
+
         let channel = channels
             .entry((node.group, c))
             .or_insert_with(|| {
@@ -416,28 +857,57 @@ impl State {
         };
         
         if !channel.is_half() && !is_include {
-            return None
+            return Err(ChannelBindError::Unavailable)
         }
         
         if !is_include {
             channel.up(a);
         }
 
-        if !is_empty && is_include {
+        if !is_empty {
             channel.refresh();
         }
 
-        let source = ports.get(&(node.group, p))?;
         if !node.channels.contains(&c) {
             node.channels.push(c)
         }
 
-        self.channel_bonds
-            .write()
-            .await
+        channel_bonds
             .entry((a.clone(), c))
             .or_insert_with(|| source.clone());
-        Some(())
+        self.emit(Event::ChannelBound { 
+            addr: a.clone(), 
+            group: node.group, 
+            channel: c,
+            port: p
+        });
+
+        Ok(result)
+    }
+
+    /// get the remaining time (second) of the channel binding.
+    ///
+    /// ```no_run
+    /// use std::net::SocketAddr;
+    /// use std::sync::Arc;
+    /// use turn::argv::Argv;
+    /// use turn::broker::Broker;
+    ///
+    /// let addr = "127.0.0.1:8080".parse::<SocketAddr>().unwrap();
+    /// let argvure = Argv::generate().unwrap();
+    /// let broker = Broker::new(&argvure);
+    /// let state = State::new(&argvure, &broker);
+    ///
+    /// assert_eq!(state.get_channel_expiry(&addr, 0x4000), None);
+    /// ```
+    #[rustfmt::skip]
+    pub async fn get_channel_expiry(&self, a: &Addr, c: u16) -> Option<u64> {
+        let channels = self.channels.read().await;
+        let group = self.nodes.read().await.get(a)?.group;
+        channels
+            .get(&(group, c))
+            .filter(|x| x.includes(a))
+            .map(Channel::remaining)
     }
 
     /// refresh node lifetime.
@@ -475,6 +945,9 @@ impl State {
     /// allocation has already been deleted, but the client will treat
     /// this as equivalent to a success response (see below).
     ///
+    /// the granted remaining lifetime is returned, it is zero 
+    /// when the allocation is deleted or does not exist.
+    ///
     /// ```no_run
     /// use std::net::SocketAddr;
     /// use std::sync::Arc;
@@ -487,19 +960,106 @@ impl State {
     /// let state = State::new(&argvure, &broker);
     ///
     /// state.get_key(&addr, "panda");
-    /// state.refresh(&addr, 600);
-    /// state.refresh(&addr, 0);
+    /// assert_eq!(state.refresh(&addr, 60), DEFAULT_LIFETIME);
+    /// assert_eq!(state.refresh(&addr, 1200), 1200);
+    /// assert_eq!(state.refresh(&addr, 86400), MAX_LIFETIME);
+    /// assert_eq!(state.refresh(&addr, 0), 0);
     /// ```
     #[rustfmt::skip]
-    pub async fn refresh(&self, a: &Addr, delay: u32) {
+    pub async fn refresh(&self, a: &Addr, delay: u32) -> u32 {
         if delay == 0 { 
-            self.remove(a).await; 
-        } else if let Some(n) = self.nodes.write().await.get_mut(a) {
-            n.set_lifetime(delay);
+            self.remove_allocation(a).await;
+            return 0
         }
+
+        let delay = delay.clamp(DEFAULT_LIFETIME, MAX_LIFETIME);
+        let lifetime = match self.nodes.write().await.get_mut(a) {
+            Some(n) => {
+                n.set_lifetime(delay);
+                n.remaining()
+            },
+            None => return 0
+        };
+
+        self.emit(Event::Refreshed { addr: a.clone(), lifetime });
+        lifetime
     }
 
-    /// remove a node.
+    /// rebind an existing allocation to a new source address.
+    ///
+    /// a roaming client changes its source address mid-allocation, 
+    /// after it re-authenticates from the new address, the only 
+    /// alive allocation of the same group and credentials is moved 
+    /// to the new address, together with its ports, permissions 
+    /// and channels. 
+    /// the call must follow a successful integrity check, and the 
+    /// request must carry the nonce issued to the new address, it 
+    /// is a no-op when the node at the new address already owns 
+    /// ports.
+    #[rustfmt::skip]
+    pub async fn migrate(&self, a: &Addr, u: &str, nonce: &str) -> Option<Addr> {
+        if !self.nonces.is_issued(a, nonce).await {
+            return None
+        }
+
+        let old = {
+            let nodes = self.nodes.read().await;
+            self.find_migration(&nodes, a, u)?
+        };
+
+        let mut ports = self.ports.write().await;
+        let mut channels = self.channels.write().await;
+        let mut nodes = self.nodes.write().await;
+
+        // the nodes were unlocked in between, check again.
+        if self.find_migration(&nodes, a, u).as_ref() != Some(&old) {
+            return None
+        }
+
+        let node = nodes.remove(&old)?;
+        for p in &node.ports {
+            ports.insert((node.group, *p), a.clone());
+        }
+
+        for c in &node.channels {
+            if let Some(channel) = channels.get_mut(&(node.group, *c)) {
+                channel.replace(&old, a);
+            }
+        }
+
+        let mut port_bonds = self.port_bonds.write().await;
+        if let Some(bonds) = port_bonds.remove(&old) {
+            port_bonds.insert(a.clone(), bonds);
+        }
+
+        for bonds in port_bonds.values_mut() {
+            if let Some(bond) = bonds.remove(&old) {
+                bonds.insert(a.clone(), bond);
+            }
+        }
+
+        let mut channel_bonds = self.channel_bonds.write().await;
+        for c in &node.channels {
+            if let Some(source) = channel_bonds.remove(&(old.clone(), *c)) {
+                channel_bonds.insert((a.clone(), *c), source);
+            }
+        }
+
+        for source in channel_bonds.values_mut() {
+            if *source == old {
+                *source = a.clone();
+            }
+        }
+
+        nodes.insert(a.clone(), node);
+        self.nonces.remove(&old).await;
+        Some(old)
+    }
+
+    /// remove an allocation.
+    ///
+    /// the node, its relayed ports, channels, permissions 
+    /// and nonce are removed together.
     ///
     /// ```no_run
     /// use std::net::SocketAddr;
@@ -513,10 +1073,10 @@ impl State {
     /// let state = State::new(&argvure, &broker);
     ///
     /// state.get_key(&addr, "panda");
-    /// state.remove(&addr);
+    /// state.remove_allocation(&addr);
     /// ```
     #[rustfmt::skip]
-    pub async fn remove(&self, a: &Addr) {
+    pub async fn remove_allocation(&self, a: &Addr) {
         let mut ports = self.ports.write().await;
 
         let node = match self.nodes.write().await.remove(a) {
@@ -538,6 +1098,7 @@ impl State {
             .write()
             .await
             .remove(a);
+        self.emit(Event::Deleted { addr: a.clone(), group: node.group });
     }
     
     /// remove channel in State. 
@@ -558,16 +1119,21 @@ impl State {
     /// ```
     #[rustfmt::skip]
     pub async fn remove_channel(&self, g: u32, c: u16) -> Option<()> {
-        let mut channel_bonds = self.channel_bonds
+        let mut channels = self.channels
             .write()
             .await;
-        let mut channels = self.channels
+        let mut channel_bonds = self.channel_bonds
             .write()
             .await;
         let channel = channels
             .remove(&(g, c))?;
+        let mut channel_cooldowns = self.channel_cooldowns
+            .write()
+            .await;
         for a in channel {
-            channel_bonds.remove(&(a, c));
+            if let Some(peer) = channel_bonds.remove(&(a.clone(), c)) {
+                channel_cooldowns.insert((a, c), (peer, Instant::now()));
+            }
         }
         
         Some(())
@@ -575,6 +1141,34 @@ impl State {
     
     /// poll in state.
     ///
+    /// the expired nodes are removed, so are the nodes that have 
+    /// been idle for longer than the idle timeout, even if their 
+    /// lifetime has not expired, this reclaims the ports of the 
+    /// zombie clients that keep no traffic on the allocation.
+    ///
+    /// ```no_run
+    /// use std::net::SocketAddr;
+    /// use std::sync::Arc;
+    /// use turn::argv::Argv;
+    /// use turn::broker::Broker;
+    /// use stun::util::RustCrypto;
+    ///
+    /// let addr = Arc::new("127.0.0.1:8080".parse::<SocketAddr>().unwrap());
+    /// let mut argvure = Argv::generate().unwrap();
+    /// argvure.idle_timeout = Some(1);
+    /// let argvure = Arc::new(argvure);
+    /// let broker = Broker::new(&argvure);
+    /// let (state, _) = State::new(&argvure, &broker, RustCrypto);
+    ///
+    /// state.get_key(&addr, "panda");
+    /// state.allocate(&addr).unwrap();
+    /// assert_eq!(state.refresh(&addr, 600), 600);
+    ///
+    /// sleep(Duration::from_secs(2)).await;
+    /// state.poll().await;
+    /// assert!(state.get_allocation(&addr).await.is_none());
+    /// ```
+    ///
     /// ```no_run
     /// use turn::argv::Argv;
     /// use turn::broker::Broker;
@@ -595,11 +1189,11 @@ impl State {
             .read()
             .await
             .iter()
-            .filter(|(_, v)| v.is_death())
+            .filter(|(_, v)| v.is_death() || self.is_idle(v))
             .map(|(k, _)| k.clone())
             .collect::<Vec<Addr>>();
         for a in &fail_nodes {
-            self.remove(a).await;
+            self.remove_allocation(a).await;
         }
         
         let fail_channels = self.channels
@@ -612,6 +1206,26 @@ impl State {
         for (g, c) in fail_channels {
             self.remove_channel(g, c).await;
         }
+
+        self.channel_cooldowns
+            .write()
+            .await
+            .retain(|_, (_, t)| t.elapsed().as_secs() < CHANNEL_COOLDOWN);
+
+        let fail_reservations = self.reservations
+            .read()
+            .await
+            .iter()
+            .filter(|(_, v)| v.is_death())
+            .map(|(k, _)| *k)
+            .collect::<Vec<u64>>();
+        for t in fail_reservations {
+            if let Some(r) = self.reservations.write().await.remove(&t) {
+                self.buckets.remove(r.group, r.port).await;
+            }
+        }
+
+        self.nonces.poll().await;
     }
 
     /// auto run state poll.
@@ -640,21 +1254,524 @@ impl State {
         Ok(())
     }
     
-    pub fn new(c: &Arc<Argv>, b: &Arc<Broker>) -> Arc<Self> {
-        Arc::new(Self {
+    /// find the alive node that the new address can take over, 
+    /// the node at the new address must own no ports yet. 
+    ///
+    /// the node must be the only allocation of the credentials, 
+    /// when a user has several allocations, it is unknown which 
+    /// one the new address belongs to, and the new address could 
+    /// take over another session, so there is no migration.
+    #[rustfmt::skip]
+    fn find_migration(&self, nodes: &HashMap<Addr, Node>, a: &Addr, u: &str) -> Option<Addr> {
+        let current = nodes.get(a)?;
+        if !current.ports.is_empty() {
+            return None
+        }
+
+        let mut candidates = nodes
+            .iter()
+            .filter(|(k, n)| {
+                *k != a && 
+                !n.is_death() &&
+                !n.ports.is_empty() &&
+                n.username == u &&
+                n.group == current.group &&
+                n.get_password() == current.get_password()
+            })
+            .map(|(k, _)| k);
+        match (candidates.next(), candidates.next()) {
+            (Some(k), None) => Some(k.clone()),
+            _ => None
+        }
+    }
+
+    /// whether the node has been idle for longer than the idle 
+    /// timeout, it is never idle when the timeout is not set.
+    fn is_idle(&self, node: &Node) -> bool {
+        match self.conf.idle_timeout {
+            Some(t) if node.idle() >= t => {
+                log::info!(
+                    "allocation idle: username={}, idle={}", 
+                    node.username, 
+                    node.idle()
+                );
+
+                true
+            },
+            _ => false
+        }
+    }
+
+    /// emit an allocation event.
+    ///
+    /// the emission never blocks, the event is dropped 
+    /// when the channel is full or the subscriber is gone.
+    fn emit(&self, event: Event) {
+        let _ = self.events.try_send(event);
+    }
+    
+    /// create state with the hashing backend.
+    ///
+    /// the receiver of the allocation events is returned 
+    /// together with the state, an external subscriber 
+    /// takes it at startup.
+    ///
+    /// ```no_run
+    /// use std::net::SocketAddr;
+    /// use std::sync::Arc;
+    /// use turn::argv::Argv;
+    /// use turn::broker::Broker;
+    /// use stun::util::RustCrypto;
+    ///
+    /// let addr = Arc::new("127.0.0.1:8080".parse::<SocketAddr>().unwrap());
+    /// let argvure = Argv::generate().unwrap();
+    /// let broker = Broker::new(&argvure);
+    /// let (state, mut events) = State::new(&argvure, &broker, RustCrypto);
+    ///
+    /// state.get_key(&addr, "panda");
+    /// let port = state.allocate(&addr).unwrap();
+    /// assert_eq!(events.try_recv().unwrap(), Event::Allocated { 
+    ///     addr, 
+    ///     group: 0, 
+    ///     port 
+    /// });
+    /// ```
+    pub fn new<T: Crypto + 'static>(
+        c: &Arc<Argv>, 
+        b: &Arc<Broker>, 
+        crypto: T
+    ) -> (Arc<Self>, mpsc::Receiver<Event>) {
+        let (events, receiver) = mpsc::channel(EVENT_CAPACITY);
+        let realms = c.realm_map
+            .iter()
+            .map(|m| (m.ip, m.realm.clone()))
+            .collect();
+        let state = Arc::new(Self {
             conf: c.clone(),
             broker: b.clone(),
+            crypto: Box::new(crypto),
+            realms,
             buckets: BucketTable::new(),
-            nonces: NonceTable::new(),
+            nonces: NonceTable::new(c.nonce_capacity),
             channel_bonds: create_table(),
+            channel_cooldowns: create_table(),
             channels: create_table(),
             port_bonds: create_table(),
             ports: create_table(),
-            nodes: create_table()
-        })
+            reservations: create_table(),
+            nodes: create_table(),
+            events,
+        });
+
+        (state, receiver)
     }
 }
 
 fn create_table<K, V>() -> RwLock<HashMap<K, V>> {
     RwLock::new(HashMap::with_capacity(1024))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+
+    #[tokio::test]
+    async fn emit_events() {
+        let c = mock::argv(&[]);
+        let (state, mut events) = mock::state(&c);
+        let addr = mock::node(&state, "127.0.0.1:8080").await;
+        let peer = mock::node(&state, "127.0.0.1:8081").await;
+
+        let port = state.allocate(&addr).await.unwrap();
+        assert_eq!(events.recv().await, Some(Event::Allocated { addr: addr.clone(), group: 0, port }));
+        let peer_port = state.allocate(&peer).await.unwrap();
+        assert_eq!(events.recv().await, Some(Event::Allocated { addr: peer.clone(), group: 0, port: peer_port }));
+
+        state.bind_port(&addr, peer_port).await.unwrap();
+        assert_eq!(
+            events.recv().await, 
+            Some(Event::PermissionCreated { addr: addr.clone(), group: 0, port: peer_port })
+        );
+
+        state.bind_channel(&addr, peer_port, 0x4000).await.unwrap();
+        assert_eq!(
+            events.recv().await, 
+            Some(Event::ChannelBound { addr: addr.clone(), group: 0, port: peer_port, channel: 0x4000 })
+        );
+
+        let lifetime = state.refresh(&addr, 1200).await;
+        assert_eq!(events.recv().await, Some(Event::Refreshed { addr: addr.clone(), lifetime }));
+
+        state.remove_allocation(&addr).await;
+        assert_eq!(events.recv().await, Some(Event::Deleted { addr, group: 0 }));
+    }
+
+    #[tokio::test]
+    async fn drop_events_when_full() {
+        let c = mock::argv(&[]);
+        let (state, mut events) = mock::state(&c);
+        let addr = mock::node(&state, "127.0.0.1:8080").await;
+        for _ in 0..EVENT_CAPACITY + 10 {
+            state.refresh(&addr, DEFAULT_LIFETIME).await;
+        }
+
+        let mut count = 0;
+        while events.try_recv().is_ok() {
+            count += 1;
+        }
+
+        assert_eq!(count, EVENT_CAPACITY);
+    }
+
+    #[tokio::test]
+    async fn realm_map() {
+        let c = mock::argv(&["--realm", "localhost", "--realm-map", "127.0.0.2=tenant"]);
+        let (state, _) = mock::state(&c);
+        let addr = mock::node(&state, "127.0.0.1:8080").await;
+        let peer = mock::node(&state, "127.0.0.2:8080").await;
+        assert_eq!(state.get_realm(&addr), "localhost");
+        assert_eq!(state.get_realm(&peer), "tenant");
+
+        let key = state.get_crypto().long_key(mock::USERNAME, mock::PASSWORD, "tenant");
+        assert_eq!(*state.get_key(&peer, mock::USERNAME).await.unwrap(), key);
+    }
+
+    #[tokio::test]
+    async fn permission_capacity() {
+        let c = mock::argv(&["--permission-capacity", "2"]);
+        let (state, _) = mock::state(&c);
+        let addr = mock::node(&state, "127.0.0.1:8080").await;
+        let mut ports = Vec::new();
+        for port in 8081..8084 {
+            let peer = mock::node(&state, &format!("127.0.0.1:{}", port)).await;
+            ports.push(state.allocate(&peer).await.unwrap());
+        }
+
+        for p in &ports {
+            state.bind_port(&addr, *p).await.unwrap();
+        }
+
+        // the least recently refreshed permission is evicted.
+        let mut permissions = state.get_allocation(&addr).await.unwrap().permissions;
+        permissions.sort_unstable();
+        let mut expected = ports[1..].to_vec();
+        expected.sort_unstable();
+        assert_eq!(permissions, expected);
+    }
+
+    #[tokio::test]
+    async fn reserve_and_claim() {
+        let c = mock::argv(&[]);
+        let (state, _) = mock::state(&c);
+        let addr = mock::node(&state, "127.0.0.1:8080").await;
+        let peer = mock::node(&state, "127.0.0.1:8081").await;
+
+        let (port, token) = state.alloc_even_port(&addr, true).await.unwrap();
+        assert_eq!(port % 2, 0);
+
+        let token = token.unwrap();
+        assert_eq!(state.claim_port(&peer, token).await, Some(port + 1));
+        assert_eq!(state.claim_port(&peer, token).await, None);
+        assert_eq!(state.get_allocation(&peer).await.unwrap().ports, vec![port + 1]);
+
+        let (_, token) = state.alloc_even_port(&addr, false).await.unwrap();
+        assert_eq!(token, None);
+    }
+
+    #[tokio::test]
+    async fn claim_expired_reservation() {
+        let c = mock::argv(&[]);
+        let (state, _) = mock::state(&c);
+        let addr = mock::node(&state, "127.0.0.1:8080").await;
+        let (port, token) = state.alloc_even_port(&addr, true).await.unwrap();
+        let token = token.unwrap();
+        if let Some(r) = state.reservations.write().await.get_mut(&token) {
+            r.timer -= Duration::from_secs(30);
+        }
+
+        let peer = mock::node(&state, "127.0.0.1:8081").await;
+        assert_eq!(state.claim_port(&peer, token).await, None);
+
+        // the reserved port is released by the sweep.
+        state.poll().await;
+        assert!(state.reservations.read().await.is_empty());
+        assert_eq!(state.get_allocation(&addr).await.unwrap().ports, vec![port]);
+    }
+
+    #[tokio::test]
+    async fn migrate_allocation() {
+        let c = mock::argv(&[]);
+        let (state, _) = mock::state(&c);
+        let old = mock::node(&state, "127.0.0.1:8080").await;
+        let peer = mock::node_with(&state, "127.0.0.1:8081", mock::OTHER_USERNAME).await;
+        let port = state.allocate(&old).await.unwrap();
+        let peer_port = state.allocate(&peer).await.unwrap();
+        state.bind_port(&old, peer_port).await.unwrap();
+        state.bind_port(&peer, port).await.unwrap();
+        state.bind_channel(&old, peer_port, 0x4000).await.unwrap();
+        state.bind_channel(&peer, port, 0x4001).await.unwrap();
+
+        let addr = mock::node(&state, "127.0.0.1:9090").await;
+        let nonce = state.get_nonce(&addr).await;
+        assert_eq!(state.migrate(&addr, mock::USERNAME, &nonce).await, Some(old.clone()));
+        assert!(state.get_allocation(&old).await.is_none());
+
+        let allocation = state.get_allocation(&addr).await.unwrap();
+        assert_eq!(allocation.ports, vec![port]);
+        assert_eq!(allocation.permissions, vec![peer_port]);
+        assert_eq!(allocation.channels.get(&0x4000), Some(&peer));
+
+        // the peer follows the allocation.
+        assert_eq!(state.get_port_bond(&peer, port).await, Some(addr.clone()));
+        assert_eq!(state.get_bond_port(&peer, &addr).await, Some(peer_port));
+        assert_eq!(state.get_channel_bond(&peer, 0x4001).await, Some(addr.clone()));
+        assert!(state.is_permitted(&peer, &addr, 0x4001).await);
+        assert_eq!(state.get_channel_expiry(&addr, 0x4000).await, Some(600));
+    }
+
+    #[tokio::test]
+    async fn migrate_requires_issued_nonce() {
+        let c = mock::argv(&[]);
+        let (state, _) = mock::state(&c);
+        let old = mock::node(&state, "127.0.0.1:8080").await;
+        let port = state.allocate(&old).await.unwrap();
+
+        let addr = mock::node(&state, "127.0.0.1:9090").await;
+        let other = state.get_nonce(&old).await;
+        assert_eq!(state.migrate(&addr, mock::USERNAME, "").await, None);
+        assert_eq!(state.migrate(&addr, mock::USERNAME, &other).await, None);
+        assert_eq!(state.get_allocation(&old).await.unwrap().ports, vec![port]);
+
+        // a node with an allocation of its own is not migrated.
+        let nonce = state.get_nonce(&addr).await;
+        state.allocate(&addr).await.unwrap();
+        assert_eq!(state.migrate(&addr, mock::USERNAME, &nonce).await, None);
+        assert_eq!(state.get_allocation(&old).await.unwrap().ports, vec![port]);
+    }
+
+    #[tokio::test]
+    async fn migrate_ambiguous() {
+        let c = mock::argv(&[]);
+        let (state, _) = mock::state(&c);
+        let first = mock::node(&state, "127.0.0.1:8080").await;
+        let second = mock::node(&state, "127.0.0.1:8081").await;
+        let first_port = state.allocate(&first).await.unwrap();
+        let second_port = state.allocate(&second).await.unwrap();
+
+        // the user has two allocations, neither is taken over.
+        let addr = mock::node(&state, "127.0.0.1:9090").await;
+        let nonce = state.get_nonce(&addr).await;
+        assert_eq!(state.migrate(&addr, mock::USERNAME, &nonce).await, None);
+        assert_eq!(state.get_allocation(&first).await.unwrap().ports, vec![first_port]);
+        assert_eq!(state.get_allocation(&second).await.unwrap().ports, vec![second_port]);
+        assert!(state.get_allocation(&addr).await.unwrap().ports.is_empty());
+
+        state.remove_allocation(&second).await;
+        assert_eq!(state.migrate(&addr, mock::USERNAME, &nonce).await, Some(first.clone()));
+        assert_eq!(state.get_allocation(&addr).await.unwrap().ports, vec![first_port]);
+    }
+
+    #[tokio::test]
+    async fn concurrent_operations() {
+        let c = mock::argv(&[]);
+        let (state, _) = mock::state(&c);
+        let peer = mock::node_with(&state, "127.0.0.1:8081", mock::OTHER_USERNAME).await;
+        let peer_port = state.allocate(&peer).await.unwrap();
+
+        // the operations take the tables in the same order, 
+        // they never wait for each other.
+        let tasks = (0..32u16).map(|i| {
+            let state = state.clone();
+            let peer = peer.clone();
+            tokio::spawn(async move {
+                let old = mock::node(&state, &format!("127.0.0.1:{}", 10000 + i)).await;
+                let port = state.allocate(&old).await.unwrap_or_default();
+                state.bind_port(&old, peer_port).await;
+                state.bind_port(&peer, port).await;
+                let _ = state.bind_channel(&old, peer_port, 0x4000 + i).await;
+
+                let addr = mock::node(&state, &format!("127.0.0.1:{}", 20000 + i)).await;
+                let nonce = state.get_nonce(&addr).await;
+                state.migrate(&addr, mock::USERNAME, &nonce).await;
+                state.get_allocation(&addr).await;
+                state.refresh(&addr, 0).await;
+                state.poll().await;
+            })
+        }).collect::<Vec<_>>();
+
+        let all = async {
+            for task in tasks {
+                task.await.unwrap();
+            }
+        };
+
+        tokio::time::timeout(Duration::from_secs(10), all).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn is_permitted() {
+        let c = mock::argv(&[]);
+        let (state, _) = mock::state(&c);
+        let addr = mock::node(&state, "127.0.0.1:8080").await;
+        let peer = mock::node_with(&state, "127.0.0.1:8081", mock::OTHER_USERNAME).await;
+        let port = state.allocate(&addr).await.unwrap();
+        let peer_port = state.allocate(&peer).await.unwrap();
+        assert!(!state.is_permitted(&addr, &peer, 0x4000).await);
+
+        state.bind_port(&peer, port).await.unwrap();
+        assert!(state.is_permitted(&addr, &peer, 0x4000).await);
+        assert!(!state.is_permitted(&peer, &addr, 0x4000).await);
+
+        // the channel binding of the client to the peer 
+        // permits the peer on the same channel number.
+        state.bind_channel(&addr, peer_port, 0x4000).await.unwrap();
+        assert!(state.is_permitted(&peer, &addr, 0x4000).await);
+        assert!(!state.is_permitted(&peer, &addr, 0x4001).await);
+    }
+
+    #[tokio::test]
+    async fn refresh_clamp() {
+        let c = mock::argv(&[]);
+        let (state, _) = mock::state(&c);
+        let addr = mock::node(&state, "127.0.0.1:8080").await;
+        state.allocate(&addr).await.unwrap();
+
+        assert_eq!(state.refresh(&addr, 60).await, DEFAULT_LIFETIME);
+        assert_eq!(state.refresh(&addr, 1200).await, 1200);
+        assert_eq!(state.refresh(&addr, 86400).await, MAX_LIFETIME);
+        let remaining = state.get_allocation(&addr).await.unwrap().remaining();
+        assert!(remaining <= MAX_LIFETIME as u64 && remaining >= MAX_LIFETIME as u64 - 1);
+
+        assert_eq!(state.refresh(&addr, 0).await, 0);
+        assert!(state.get_allocation(&addr).await.is_none());
+        assert_eq!(state.refresh(&addr, 600).await, 0);
+    }
+
+    #[tokio::test]
+    async fn bind_channel() {
+        let c = mock::argv(&[]);
+        let (state, _) = mock::state(&c);
+        let addr = mock::node(&state, "127.0.0.1:8080").await;
+        let peer = mock::node(&state, "127.0.0.1:8081").await;
+        let other = mock::node(&state, "127.0.0.1:8082").await;
+        state.allocate(&addr).await.unwrap();
+        let peer_port = state.allocate(&peer).await.unwrap();
+        let other_port = state.allocate(&other).await.unwrap();
+
+        assert_eq!(state.bind_channel(&addr, peer_port, 0x4000).await, Ok(ChannelBind::Created));
+        assert_eq!(state.bind_channel(&addr, peer_port, 0x4000).await, Ok(ChannelBind::Refreshed));
+        assert_eq!(state.get_channel_expiry(&addr, 0x4000).await, Some(600));
+
+        // the number and the peer are bound to each other.
+        assert_eq!(state.bind_channel(&addr, other_port, 0x4000).await, Err(ChannelBindError::Conflict));
+        assert_eq!(state.bind_channel(&addr, peer_port, 0x4001).await, Err(ChannelBindError::Conflict));
+        assert_eq!(state.bind_channel(&addr, 50000, 0x4001).await, Err(ChannelBindError::Unavailable));
+        assert_eq!(state.bind_channel(&addr, other_port, 0x4001).await, Ok(ChannelBind::Created));
+    }
+
+    #[tokio::test]
+    async fn channel_cooldown() {
+        let c = mock::argv(&[]);
+        let (state, _) = mock::state(&c);
+        let addr = mock::node(&state, "127.0.0.1:8080").await;
+        let peer = mock::node(&state, "127.0.0.1:8081").await;
+        let other = mock::node(&state, "127.0.0.1:8082").await;
+        state.allocate(&addr).await.unwrap();
+        let peer_port = state.allocate(&peer).await.unwrap();
+        let other_port = state.allocate(&other).await.unwrap();
+
+        state.bind_channel(&addr, peer_port, 0x4000).await.unwrap();
+        state.remove_channel(0, 0x4000).await.unwrap();
+        assert_eq!(state.get_channel_bond(&addr, 0x4000).await, None);
+
+        // the expired number is not bound to a different peer 
+        // during the cooldown, but the same peer can rebind it.
+        assert_eq!(state.bind_channel(&addr, other_port, 0x4000).await, Err(ChannelBindError::Conflict));
+        assert_eq!(state.bind_channel(&addr, peer_port, 0x4000).await, Ok(ChannelBind::Created));
+
+        state.remove_channel(0, 0x4000).await.unwrap();
+        for (_, t) in state.channel_cooldowns.write().await.values_mut() {
+            *t -= Duration::from_secs(CHANNEL_COOLDOWN);
+        }
+
+        assert_eq!(state.bind_channel(&addr, other_port, 0x4000).await, Ok(ChannelBind::Created));
+        state.poll().await;
+        assert!(state.channel_cooldowns.read().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn allocation_lifecycle() {
+        let c = mock::argv(&[]);
+        let (state, _) = mock::state(&c);
+        let addr = mock::node(&state, "127.0.0.1:8080").await;
+        let peer = mock::node(&state, "127.0.0.1:8081").await;
+        let port = state.allocate(&addr).await.unwrap();
+        let peer_port = state.allocate(&peer).await.unwrap();
+
+        state.bind_port(&addr, peer_port).await.unwrap();
+        state.bind_channel(&addr, peer_port, 0x4000).await.unwrap();
+        state.add_relayed(&addr, &peer, 100).await;
+
+        let allocation = state.get_allocation(&addr).await.unwrap();
+        assert_eq!(allocation.addr, addr);
+        assert_eq!(allocation.group, 0);
+        assert_eq!(allocation.username, mock::USERNAME);
+        assert_eq!(allocation.ports, vec![port]);
+        assert_eq!(allocation.permissions, vec![peer_port]);
+        assert_eq!(allocation.channels.get(&0x4000), Some(&peer));
+        assert_eq!((allocation.sent, allocation.received), (100, 0));
+        assert!(allocation.remaining() <= DEFAULT_LIFETIME as u64);
+
+        let peer_allocation = state.get_allocation(&peer).await.unwrap();
+        assert_eq!((peer_allocation.sent, peer_allocation.received), (0, 100));
+        assert!(peer_allocation.permissions.is_empty());
+        assert!(peer_allocation.channels.is_empty());
+
+        assert!(allocation.to_string().starts_with(&format!(
+            "addr=127.0.0.1:8080, group=0, username={}, ports=[{}], lifetime=", 
+            mock::USERNAME, 
+            port
+        )));
+        assert!(allocation.to_string().ends_with(&format!(
+            "permissions=[{}], channels=1, sent=100, received=0",
+            peer_port
+        )));
+
+        state.remove_allocation(&addr).await;
+        assert!(state.get_allocation(&addr).await.is_none());
+        assert_eq!(state.get_channel_bond(&addr, 0x4000).await, None);
+        assert!(state.get_allocation(&peer).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn reclaim_idle() {
+        tokio::time::pause();
+        let c = mock::argv(&["--idle-timeout", "1"]);
+        let (state, _) = mock::state(&c);
+        let idle = mock::node(&state, "127.0.0.1:8080").await;
+        let sender = mock::node(&state, "127.0.0.1:8081").await;
+        let receiver = mock::node(&state, "127.0.0.1:8082").await;
+
+        let c = mock::argv(&[]);
+        let (default, _) = mock::state(&c);
+        let addr = mock::node(&default, "127.0.0.1:8080").await;
+
+        for a in [&idle, &sender, &receiver] {
+            state.allocate(a).await.unwrap();
+        }
+
+        default.allocate(&addr).await.unwrap();
+        tokio::time::advance(Duration::from_secs(2)).await;
+        state.add_relayed(&sender, &receiver, 100).await;
+        state.poll().await;
+        default.poll().await;
+
+        // the lifetime has not expired, only the 
+        // allocation without activity is reclaimed.
+        assert!(state.get_allocation(&idle).await.is_none());
+        assert!(state.get_allocation(&sender).await.is_some());
+        assert!(state.get_allocation(&receiver).await.is_some());
+        assert!(default.get_allocation(&addr).await.is_some());
+    }
+}
@@ -1,38 +1,63 @@
-use tokio::time::Instant;
-use std::sync::Arc;
+use tokio::time::{
+    Duration,
+    Instant
+};
+
+use std::sync::{
+    atomic::{
+        AtomicU64,
+        Ordering
+    },
+    Arc
+};
+
+use super::DEFAULT_LIFETIME;
 
 /// turn node session.
 ///
 /// * the authentication information.
+/// * the username of the authentication.
 /// * the port bind table.
 /// * the channel alloc table.
 /// * the group number.
 /// * the time-to-expiry for each relayed transport address.
+/// * the bytes relayed from and to the node.
+/// * the last activity of the node.
 pub struct Node {
     pub channels: Vec<u16>,
     pub ports: Vec<u16>,
     pub group: u32,
+    pub username: String,
     timer: Instant,
     lifetime: u64,
-    password: Arc<[u8; 16]>
+    password: Arc<[u8; 16]>,
+    sent: AtomicU64,
+    received: AtomicU64,
+    created: Instant,
+    active: AtomicU64,
 }
 
 impl Node {
     /// create node session.
     ///
-    /// node session from group number and long key.
+    /// node session from group number, username and long key.
     ///
     /// ```no_run
     /// let key = stun::util::long_key("panda", "panda", "raspberry");
-    /// // Node::new(0, key.clone());
+    /// // Node::new(0, "panda", key.clone());
     /// ```
-    pub fn new(group: u32, password: [u8; 16]) -> Self {
+    pub fn new(group: u32, username: &str, password: [u8; 16]) -> Self {
         Self {
             channels: Vec::with_capacity(5),
             ports: Vec::with_capacity(10),
             timer: Instant::now(),
             password: Arc::new(password),
-            lifetime: 600,
+            username: username.to_string(),
+            lifetime: DEFAULT_LIFETIME as u64,
+            sent: AtomicU64::new(0),
+            received: AtomicU64::new(0),
+            created: Instant::now(),
+            active: AtomicU64::new(0),
             group,
         }
     }
@@ -43,19 +68,113 @@ impl Node {
     ///
     /// ```no_run
     /// let key = stun::util::long_key("panda", "panda", "raspberry");
-    /// let mut node = Node::new(0, key.clone());
+    /// let mut node = Node::new(0, "panda", key.clone());
     /// node.set_lifetime(600);
     /// ```
     pub fn set_lifetime(&mut self, delay: u32) {
         self.lifetime = delay as u64;
         self.timer = Instant::now();
+        self.touch();
+    }
+
+    /// the remaining lifetime (second) of the node.
+    ///
+    /// ```no_run
+    /// let key = stun::util::long_key("panda", "panda", "raspberry");
+    /// let mut node = Node::new(0, "panda", key.clone());
+    /// node.set_lifetime(600);
+    /// assert!(node.remaining() <= 600);
+    /// ```
+    pub fn remaining(&self) -> u32 {
+        self.lifetime.saturating_sub(self.timer.elapsed().as_secs()) as u32
+    }
+
+    /// the time-to-expiry deadline of the node.
+    ///
+    /// ```no_run
+    /// let key = stun::util::long_key("panda", "panda", "raspberry");
+    /// let node = Node::new(0, "panda", key.clone());
+    /// assert!(node.deadline() > Instant::now());
+    /// ```
+    pub fn deadline(&self) -> Instant {
+        self.timer + Duration::from_secs(self.lifetime)
+    }
+
+    /// count the bytes relayed from the node.
+    ///
+    /// the counters are atomic, they are updated 
+    /// without the write lock of the node table.
+    ///
+    /// ```no_run
+    /// let key = stun::util::long_key("panda", "panda", "raspberry");
+    /// let node = Node::new(0, "panda", key.clone());
+    /// node.add_sent(100);
+    /// assert_eq!(node.get_sent(), 100);
+    /// ```
+    pub fn add_sent(&self, size: usize) {
+        self.sent.fetch_add(size as u64, Ordering::Relaxed);
+        self.touch();
+    }
+
+    /// count the bytes relayed to the node.
+    ///
+    /// ```no_run
+    /// let key = stun::util::long_key("panda", "panda", "raspberry");
+    /// let node = Node::new(0, "panda", key.clone());
+    /// node.add_received(100);
+    /// assert_eq!(node.get_received(), 100);
+    /// ```
+    pub fn add_received(&self, size: usize) {
+        self.received.fetch_add(size as u64, Ordering::Relaxed);
+        self.touch();
+    }
+
+    /// the bytes relayed from the node.
+    pub fn get_sent(&self) -> u64 {
+        self.sent.load(Ordering::Relaxed)
+    }
+
+    /// the bytes relayed to the node.
+    pub fn get_received(&self) -> u64 {
+        self.received.load(Ordering::Relaxed)
+    }
+
+    /// record the activity of the node.
+    ///
+    /// a refresh, a permission, a channel binding or the data 
+    /// relayed from or to the node is an activity.
+    ///
+    /// ```no_run
+    /// let key = stun::util::long_key("panda", "panda", "raspberry");
+    /// let node = Node::new(0, "panda", key.clone());
+    /// node.touch();
+    /// assert_eq!(node.idle(), 0);
+    /// ```
+    pub fn touch(&self) {
+        let elapsed = self.created.elapsed().as_secs();
+        self.active.store(elapsed, Ordering::Relaxed);
+    }
+
+    /// the time (second) since the last activity of the node.
+    ///
+    /// ```no_run
+    /// let key = stun::util::long_key("panda", "panda", "raspberry");
+    /// let node = Node::new(0, "panda", key.clone());
+    /// sleep(Duration::from_secs(2)).await;
+    /// assert!(node.idle() >= 2);
+    /// node.add_received(100);
+    /// assert_eq!(node.idle(), 0);
+    /// ```
+    pub fn idle(&self) -> u64 {
+        let active = self.active.load(Ordering::Relaxed);
+        self.created.elapsed().as_secs().saturating_sub(active)
     }
 
     /// whether the node is dead.
     ///
     /// ```no_run
     /// let key = stun::util::long_key("panda", "panda", "raspberry");
-    /// let mut node = Node::new(0, key.clone());
+    /// let mut node = Node::new(0, "panda", key.clone());
     /// node.set_lifetime(600);
     /// assert!(!node.is_death());
     /// ```
@@ -71,10 +190,37 @@ impl Node {
     ///
     /// ```no_run
     /// let key = stun::util::long_key("panda", "panda", "raspberry");
-    /// let node = Node::new(0, key.clone());
+    /// let node = Node::new(0, "panda", key.clone());
     /// assert_eq!(!node.get_password(), Arc::new(key));
     /// ```
     pub fn get_password(&self) -> Arc<[u8; 16]> {
         self.password.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idle() {
+        let mut node = Node::new(0, "panda", [0u8; 16]);
+        assert_eq!(node.idle(), 0);
+
+        node.created -= Duration::from_secs(10);
+        assert_eq!(node.idle(), 10);
+
+        node.add_received(100);
+        assert_eq!(node.idle(), 0);
+
+        node.created -= Duration::from_secs(10);
+        assert_eq!(node.idle(), 10);
+        node.add_sent(100);
+        assert_eq!(node.idle(), 0);
+
+        node.created -= Duration::from_secs(10);
+        node.set_lifetime(600);
+        assert_eq!(node.idle(), 0);
+        assert!(!node.is_death());
+    }
+}
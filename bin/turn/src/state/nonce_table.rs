@@ -1,6 +1,9 @@
 use super::Addr;
 use std::{
-    collections::HashMap,
+    collections::{
+        HashMap,
+        VecDeque
+    },
     sync::Arc
 };
 
@@ -32,14 +35,34 @@ pub struct Nonce {
 }
 
 /// Nonce table.
+///
+/// the table is bounded, when the table is full, the oldest 
+/// nonce is evicted. the nonces are kept in the order they 
+/// were issued, and they all live for the same time, so the 
+/// oldest nonce is also the first to die, the dead nonces 
+/// are swept by `poll`.
 pub struct NonceTable {
-    raw: RwLock<HashMap<Addr, Nonce>>
+    raw: RwLock<Table>,
+    capacity: usize
+}
+
+/// the nonces and their issue order.
+///
+/// an entry of the order is stale when the nonce was removed 
+/// or reissued since, it is skipped when it is reached.
+struct Table {
+    nonces: HashMap<Addr, Nonce>,
+    order: VecDeque<(Addr, Instant)>,
 }
 
 impl NonceTable {
-    pub fn new() -> Self {
+    pub fn new(capacity: usize) -> Self {
         Self {
-            raw: RwLock::new(HashMap::with_capacity(1024))
+            raw: RwLock::new(Table {
+                nonces: HashMap::with_capacity(capacity.min(1024)),
+                order: VecDeque::with_capacity(capacity.min(1024)),
+            }),
+            capacity
         }
     }
     
@@ -51,22 +74,73 @@ impl NonceTable {
     /// use std::net::SocketAddr;
     /// 
     /// let addr = "127.0.0.1:1080".parse::<SocketAddr>().unwrap(); 
-    /// let nonce_table = NonceTable::new();
+    /// let nonce_table = NonceTable::new(1024);
     /// // nonce_table.get(&addr)
     /// ```
+    ///
+    /// the table does not grow beyond the capacity:
+    ///
+    /// ```no_run
+    /// use std::net::SocketAddr;
+    /// use std::sync::Arc;
+    ///
+    /// let nonce_table = NonceTable::new(100);
+    /// for port in 1000..2000 {
+    ///     let addr = Arc::new(SocketAddr::from(([127, 0, 0, 1], port)));
+    ///     nonce_table.get(&addr).await;
+    /// }
+    ///
+    /// assert!(nonce_table.raw.read().await.nonces.len() <= 100);
+    /// ```
+    #[rustfmt::skip]
     pub async fn get(&self, a: &Addr) -> Arc<String> {
-        if let Some(n) = self.raw.read().await.get(a) {
+        if let Some(n) = self.raw.read().await.nonces.get(a) {
             if !n.is_death() {
                 return n.unwind()   
             }
         }
 
+        let mut raw = self.raw.write().await;
+        if let Some(n) = raw.nonces.get(a) {
+            if !n.is_death() {
+                return n.unwind()
+            }
+        } else if raw.nonces.len() >= self.capacity {
+            raw.evict();
+        }
+
+        let nonce = Nonce::new();
+        let value = nonce.unwind();
+        raw.order.push_back((a.clone(), nonce.timer));
+        raw.nonces.insert(a.clone(), nonce);
+        if raw.order.len() > self.capacity * 2 {
+            raw.compact();
+        }
+
+        value
+    }
+
+    /// whether the nonce is the alive nonce issued to the node.
+    pub async fn is_issued(&self, a: &Addr, nonce: &str) -> bool {
         self.raw
-            .write()
+            .read()
             .await
-            .entry(a.clone())
-            .or_insert_with(Nonce::new)
-            .unwind()
+            .nonces
+            .get(a)
+            .map(|n| !n.is_death() && n.raw.as_str() == nonce)
+            .unwrap_or(false)
+    }
+
+    /// remove all dead nonces.
+    ///
+    /// ```no_run
+    /// let nonce_table = NonceTable::new(1024);
+    /// nonce_table.poll().await;
+    /// ```
+    pub async fn poll(&self) {
+        let mut raw = self.raw.write().await;
+        raw.nonces.retain(|_, n| !n.is_death());
+        raw.compact();
     }
 
     /// remove session nonce string.
@@ -80,7 +154,35 @@ impl NonceTable {
     /// nonce_table.remove(&addr);
     /// ```
     pub async fn remove(&self, a: &Addr) {
-        self.raw.write().await.remove(a);
+        self.raw.write().await.nonces.remove(a);
+    }
+}
+
+impl Table {
+    /// whether the entry of the order is the issued nonce.
+    fn is_current(&self, a: &Addr, timer: &Instant) -> bool {
+        self.nonces
+            .get(a)
+            .map(|n| n.timer == *timer)
+            .unwrap_or(false)
+    }
+
+    /// make room for a new nonce, the stale entries 
+    /// are skipped until the oldest nonce is found.
+    fn evict(&mut self) {
+        while let Some((a, timer)) = self.order.pop_front() {
+            if self.is_current(&a, &timer) {
+                self.nonces.remove(&a);
+                return
+            }
+        }
+    }
+
+    /// drop the stale entries of the order.
+    fn compact(&mut self) {
+        let mut order = std::mem::take(&mut self.order);
+        order.retain(|(a, timer)| self.is_current(a, timer));
+        self.order = order;
     }
 }
 
@@ -99,7 +201,7 @@ impl Nonce {
     /// assert!(!nonce.is_death());
     /// ```
     pub fn is_death(&self) -> bool {
-        self.timer.elapsed().as_secs() < 3600
+        self.timer.elapsed().as_secs() >= 3600
     }
 
     /// unwind nonce random string.
@@ -122,3 +224,67 @@ impl Nonce {
             .to_lowercase()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::SocketAddr;
+
+    fn addr(port: u16) -> Addr {
+        Arc::new(SocketAddr::from(([127, 0, 0, 1], port)))
+    }
+
+    #[tokio::test]
+    async fn bounded() {
+        let table = NonceTable::new(100);
+        for port in 1000..2000 {
+            table.get(&addr(port)).await;
+        }
+
+        let raw = table.raw.read().await;
+        assert_eq!(raw.nonces.len(), 100);
+        assert!(raw.order.len() <= 200);
+    }
+
+    #[tokio::test]
+    async fn evict_oldest() {
+        let table = NonceTable::new(2);
+        let a = table.get(&addr(1000)).await;
+        let b = table.get(&addr(1001)).await;
+        assert_eq!(table.get(&addr(1000)).await, a);
+
+        let c = table.get(&addr(1002)).await;
+        assert!(!table.is_issued(&addr(1000), &a).await);
+        assert!(table.is_issued(&addr(1001), &b).await);
+        assert!(table.is_issued(&addr(1002), &c).await);
+    }
+
+    #[tokio::test]
+    async fn skip_stale_order() {
+        let table = NonceTable::new(2);
+        table.get(&addr(1000)).await;
+        let b = table.get(&addr(1001)).await;
+
+        // the first entry of the order is stale after the 
+        // nonce is reissued, the next oldest is evicted.
+        table.remove(&addr(1000)).await;
+        let a = table.get(&addr(1000)).await;
+        table.get(&addr(1002)).await;
+        assert!(table.is_issued(&addr(1000), &a).await);
+        assert!(!table.is_issued(&addr(1001), &b).await);
+
+        table.poll().await;
+        assert_eq!(table.raw.read().await.order.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn is_issued() {
+        let table = NonceTable::new(10);
+        let nonce = table.get(&addr(1000)).await;
+        assert_eq!(nonce.len(), 16);
+        assert!(table.is_issued(&addr(1000), &nonce).await);
+        assert!(!table.is_issued(&addr(1000), "0123456789abcdef").await);
+        assert!(!table.is_issued(&addr(1001), &nonce).await);
+        assert!(!table.is_issued(&addr(1000), "").await);
+    }
+}
@@ -122,15 +122,72 @@ impl RandomPort {
         }
     }
 
-        let bi = match index {
-            Some(i) => i,
-            None => return None
-        };
+        let bi = index?;
 
         self.write(start, bi, Bit::Low);
         Some(self.range.start + (start * 64 + bi) as u16)
     }
     
+    /// assign an even port.
+    ///
+    /// if reserve is true, the next-higher port is also 
+    /// assigned, the caller holds it for a later allocation.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use random_port::RandomPort;
+    ///
+    /// let range = 49152..65535;
+    /// let mut pool = RandomPort::new(range);
+    ///
+    /// assert_eq!(pool.alloc(Some(0)), Some(49152));
+    /// assert_eq!(pool.alloc_even(Some(0), true), Some(49154));
+    /// assert_eq!(pool.alloc(Some(0)), Some(49153));
+    /// assert_eq!(pool.alloc(Some(0)), Some(49156));
+    /// assert_eq!(pool.alloc_even(Some(0), false), Some(49158));
+    /// assert_eq!(pool.alloc(Some(0)), Some(49157));
+    /// assert_eq!(pool.alloc(Some(0)), Some(49159));
+    ///
+    /// let port = pool.alloc_even(None, true).unwrap();
+    /// assert_eq!(port % 2, 0);
+    /// ```
+    #[rustfmt::skip]
+    pub fn alloc_even(&mut self, si: Option<usize>, reserve: bool) -> Option<u16> {
+        let start = si.unwrap_or_else(|| self.random() as usize);
+        let size = self.buckets.len();
+        for n in 0..size {
+            let i = (start + n) % size;
+            let value = self.buckets[i];
+
+            // the even index of the bucket is the odd bit, 
+            // the pair also requires the next index is free.
+            let mut flags = if reserve {
+                value & (value << 1) & 0xAAAA_AAAA_AAAA_AAAA
+            } else {
+                value & 0xAAAA_AAAA_AAAA_AAAA
+            };
+
+            while flags != 0 {
+                let bi = flags.leading_zeros() as usize;
+                let port = self.range.start as usize + i * 64 + bi;
+                let last = if reserve { port + 1 } else { port };
+                if last < self.range.end as usize {
+                    self.write(i, bi, Bit::Low);
+                    if reserve {
+                        self.write(i, bi + 1, Bit::Low);
+                    }
+
+                    return Some(port as u16)
+                }
+
+                flags &= !(1 << (63 - bi));
+            }
+        }
+
+        None
+    }
+    
     /// find the high bit in the bucket.
     ///
     /// # Unit Test
@@ -262,3 +319,69 @@ impl RandomPort {
         ((range.end - range.start) as f32 / 64.0).ceil() as usize
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alloc() {
+        let mut pool = RandomPort::new(49152..65535);
+        assert_eq!(pool.alloc(Some(0)), Some(49152));
+        assert_eq!(pool.alloc(Some(0)), Some(49153));
+        assert_eq!(pool.find_high(0), Some(2));
+        assert_eq!(pool.find_high(1), Some(0));
+        assert!(pool.alloc(None).is_some());
+    }
+
+    #[test]
+    fn alloc_even() {
+        let mut pool = RandomPort::new(49152..65535);
+        assert_eq!(pool.alloc(Some(0)), Some(49152));
+        assert_eq!(pool.alloc_even(Some(0), true), Some(49154));
+        assert_eq!(pool.alloc(Some(0)), Some(49153));
+        assert_eq!(pool.alloc(Some(0)), Some(49156));
+        assert_eq!(pool.alloc_even(Some(0), false), Some(49158));
+        assert_eq!(pool.alloc(Some(0)), Some(49157));
+        assert_eq!(pool.alloc(Some(0)), Some(49159));
+
+        let port = pool.alloc_even(None, true).unwrap();
+        assert_eq!(port % 2, 0);
+    }
+
+    #[test]
+    fn alloc_even_last_pair() {
+        // the last port of the range has no next-higher port.
+        let mut pool = RandomPort::new(49152..49155);
+        assert_eq!(pool.alloc_even(Some(0), true), Some(49152));
+        assert_eq!(pool.alloc_even(Some(0), true), None);
+        assert_eq!(pool.alloc_even(Some(0), false), Some(49154));
+    }
+
+    #[test]
+    fn write_and_restore() {
+        let mut pool = RandomPort::new(49152..65535);
+        assert_eq!(pool.alloc(Some(0)), Some(49152));
+        assert_eq!(pool.alloc(Some(0)), Some(49153));
+
+        pool.write(0, 0, Bit::High);
+        pool.write(0, 1, Bit::High);
+        assert_eq!(pool.alloc(Some(0)), Some(49152));
+        assert_eq!(pool.alloc(Some(0)), Some(49153));
+
+        pool.restore(49152);
+        pool.restore(49153);
+        assert_eq!(pool.alloc(Some(0)), Some(49152));
+        assert_eq!(pool.alloc(Some(0)), Some(49153));
+    }
+
+    #[test]
+    fn bucket_size() {
+        let range = 49152..65535;
+        let max = RandomPort::bucket_size(&range) as u16;
+        assert_eq!(max, 256);
+
+        let pool = RandomPort::new(range);
+        assert!((0..max - 1).contains(&pool.random()));
+    }
+}
@@ -0,0 +1,58 @@
+use tokio::time::Instant;
+
+/// Port reservation.
+///
+/// If the server can satisfy the request and the request contains an
+/// EVEN-PORT attribute with the R bit set to 1, the server reserves the
+/// next-higher port on the same IP address for a subsequent allocation
+/// and returns a RESERVATION-TOKEN that identifies the reservation.
+/// 
+/// The reservation is held for approximately 30 seconds, after which
+/// the port is released if no allocation has claimed it.
+pub struct Reservation {
+    pub group: u32,
+    pub port: u16,
+    pub timer: Instant
+}
+
+impl Reservation {
+    /// create port reservation.
+    ///
+    /// ```no_run
+    /// let reservation = Reservation::new(0, 49153);
+    /// assert_eq!(reservation.port, 49153);
+    /// ```
+    pub fn new(group: u32, port: u16) -> Self {
+        Self {
+            timer: Instant::now(),
+            group,
+            port
+        }
+    }
+
+    /// whether the reservation is dead.
+    ///
+    /// ```no_run
+    /// let reservation = Reservation::new(0, 49153);
+    /// assert!(!reservation.is_death());
+    /// ```
+    pub fn is_death(&self) -> bool {
+        self.timer.elapsed().as_secs() >= 30
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::Duration;
+
+    #[test]
+    fn is_death() {
+        let mut reservation = Reservation::new(0, 49153);
+        assert_eq!(reservation.port, 49153);
+        assert!(!reservation.is_death());
+
+        reservation.timer -= Duration::from_secs(30);
+        assert!(reservation.is_death());
+    }
+}
@@ -7,6 +7,7 @@ pub enum Payload {
     
 } 
 
+#[allow(dead_code)]
 pub struct Dtls {
     content: Content,
     version: u16,
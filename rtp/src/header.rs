@@ -150,10 +150,10 @@ impl Header {
         basic[0] = (basic[0] & LE_VERSION_MASK) | (self.version << 6);
         basic[0] = if self.padding { basic[0] | 1 << 5 } else { basic[0] & !(1 << 5) };
         basic[0] = if self.extension { basic[0] | 1 << 4 } else { basic[0] & !(1 << 4) };
-        basic[0] = (basic[0] & LE_CSRC_COUNT_MASK) | ((self.csrc_list.len() as u8) << 0);
+        basic[0] = (basic[0] & LE_CSRC_COUNT_MASK) | (self.csrc_list.len() as u8);
         
         basic[1] = if self.marker { basic[1] | 1 << 4 } else { basic[1] & !(1 << 4) };
-        basic[1] = (basic[1] & LE_PAYLOAD_KIND_MASK) | (self.payload_kind << 0);
+        basic[1] = (basic[1] & LE_PAYLOAD_KIND_MASK) | self.payload_kind;
         
         buf.put(&basic[..]);
         buf.put_u16(self.sequence_number);
@@ -161,7 +161,7 @@ impl<'a> TryFrom<&'a [u8]> for Rtp<'a> {
     #[rustfmt::skip]
     fn try_from(buf: &'a [u8]) -> Result<Self, Self::Error> {
         let mut bytes = Bytes::from_static(unsafe {
-            std::mem::transmute(buf)
+            std::mem::transmute::<&'a [u8], &'static [u8]>(buf)
         });
 
         let header = Header::try_from(&mut bytes)?;
@@ -0,0 +1,45 @@
+use std::convert::TryFrom;
+
+use sdp::{
+    connection::Connection,
+    origin::Origin,
+    timing::Timing,
+    Sdp
+};
+
+use criterion::*;
+
+const OFFER: &str = "v=0\r\n\
+    o=- 9216395717180620054 2 IN IP4 127.0.0.1\r\n\
+    s=-\r\n\
+    c=IN IP4 0.0.0.0\r\n\
+    t=0 0\r\n\
+    a=group:BUNDLE 0 1\r\n\
+    m=audio 9 UDP/TLS/RTP/SAVPF 0\r\n\
+    c=IN IP4 0.0.0.0\r\n\
+    a=mid:0\r\n\
+    a=sendrecv\r\n\
+    a=ptime:20\r\n\
+    m=video 9 UDP/TLS/RTP/SAVPF 96 97\r\n\
+    c=IN IP4 0.0.0.0\r\n\
+    a=mid:1\r\n\
+    a=rtpmap:96 VP8/90000\r\n\
+    a=rtpmap:97 H264/90000\r\n\
+    a=rtcp-fb:96 nack pli\r\n";
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let origin = "- 9216395717180620054 2 IN IP4 127.0.0.1";
+    let connection = "IN IP4 0.0.0.0";
+    let timing = "0 0";
+
+    let mut group = c.benchmark_group("sdp_decoder");
+    group.bench_function("decoder_origin", |b| b.iter(|| Origin::try_from(black_box(origin)).unwrap()));
+    group.bench_function("decoder_connection", |b| b.iter(|| Connection::try_from(black_box(connection)).unwrap()));
+    group.bench_function("decoder_timing", |b| b.iter(|| Timing::try_from(black_box(timing)).unwrap()));
+    group.throughput(Throughput::Bytes(OFFER.len() as u64));
+    group.bench_function("decoder_offer", |b| b.iter(|| Sdp::try_from(black_box(OFFER)).unwrap()));
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
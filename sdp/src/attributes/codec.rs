@@ -1,14 +1,11 @@
-use anyhow::{
-    Result,
-    anyhow
-};
+use super::SdpError;
 
 use std::{
     convert::TryFrom,
     fmt
 };
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Codec {
     Vp9,
     Vp8,
@@ -17,7 +14,13 @@ pub enum Codec {
     Av1x,
     Rtx,
     Red,
-    Ulpfec
+    Ulpfec,
+    Opus,
+    G722,
+    Pcmu,
+    Pcma,
+    Cn,
+    TelephoneEvent
 }
 
 impl fmt::Display for Codec {
@@ -30,6 +33,8 @@ impl fmt::Display for Codec {
     /// assert_eq!(format!("{}", Codec::Vp8), "VP8");
     /// assert_eq!(format!("{}", Codec::Av1x), "AV1X");
     /// assert_eq!(format!("{}", Codec::H265), "H265");
+    /// assert_eq!(format!("{}", Codec::Opus), "opus");
+    /// assert_eq!(format!("{}", Codec::TelephoneEvent), "telephone-event");
     /// ```
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", match self {
@@ -40,13 +45,19 @@ impl fmt::Display for Codec {
             Self::Av1x =>   "AV1X",
             Self::Rtx =>    "rtx",
             Self::Red =>    "red",
-            Self::Ulpfec => "ulpfec"
+            Self::Ulpfec => "ulpfec",
+            Self::Opus =>   "opus",
+            Self::G722 =>   "G722",
+            Self::Pcmu =>   "PCMU",
+            Self::Pcma =>   "PCMA",
+            Self::Cn =>     "CN",
+            Self::TelephoneEvent => "telephone-event"
         })
     }
 }
 
 impl<'a> TryFrom<&'a str> for Codec {
-    type Error = anyhow::Error;
+    type Error = SdpError;
     /// # Unit Test
     ///
     /// ```
@@ -58,6 +69,8 @@ impl<'a> TryFrom<&'a str> for Codec {
     /// assert_eq!(Codec::try_from("H264").unwrap(), Codec::H264);
     /// assert_eq!(Codec::try_from("H265").unwrap(), Codec::H265);
     /// assert_eq!(Codec::try_from("AV1X").unwrap(), Codec::Av1x);
+    /// assert_eq!(Codec::try_from("opus").unwrap(), Codec::Opus);
+    /// assert_eq!(Codec::try_from("PCMU").unwrap(), Codec::Pcmu);
     /// assert!(Codec::try_from("av1x").is_err());
     /// ```
     fn try_from(value: &'a str) -> Result<Self, Self::Error> {
@@ -70,7 +83,13 @@ impl<'a> TryFrom<&'a str> for Codec {
             "rtx" =>    Ok(Self::Rtx),
             "red" =>    Ok(Self::Red),
             "ulpfec" => Ok(Self::Ulpfec),
-            _ => Err(anyhow!("invalid codec!"))
+            "opus" =>   Ok(Self::Opus),
+            "G722" =>   Ok(Self::G722),
+            "PCMU" =>   Ok(Self::Pcmu),
+            "PCMA" =>   Ok(Self::Pcma),
+            "CN" =>     Ok(Self::Cn),
+            "telephone-event" => Ok(Self::TelephoneEvent),
+            _ => Err(SdpError::InvalidValue("codec"))
         }
     }
 }
@@ -0,0 +1,206 @@
+use super::SdpError;
+use std::{
+    convert::TryFrom,
+    fmt
+};
+
+/// media direction.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Direction {
+    SendRecv,
+    SendOnly,
+    RecvOnly,
+    Inactive
+}
+
+/// RTP Header Extension Attribute
+///
+/// a=extmap:<value>["/"<direction>] <URI> <extensionattributes>
+///
+/// The "extmap" attribute maps a local identifier to the URI of 
+/// an RTP header extension, see 
+/// [RFC8285](https://datatracker.ietf.org/doc/html/rfc8285#section-8).
+///
+/// The local identifier values 1-14 inclusive can be used in 
+/// both the one-byte and the two-byte header form, the values 
+/// 15-255 can only be used in the two-byte header form.  The 
+/// value 0 is reserved and MUST NOT be used.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ExtMap<'a> {
+    pub id: u8,
+    pub direction: Option<Direction>,
+    pub uri: &'a str,
+    pub params: Option<&'a str>
+}
+
+impl Direction {
+    /// the direction seen from the other side of the session.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::attributes::*;
+    ///
+    /// assert_eq!(Direction::SendOnly.reverse(), Direction::RecvOnly);
+    /// assert_eq!(Direction::RecvOnly.reverse(), Direction::SendOnly);
+    /// assert_eq!(Direction::SendRecv.reverse(), Direction::SendRecv);
+    /// assert_eq!(Direction::Inactive.reverse(), Direction::Inactive);
+    /// ```
+    pub fn reverse(self) -> Self {
+        match self {
+            Self::SendOnly => Self::RecvOnly,
+            Self::RecvOnly => Self::SendOnly,
+            d => d
+        }
+    }
+}
+
+impl ExtMap<'_> {
+    /// whether the extension fits the one-byte header form.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::attributes::*;
+    /// use std::convert::*;
+    ///
+    /// assert!(ExtMap::try_from("14 urn:a").unwrap().is_one_byte());
+    /// assert!(!ExtMap::try_from("15 urn:a").unwrap().is_one_byte());
+    /// ```
+    pub fn is_one_byte(&self) -> bool {
+        self.id <= 14
+    }
+}
+
+impl fmt::Display for ExtMap<'_> {
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::attributes::*;
+    ///
+    /// let ext = ExtMap {
+    ///     id: 2,
+    ///     direction: Some(Direction::SendOnly),
+    ///     uri: "urn:ietf:params:rtp-hdrext:toffset",
+    ///     params: None
+    /// };
+    ///
+    /// assert_eq!(format!("{}", ext), "2/sendonly urn:ietf:params:rtp-hdrext:toffset");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.id)?;
+        if let Some(direction) = self.direction {
+            write!(f, "/{}", direction)?;
+        }
+
+        write!(f, " {}", self.uri)?;
+        if let Some(params) = self.params {
+            write!(f, " {}", params)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a str> for ExtMap<'a> {
+    type Error = SdpError;
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::attributes::*;
+    /// use std::convert::*;
+    ///
+    /// let ext = ExtMap::try_from(
+    ///     "3 http://www.webrtc.org/experiments/rtp-hdrext/abs-send-time"
+    /// ).unwrap();
+    ///
+    /// assert_eq!(ext.id, 3);
+    /// assert_eq!(ext.direction, None);
+    /// assert_eq!(ext.uri, "http://www.webrtc.org/experiments/rtp-hdrext/abs-send-time");
+    /// assert_eq!(ext.params, None);
+    ///
+    /// let ext = ExtMap::try_from("1/recvonly urn:ietf:params:rtp-hdrext:toffset x=1")
+    ///     .unwrap();
+    ///
+    /// assert_eq!(ext.id, 1);
+    /// assert_eq!(ext.direction, Some(Direction::RecvOnly));
+    /// assert_eq!(ext.uri, "urn:ietf:params:rtp-hdrext:toffset");
+    /// assert_eq!(ext.params, Some("x=1"));
+    ///
+    /// assert!(ExtMap::try_from("0 urn:a").is_err());
+    /// assert!(ExtMap::try_from("256 urn:a").is_err());
+    /// assert!(ExtMap::try_from("1").is_err());
+    /// ```
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let (head, tail) = value
+            .split_once(' ')
+            .ok_or(SdpError::MissingField("extmap"))?;
+        let (id, direction) = match head.split_once('/') {
+            Some((i, d)) => (i, Some(Direction::try_from(d)?)),
+            None => (head, None)
+        };
+
+        let id: u8 = id.parse()?;
+        if id == 0 {
+            return Err(SdpError::InvalidValue("extmap id"))
+        }
+
+        let (uri, params) = match tail.split_once(' ') {
+            Some((u, p)) => (u, Some(p)),
+            None => (tail, None)
+        };
+
+        Ok(Self {
+            id,
+            direction,
+            uri,
+            params
+        })
+    }
+}
+
+impl fmt::Display for Direction {
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::attributes::*;
+    ///
+    /// assert_eq!(format!("{}", Direction::SendRecv), "sendrecv");
+    /// assert_eq!(format!("{}", Direction::SendOnly), "sendonly");
+    /// assert_eq!(format!("{}", Direction::RecvOnly), "recvonly");
+    /// assert_eq!(format!("{}", Direction::Inactive), "inactive");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", match self {
+            Self::SendRecv =>  "sendrecv",
+            Self::SendOnly =>  "sendonly",
+            Self::RecvOnly =>  "recvonly",
+            Self::Inactive =>  "inactive"
+        })
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Direction {
+    type Error = SdpError;
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::attributes::*;
+    /// use std::convert::*;
+    ///
+    /// assert_eq!(Direction::try_from("sendrecv").unwrap(), Direction::SendRecv);
+    /// assert_eq!(Direction::try_from("sendonly").unwrap(), Direction::SendOnly);
+    /// assert_eq!(Direction::try_from("recvonly").unwrap(), Direction::RecvOnly);
+    /// assert_eq!(Direction::try_from("inactive").unwrap(), Direction::Inactive);
+    /// assert!(Direction::try_from("send").is_err());
+    /// ```
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value {
+            "sendrecv" =>  Ok(Self::SendRecv),
+            "sendonly" =>  Ok(Self::SendOnly),
+            "recvonly" =>  Ok(Self::RecvOnly),
+            "inactive" =>  Ok(Self::Inactive),
+            _ => Err(SdpError::InvalidValue("direction"))
+        }
+    }
+}
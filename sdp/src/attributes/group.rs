@@ -0,0 +1,72 @@
+use super::SdpError;
+use std::{
+    convert::TryFrom,
+    fmt
+};
+
+/// Media Grouping Attribute
+///
+/// a=group:<semantics> <identification-tag> ...
+///
+/// The "group" attribute groups several media descriptions of the 
+/// session, each media description is identified by its "a=mid:" 
+/// identification tag, for example "BUNDLE" indicates that the 
+/// media share a single transport, see 
+/// [RFC5888](https://datatracker.ietf.org/doc/html/rfc5888#section-5).
+#[derive(Debug, PartialEq, Eq)]
+pub struct Group<'a> {
+    pub semantics: &'a str,
+    pub mids: Vec<&'a str>
+}
+
+impl fmt::Display for Group<'_> {
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::attributes::*;
+    ///
+    /// let group = Group {
+    ///     semantics: "BUNDLE",
+    ///     mids: vec!["audio", "video"]
+    /// };
+    ///
+    /// assert_eq!(format!("{}", group), "BUNDLE audio video");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.semantics)?;
+        for mid in &self.mids {
+            write!(f, " {}", mid)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Group<'a> {
+    type Error = SdpError;
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::attributes::*;
+    /// use std::convert::*;
+    ///
+    /// let group = Group::try_from("BUNDLE 0 1").unwrap();
+    /// assert_eq!(group.semantics, "BUNDLE");
+    /// assert_eq!(group.mids, vec!["0", "1"]);
+    ///
+    /// let group = Group::try_from("BUNDLE").unwrap();
+    /// assert!(group.mids.is_empty());
+    /// assert!(Group::try_from("").is_err());
+    /// ```
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let mut values = value.split(' ');
+        let semantics = values
+            .next()
+            .filter(|s| !s.is_empty())
+            .ok_or(SdpError::MissingField("group"))?;
+        Ok(Self {
+            mids: values.filter(|m| !m.is_empty()).collect(),
+            semantics
+        })
+    }
+}
@@ -1,7 +1,4 @@
-use anyhow::{
-    Result,
-    anyhow
-};
+use super::SdpError;
 
 use std::{
     convert::TryFrom,
@@ -14,24 +11,24 @@ use std::{
 /// appropriate:
 /// 
 /// *  When "a=type:broadcast" is specified, "a=recvonly" is probably
-/// appropriate for those connecting.
+///    appropriate for those connecting.
 /// 
 /// *  When "a=type:meeting" is specified, "a=sendrecv" is likely to be
-/// appropriate.
+///    appropriate.
 /// 
 /// *  "a=type:moderated" suggests the use of a floor control tool and
-/// that the media tools be started so as to mute new sites joining
-/// the multimedia conference.
+///    that the media tools be started so as to mute new sites joining
+///    the multimedia conference.
 /// 
 /// *  Specifying "a=type:H332" indicates that this loosely coupled
-/// session is part of an H.332 session as defined in the ITU H.332
-/// specification [ITU.H332.1998](https://datatracker.ietf.org/doc/
-/// html/rfc8866#ref-ITU.H332.1998). Media tools should be started 
-/// using "a=recvonly".
+///    session is part of an H.332 session as defined in the ITU H.332
+///    specification [ITU.H332.1998](https://datatracker.ietf.org/doc/
+///    html/rfc8866#ref-ITU.H332.1998). Media tools should be started 
+///    using "a=recvonly".
 /// 
 /// *  Specifying "a=type:test" is suggested as a hint that, unless
-/// explicitly requested otherwise, receivers can safely avoid
-/// displaying this session description to users.
+///    explicitly requested otherwise, receivers can safely avoid
+///    displaying this session description to users.
 #[derive(Debug, PartialEq, Eq)]
 pub enum Kind {
     Broadcast,
@@ -63,7 +60,7 @@ impl fmt::Display for Kind {
 }
 
 impl<'a> TryFrom<&'a str> for Kind {
-    type Error = anyhow::Error;
+    type Error = SdpError;
     /// # Unit Test
     ///
     /// ```
@@ -82,7 +79,7 @@ impl<'a> TryFrom<&'a str> for Kind {
             "moderated" =>  Ok(Self::Moderated),
             "test" =>       Ok(Self::Test),
             "H332" =>       Ok(Self::H332),
-            _ => Err(anyhow!("invalid type!"))
+            _ => Err(SdpError::InvalidValue("type"))
         }
     }
 }
@@ -1,10 +1,10 @@
-use anyhow::Result;
+use super::SdpError;
 use std::{
     convert::TryFrom,
     fmt
 };
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Mid {
     Audio,
     Video,
@@ -31,7 +31,7 @@ impl fmt::Display for Mid {
 }
 
 impl<'a> TryFrom<&'a str> for Mid {
-    type Error = anyhow::Error;
+    type Error = SdpError;
     /// # Unit Test
     ///
     /// ```
@@ -3,23 +3,50 @@ mod codec;
 mod kind;
 mod orient;
 mod rtp_value;
+mod rtcp_fb;
+mod ext_map;
+mod ssrc;
+mod group;
+mod range;
 
 pub use rtp_value::RtpValue;
+pub use group::Group;
+pub use range::{
+    NptTime,
+    Range
+};
+pub use ssrc::{
+    SsrcGroup,
+    Ssrc,
+    Msid
+};
+
+pub use ext_map::{
+    Direction,
+    ExtMap
+};
+
+pub use rtcp_fb::{
+    PayloadMatch,
+    RtcpFb
+};
+
 pub use orient::Orient;
 pub use codec::Codec;
 pub use kind::Kind;
 pub use mid::Mid;
 
-use itertools::Itertools;
-use anyhow::{
-    Result,
-    ensure,
-    anyhow
+use super::error::{
+    SdpError,
+    Result
 };
 
+use itertools::Itertools;
+
 use std::{
     collections::HashMap,
     convert::TryFrom,
+    borrow::Cow,
     fmt
 };
 
@@ -37,7 +64,23 @@ pub enum Key {
     Orient,
     Type,
     Framerate,
-    Quality
+    Quality,
+    RtcpFb,
+    Ssrc,
+    SsrcGroup,
+    Msid,
+    Candidate,
+    EndOfCandidates,
+    Group,
+    Mid,
+    Control,
+    Range,
+    SendRecv,
+    SendOnly,
+    RecvOnly,
+    Inactive,
+    IceLite,
+    IceOptions
 }
 
 #[derive(Debug, Default)]
@@ -377,11 +420,108 @@ pub struct Attributes<'a> {
     /// stream.  Note that an RTP-based system MUST still send RTCP (if RTCP
     /// is used), even if started in inactive mode.
     pub inactive: bool,
-    /// SDP extmap Attribute
-    pub extmap: HashMap<u8, &'a str>,
-    
-    pub mid: Option<Mid>,
-    
+    /// RTP Header Extension Attribute
+    ///
+    /// Example:
+    /// a=extmap:1/sendonly urn:ietf:params:rtp-hdrext:toffset
+    pub extmap: Vec<ExtMap<'a>>,
+    /// RTCP Feedback Capability Attribute
+    ///
+    /// Example:
+    /// a=rtcp-fb:96 nack pli
+    pub rtcp_fb: Vec<RtcpFb<'a>>,
+    /// Source-Specific Media Attribute, grouped by ssrc-id.
+    ///
+    /// Example:
+    /// a=ssrc:1234 cname:panda
+    pub ssrcs: Vec<Ssrc<'a>>,
+    /// Source Group Attribute
+    ///
+    /// Example:
+    /// a=ssrc-group:FID 1234 5678
+    pub ssrc_groups: Vec<SsrcGroup<'a>>,
+    /// Media Stream Identification Attribute
+    ///
+    /// Example:
+    /// a=msid:stream track
+    pub msid: Option<Msid<'a>>,
+    /// ICE Candidate Attribute
+    ///
+    /// Example:
+    /// a=candidate:1 1 UDP 2130706431 10.0.1.1 8998 typ host
+    ///
+    /// the candidates are kept as the raw attribute value, with
+    /// trickle ICE they may be appended after the description was
+    /// parsed, in which case they are owned.
+    pub candidates: Vec<Cow<'a, str>>,
+    /// End-of-Candidates Attribute
+    ///
+    /// Example:
+    /// a=end-of-candidates
+    ///
+    /// indicates that no more candidates will be trickled for the
+    /// media, see 
+    /// [RFC8840](https://datatracker.ietf.org/doc/html/rfc8840#section-8.2).
+    pub end_of_candidates: bool,
+    /// Media Grouping Attribute
+    ///
+    /// Example:
+    /// a=group:BUNDLE audio video
+    pub groups: Vec<Group<'a>>,
+    /// Media Stream Identification Attribute
+    ///
+    /// Example:
+    /// a=mid:audio
+    ///
+    /// the identification tag is an arbitrary token, which is 
+    /// unique within the session, see 
+    /// [RFC5888](https://datatracker.ietf.org/doc/html/rfc5888#section-4).
+    pub mid: Option<&'a str>,
+    /// Control Attribute
+    ///
+    /// Example:
+    /// a=control:trackID=1
+    ///
+    /// the RTSP control url of the session or media, it is 
+    /// absolute or relative to the base url of the session, see 
+    /// [RFC2326](https://datatracker.ietf.org/doc/html/rfc2326#appendix-C.1.1).
+    pub control: Option<&'a str>,
+    /// Range Attribute
+    ///
+    /// Example:
+    /// a=range:npt=0-
+    ///
+    /// the time range of the stored session or media, see 
+    /// [RFC2326](https://datatracker.ietf.org/doc/html/rfc2326#appendix-C.1.5).
+    /// only normal play time ranges are modelled, a clock or smpte 
+    /// range is kept in the unrecognised attributes.
+    pub range: Option<Range>,
+    /// ICE Lite Attribute
+    ///
+    /// Example:
+    /// a=ice-lite
+    ///
+    /// indicates that the agent is a lite implementation, see 
+    /// [RFC8839](https://datatracker.ietf.org/doc/html/rfc8839#section-5.3).
+    pub ice_lite: bool,
+    /// ICE Options Attribute
+    ///
+    /// Example:
+    /// a=ice-options:trickle renomination
+    ///
+    /// the ICE options supported by the agent, see 
+    /// [RFC8839](https://datatracker.ietf.org/doc/html/rfc8839#section-5.6).
+    pub ice_options: Vec<&'a str>,
+    /// Unrecognised Attributes
+    ///
+    /// Example:
+    /// a=ice-ufrag:F7gI
+    ///
+    /// the attributes that are not modelled, such as ice-pwd,
+    /// fingerprint, setup or rtcp-mux, are kept as the raw line
+    /// without the "a=" prefix, in the order they appear, so that
+    /// they are written back unchanged.
+    pub unknown: Vec<&'a str>,
 }
 
 impl<'a> Attributes<'a> {
@@ -399,54 +539,137 @@ impl<'a> Attributes<'a> {
     /// assert_eq!(value.channels, None);
     /// ```
     pub fn handle(&mut self, line: &'a str) -> Result<()> {
-        let values = line.split(':').collect::<Vec<&str>>();
-        ensure!(!values.is_empty(), "invalid attributes!");
-        let key = match Key::try_from(values[0]) {
+        if line.is_empty() {
+            return Err(SdpError::MissingField("attributes"))
+        }
+
+        // the attribute value may contain ":" itself,
+        // for example the uri of an extmap attribute.
+        let (name, value) = line
+            .split_once(':')
+            .unwrap_or((line, ""));
+        let key = match Key::try_from(name) {
             Ok(k) => k,
-            _ => return Ok(())
+            _ => {
+                self.unknown.push(line);
+                return Ok(())
+            }
         };
         
-        Ok(match key {
-            Key::Fmtp      => self.handle_fmtp(values[1])?,
-            Key::Lang      => self.lang = Some(values[1]),
-            Key::RtpMap    => self.handle_rtpmap(values[1])?,
-            Key::ExtMap    => self.handle_extmap(values[1])?,
-            Key::Charset   => self.charset = Some(values[1]),
-            Key::SdpLang   => self.sdplang = Some(values[1]),
-            Key::Ptime     => self.ptime = Some(values[1].parse()?),
-            Key::MaxPtime  => self.maxptime = Some(values[1].parse()?),
-            Key::Orient    => self.orient = Some(Orient::try_from(values[1])?),
-            Key::Type      => self.kind = Some(Kind::try_from(values[1])?),
-            Key::Framerate => self.framerate = Some(values[1].parse()?),
-            Key::Quality   => self.quality = Some(values[1].parse()?),
-        })
+        match key {
+            Key::Fmtp if !is_fmtp_params(value) => self.unknown.push(line),
+            Key::Fmtp      => self.handle_fmtp(value)?,
+            Key::Lang      => self.lang = Some(value),
+            Key::RtpMap    => self.handle_rtpmap(value)?,
+            Key::ExtMap    => self.extmap.push(ExtMap::try_from(value)?),
+            Key::Charset   => self.charset = Some(value),
+            Key::SdpLang   => self.sdplang = Some(value),
+            Key::Ptime     => self.ptime = Some(value.parse()?),
+            Key::MaxPtime  => self.maxptime = Some(value.parse()?),
+            Key::Orient    => self.orient = Some(Orient::try_from(value)?),
+            Key::Type      => self.kind = Some(Kind::try_from(value)?),
+            Key::Framerate => self.framerate = Some(value.parse()?),
+            Key::Quality   => self.quality = Some(value.parse()?),
+            Key::RtcpFb    => self.rtcp_fb.push(RtcpFb::try_from(value)?),
+            Key::Ssrc      => self.handle_ssrc(value)?,
+            Key::SsrcGroup => self.ssrc_groups.push(SsrcGroup::try_from(value)?),
+            Key::Msid      => self.msid = Some(Msid::try_from(value)?),
+            Key::Candidate => self.candidates.push(Cow::Borrowed(value)),
+            Key::EndOfCandidates => self.end_of_candidates = true,
+            Key::Group     => self.groups.push(Group::try_from(value)?),
+            Key::Mid       => self.mid = Some(value),
+            Key::Control   => self.control = Some(value),
+            Key::Range if !value.starts_with("npt=") => self.unknown.push(line),
+            Key::Range     => self.range = Some(Range::try_from(value)?),
+            Key::SendRecv  => self.set_direction(Direction::SendRecv),
+            Key::SendOnly  => self.set_direction(Direction::SendOnly),
+            Key::RecvOnly  => self.set_direction(Direction::RecvOnly),
+            Key::Inactive  => self.set_direction(Direction::Inactive),
+            Key::IceLite   => self.ice_lite = true,
+            Key::IceOptions => self.ice_options.extend(value.split(' ').filter(|o| !o.is_empty())),
+        }
+
+        Ok(())
     }
     
+    /// the media direction attribute.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::attributes::*;
+    ///
+    /// let mut attributes = Attributes::default();
+    /// assert_eq!(attributes.direction(), None);
+    ///
+    /// attributes.handle("sendonly").unwrap();
+    /// assert_eq!(attributes.direction(), Some(Direction::SendOnly));
+    ///
+    /// attributes.set_direction(Direction::Inactive);
+    /// assert_eq!(attributes.direction(), Some(Direction::Inactive));
+    /// assert!(!attributes.sendonly);
+    /// ```
+    pub fn direction(&self) -> Option<Direction> {
+        [
+            (self.sendrecv, Direction::SendRecv),
+            (self.sendonly, Direction::SendOnly),
+            (self.recvonly, Direction::RecvOnly),
+            (self.inactive, Direction::Inactive)
+        ].iter()
+            .find(|(flag, _)| *flag)
+            .map(|(_, d)| *d)
+    }
+
+    /// set the media direction attribute, 
+    /// the directions are mutually exclusive.
+    pub fn set_direction(&mut self, direction: Direction) {
+        self.sendrecv = direction == Direction::SendRecv;
+        self.sendonly = direction == Direction::SendOnly;
+        self.recvonly = direction == Direction::RecvOnly;
+        self.inactive = direction == Direction::Inactive;
+    }
+
     fn handle_rtpmap(&mut self, value: &str) -> Result<()> {
-        let values = value.split(' ').collect::<Vec<&str>>();
-        ensure!(values.len() == 2, "invalid rtpmap!");
-        let rtp = RtpValue::try_from(values[1])?;
-        self.rtpmap.insert(values[0].parse()?, rtp);
+        let (pt, value) = value
+            .split_once(' ')
+            .filter(|(_, v)| !v.contains(' '))
+            .ok_or(SdpError::MissingField("rtpmap"))?;
+        let rtp = RtpValue::try_from(value)?;
+        self.rtpmap.insert(pt.parse()?, rtp);
         Ok(())
     }
     
-    fn handle_extmap(&mut self, value: &'a str) -> Result<()> {
-        let values = value.split(' ').collect::<Vec<&str>>();
-        ensure!(values.len() == 2, "invalid extmap!");
-        self.extmap.insert(values[0].parse()?, values[1]);
+    fn handle_ssrc(&mut self, value: &'a str) -> Result<()> {
+        let (id, attribute) = value
+            .split_once(' ')
+            .ok_or(SdpError::MissingField("ssrc"))?;
+        let id: u32 = id.parse()?;
+        let attribute = match attribute.split_once(':') {
+            Some((k, v)) => (k, Some(v)),
+            None => (attribute, None)
+        };
+
+        match self.ssrcs.iter_mut().find(|s| s.id == id) {
+            Some(ssrc) => ssrc.attributes.push(attribute),
+            None => self.ssrcs.push(Ssrc {
+                attributes: vec![attribute],
+                id
+            })
+        }
+
         Ok(())
     }
     
     fn handle_fmtp(&mut self, value: &'a str) -> Result<()> {
-        let values = value.split(' ').collect::<Vec<&str>>();
-        ensure!(values.len() == 2, "invalid fmtp!");
-        let key: u8 = values[0].parse()?;
-        values[1]
+        let (key, params) = value
+            .split_once(' ')
+            .filter(|(_, v)| !v.contains(' '))
+            .ok_or(SdpError::MissingField("fmtp"))?;
+        let key: u8 = key.parse()?;
+        params
             .split(';')
-            .map(|x| x.split('=').collect_tuple::<(&'a str, &'a str)>())
-            .filter(|x| x.is_some())
-            .for_each(|option| {
-                let (k, v) = option.unwrap();
+            .filter_map(|x| x.split_once('='))
+            .for_each(|(k, v)| {
                 self.fmtp
                     .entry(key)
                     .or_insert_with(|| HashMap::with_capacity(10))
@@ -456,6 +679,188 @@ impl<'a> Attributes<'a> {
     }
 }
 
+/// whether the format parameters are a list of "key=value" pairs, 
+/// the other parameters, such as the "0-15" events of 
+/// telephone-event, are kept as an unrecognised attribute, so 
+/// that the line is written back unchanged.
+fn is_fmtp_params(value: &str) -> bool {
+    value
+        .split_once(' ')
+        .map(|(_, params)| params.split(';').all(|x| x.contains('=')))
+        .unwrap_or(true)
+}
+
+impl fmt::Display for Attributes<'_> {
+    /// every attribute is written as a "a=" line, payload type
+    /// related attributes are written in the order of payload type.
+    /// the unrecognised attributes are written in their original 
+    /// order, before the candidates.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::attributes::*;
+    ///
+    /// let mut attributes = Attributes::default();
+    /// attributes.handle("ice-ufrag:F7gI").unwrap();
+    /// attributes.handle("rtpmap:96 VP8/90000").unwrap();
+    /// attributes.handle("fingerprint:sha-256 49:66:12:17").unwrap();
+    /// attributes.handle("rtcp-fb:96 nack").unwrap();
+    /// attributes.handle("setup:actpass").unwrap();
+    /// attributes.handle("rtcp-mux").unwrap();
+    /// attributes.handle("candidate:1 1 UDP 1 10.0.0.1 9 typ host").unwrap();
+    /// attributes.handle("end-of-candidates").unwrap();
+    ///
+    /// assert_eq!(
+    ///     format!("{}", attributes),
+    ///     "a=rtpmap:96 VP8/90000\r\n\
+    ///      a=rtcp-fb:96 nack\r\n\
+    ///      a=ice-ufrag:F7gI\r\n\
+    ///      a=fingerprint:sha-256 49:66:12:17\r\n\
+    ///      a=setup:actpass\r\n\
+    ///      a=rtcp-mux\r\n\
+    ///      a=candidate:1 1 UDP 1 10.0.0.1 9 typ host\r\n\
+    ///      a=end-of-candidates\r\n"
+    /// );
+    ///
+    /// // the format parameters that are not "key=value" pairs
+    /// // are written back unchanged.
+    /// let mut attributes = Attributes::default();
+    /// attributes.handle("fmtp:101 0-15").unwrap();
+    /// attributes.handle("fmtp:102 sprop-parameter-sets=Z0IAH5Wo==,aM4=").unwrap();
+    /// assert!(attributes.fmtp.get(&101).is_none());
+    /// assert_eq!(attributes.fmtp[&102]["sprop-parameter-sets"], "Z0IAH5Wo==,aM4=");
+    /// assert_eq!(
+    ///     format!("{}", attributes),
+    ///     "a=fmtp:102 sprop-parameter-sets=Z0IAH5Wo==,aM4=\r\n\
+    ///      a=fmtp:101 0-15\r\n"
+    /// );
+    /// ```
+    #[rustfmt::skip]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for group in &self.groups {
+            write!(f, "a=group:{}\r\n", group)?;
+        }
+
+        if let Some(mid) = self.mid {
+            write!(f, "a=mid:{}\r\n", mid)?;
+        }
+
+        if self.ice_lite {
+            write!(f, "a=ice-lite\r\n")?;
+        }
+
+        if !self.ice_options.is_empty() {
+            write!(f, "a=ice-options:{}\r\n", self.ice_options.join(" "))?;
+        }
+
+        if let Some(msid) = &self.msid {
+            write!(f, "a=msid:{}\r\n", msid)?;
+        }
+
+        for (flag, name) in [
+            (self.sendrecv, "sendrecv"),
+            (self.sendonly, "sendonly"),
+            (self.recvonly, "recvonly"),
+            (self.inactive, "inactive")
+        ] {
+            if flag {
+                write!(f, "a={}\r\n", name)?;
+            }
+        }
+
+        for extmap in &self.extmap {
+            write!(f, "a=extmap:{}\r\n", extmap)?;
+        }
+
+        for pt in self.rtpmap.keys().sorted() {
+            write!(f, "a=rtpmap:{} {}\r\n", pt, self.rtpmap[pt])?;
+        }
+
+        for pt in self.fmtp.keys().sorted() {
+            let params = self.fmtp[pt]
+                .iter()
+                .sorted()
+                .map(|(k, v)| format!("{}={}", k, v))
+                .join(";");
+            write!(f, "a=fmtp:{} {}\r\n", pt, params)?;
+        }
+
+        for rtcp_fb in &self.rtcp_fb {
+            write!(f, "a=rtcp-fb:{}\r\n", rtcp_fb)?;
+        }
+
+        if let Some(ptime) = self.ptime {
+            write!(f, "a=ptime:{}\r\n", ptime)?;
+        }
+
+        if let Some(maxptime) = self.maxptime {
+            write!(f, "a=maxptime:{}\r\n", maxptime)?;
+        }
+
+        if let Some(orient) = &self.orient {
+            write!(f, "a=orient:{}\r\n", orient)?;
+        }
+
+        if let Some(kind) = &self.kind {
+            write!(f, "a=type:{}\r\n", kind)?;
+        }
+
+        if let Some(framerate) = self.framerate {
+            write!(f, "a=framerate:{}\r\n", framerate)?;
+        }
+
+        if let Some(quality) = self.quality {
+            write!(f, "a=quality:{}\r\n", quality)?;
+        }
+
+        for (value, name) in [
+            (self.charset, "charset"),
+            (self.sdplang, "sdplang"),
+            (self.lang, "lang")
+        ] {
+            if let Some(value) = value {
+                write!(f, "a={}:{}\r\n", name, value)?;
+            }
+        }
+
+        if let Some(control) = self.control {
+            write!(f, "a=control:{}\r\n", control)?;
+        }
+
+        if let Some(range) = &self.range {
+            write!(f, "a=range:{}\r\n", range)?;
+        }
+
+        for group in &self.ssrc_groups {
+            write!(f, "a=ssrc-group:{}\r\n", group)?;
+        }
+
+        for ssrc in &self.ssrcs {
+            for (k, v) in &ssrc.attributes {
+                match v {
+                    Some(v) => write!(f, "a=ssrc:{} {}:{}\r\n", ssrc.id, k, v)?,
+                    None => write!(f, "a=ssrc:{} {}\r\n", ssrc.id, k)?
+                }
+            }
+        }
+
+        for line in &self.unknown {
+            write!(f, "a={}\r\n", line)?;
+        }
+
+        for candidate in &self.candidates {
+            write!(f, "a=candidate:{}\r\n", candidate)?;
+        }
+
+        if self.end_of_candidates {
+            write!(f, "a=end-of-candidates\r\n")?;
+        }
+
+        Ok(())
+    }
+}
+
 impl fmt::Display for Key {
     /// # Unit Test
     ///
@@ -481,12 +886,28 @@ impl fmt::Display for Key {
             Self::Type      => "type",
             Self::Framerate => "framerate",
             Self::Quality   => "quality",
+            Self::RtcpFb    => "rtcp-fb",
+            Self::Ssrc      => "ssrc",
+            Self::SsrcGroup => "ssrc-group",
+            Self::Msid      => "msid",
+            Self::Candidate => "candidate",
+            Self::EndOfCandidates => "end-of-candidates",
+            Self::Group     => "group",
+            Self::Mid       => "mid",
+            Self::Control   => "control",
+            Self::Range     => "range",
+            Self::SendRecv  => "sendrecv",
+            Self::SendOnly  => "sendonly",
+            Self::RecvOnly  => "recvonly",
+            Self::Inactive  => "inactive",
+            Self::IceLite   => "ice-lite",
+            Self::IceOptions => "ice-options",
         })
     }
 }
 
 impl<'a> TryFrom<&'a str> for Key {
-    type Error = anyhow::Error;
+    type Error = SdpError;
     /// # Unit Test
     ///
     /// ```
@@ -517,7 +938,23 @@ impl<'a> TryFrom<&'a str> for Key {
             "type"      => Ok(Self::Type),
             "framerate" => Ok(Self::Framerate),
             "quality"   => Ok(Self::Quality),
-            _ => Err(anyhow!("invalid sdp attributes keys!"))
+            "rtcp-fb"   => Ok(Self::RtcpFb),
+            "ssrc"      => Ok(Self::Ssrc),
+            "ssrc-group" => Ok(Self::SsrcGroup),
+            "msid"      => Ok(Self::Msid),
+            "candidate" => Ok(Self::Candidate),
+            "end-of-candidates" => Ok(Self::EndOfCandidates),
+            "group"     => Ok(Self::Group),
+            "mid"       => Ok(Self::Mid),
+            "control"   => Ok(Self::Control),
+            "range"     => Ok(Self::Range),
+            "sendrecv"  => Ok(Self::SendRecv),
+            "sendonly"  => Ok(Self::SendOnly),
+            "recvonly"  => Ok(Self::RecvOnly),
+            "inactive"  => Ok(Self::Inactive),
+            "ice-lite"  => Ok(Self::IceLite),
+            "ice-options" => Ok(Self::IceOptions),
+            _ => Err(SdpError::InvalidValue("sdp attributes keys"))
         }
     }
 }
@@ -1,7 +1,4 @@
-use anyhow::{
-    Result,
-    anyhow
-};
+use super::SdpError;
 
 use std::{
     convert::TryFrom,
@@ -39,7 +36,7 @@ impl fmt::Display for Orient {
 }
 
 impl<'a> TryFrom<&'a str> for Orient {
-    type Error = anyhow::Error;
+    type Error = SdpError;
     /// # Unit Test
     ///
     /// ```
@@ -56,7 +53,7 @@ impl<'a> TryFrom<&'a str> for Orient {
             "portrait" =>   Ok(Self::Portrait),
             "landscape" =>  Ok(Self::Landscape),
             "seascape" =>   Ok(Self::Seascape),
-            _ => Err(anyhow!("invalid orient!"))
+            _ => Err(SdpError::InvalidValue("orient"))
         }
     }
 }
@@ -0,0 +1,154 @@
+use super::SdpError;
+use std::{
+    convert::TryFrom,
+    fmt
+};
+
+/// Normal Play Time
+///
+/// npt-time = "now" / npt-sec / npt-hhmmss
+/// npt-sec = 1*DIGIT [ "." *DIGIT ]
+/// npt-hhmmss = npt-hh ":" npt-mm ":" npt-ss [ "." *DIGIT ]
+///
+/// the time is kept in seconds, "now" is the current
+/// position of a live stream, see
+/// [RFC2326](https://datatracker.ietf.org/doc/html/rfc2326#section-3.6).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NptTime {
+    Now,
+    Seconds(f64)
+}
+
+/// Range Attribute
+///
+/// a=range:npt=<start>-[<stop>]
+///
+/// the time range of the stored session or media, the range
+/// is open ended for a live stream or a stream of unknown
+/// duration, only normal play time ranges are supported.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Range {
+    pub start: Option<NptTime>,
+    pub stop: Option<NptTime>
+}
+
+impl fmt::Display for NptTime {
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::attributes::*;
+    ///
+    /// assert_eq!(format!("{}", NptTime::Now), "now");
+    /// assert_eq!(format!("{}", NptTime::Seconds(12.5)), "12.5");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Now => write!(f, "now"),
+            Self::Seconds(s) => write!(f, "{}", s)
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for NptTime {
+    type Error = SdpError;
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::attributes::*;
+    /// use std::convert::*;
+    ///
+    /// assert_eq!(NptTime::try_from("now").unwrap(), NptTime::Now);
+    /// assert_eq!(NptTime::try_from("12.5").unwrap(), NptTime::Seconds(12.5));
+    /// assert_eq!(NptTime::try_from("1:02:03.5").unwrap(), NptTime::Seconds(3723.5));
+    /// assert!(NptTime::try_from("1:02").is_err());
+    /// ```
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        if value == "now" {
+            return Ok(Self::Now)
+        }
+
+        let values = value.split(':').collect::<Vec<&str>>();
+        let seconds = match values.len() {
+            1 => values[0].parse::<f64>()?,
+            3 => {
+                let hh = values[0].parse::<u64>()? as f64;
+                let mm = values[1].parse::<u8>()? as f64;
+                hh * 3600.0 + mm * 60.0 + values[2].parse::<f64>()?
+            },
+            _ => return Err(SdpError::InvalidValue("npt time"))
+        };
+
+        Ok(Self::Seconds(seconds))
+    }
+}
+
+impl fmt::Display for Range {
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::attributes::*;
+    ///
+    /// let range = Range {
+    ///     start: Some(NptTime::Seconds(0.0)),
+    ///     stop: None
+    /// };
+    ///
+    /// assert_eq!(format!("{}", range), "npt=0-");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "npt=")?;
+        if let Some(start) = self.start {
+            write!(f, "{}", start)?;
+        }
+
+        write!(f, "-")?;
+        if let Some(stop) = self.stop {
+            write!(f, "{}", stop)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Range {
+    type Error = SdpError;
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::attributes::*;
+    /// use std::convert::*;
+    ///
+    /// let range = Range::try_from("npt=0-").unwrap();
+    /// assert_eq!(range.start, Some(NptTime::Seconds(0.0)));
+    /// assert_eq!(range.stop, None);
+    ///
+    /// let range = Range::try_from("npt=10-34.5").unwrap();
+    /// assert_eq!(range.start, Some(NptTime::Seconds(10.0)));
+    /// assert_eq!(range.stop, Some(NptTime::Seconds(34.5)));
+    ///
+    /// assert!(Range::try_from("clock=19961108T142300Z-").is_err());
+    /// assert!(Range::try_from("npt=10").is_err());
+    /// ```
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let (start, stop) = value
+            .strip_prefix("npt=")
+            .ok_or(SdpError::InvalidValue("range unit"))?
+            .split_once('-')
+            .ok_or(SdpError::MissingField("range"))?;
+        let time = |v: &'a str| match v.trim() {
+            "" => Ok(None),
+            v => NptTime::try_from(v).map(Some)
+        };
+
+        let range = Self {
+            start: time(start)?,
+            stop: time(stop)?
+        };
+
+        if range.start.is_none() && range.stop.is_none() {
+            return Err(SdpError::MissingField("range"))
+        }
+
+        Ok(range)
+    }
+}
@@ -0,0 +1,131 @@
+use super::SdpError;
+use std::{
+    convert::TryFrom,
+    fmt
+};
+
+/// rtcp feedback payload type.
+///
+/// the feedback applies to a single payload type, or to 
+/// all payload types of the media with the wildcard "*".
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PayloadMatch {
+    Any,
+    Payload(u8)
+}
+
+/// RTCP Feedback Capability Attribute
+///
+/// rtcp-fb-syntax = "a=rtcp-fb:" rtcp-fb-pt SP rtcp-fb-val CRLF
+///
+/// The "rtcp-fb" attribute is used to indicate the feedback 
+/// messages an endpoint supports, such as "nack", "nack pli", 
+/// "ccm fir" or "goog-remb", see 
+/// [RFC4585](https://datatracker.ietf.org/doc/html/rfc4585#section-4.2).
+/// 
+/// The payload type "*" indicates that the attribute applies 
+/// to all payload types of the media description.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RtcpFb<'a> {
+    pub payload: PayloadMatch,
+    pub feedback_type: &'a str,
+    pub param: Option<&'a str>
+}
+
+impl fmt::Display for PayloadMatch {
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::attributes::*;
+    ///
+    /// assert_eq!(format!("{}", PayloadMatch::Any), "*");
+    /// assert_eq!(format!("{}", PayloadMatch::Payload(96)), "96");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Any =>        write!(f, "*"),
+            Self::Payload(p) => write!(f, "{}", p)
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for PayloadMatch {
+    type Error = SdpError;
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::attributes::*;
+    /// use std::convert::*;
+    ///
+    /// assert_eq!(PayloadMatch::try_from("*").unwrap(), PayloadMatch::Any);
+    /// assert_eq!(PayloadMatch::try_from("96").unwrap(), PayloadMatch::Payload(96));
+    /// assert!(PayloadMatch::try_from("a").is_err());
+    /// ```
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value {
+            "*" => Ok(Self::Any),
+            _ =>   Ok(Self::Payload(value.parse()?))
+        }
+    }
+}
+
+impl fmt::Display for RtcpFb<'_> {
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::attributes::*;
+    ///
+    /// let fb = RtcpFb {
+    ///     payload: PayloadMatch::Payload(96),
+    ///     feedback_type: "nack",
+    ///     param: Some("pli")
+    /// };
+    ///
+    /// assert_eq!(format!("{}", fb), "96 nack pli");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.payload, self.feedback_type)?;
+        if let Some(param) = self.param {
+            write!(f, " {}", param)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a str> for RtcpFb<'a> {
+    type Error = SdpError;
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::attributes::*;
+    /// use std::convert::*;
+    ///
+    /// let fb = RtcpFb::try_from("96 nack pli").unwrap();
+    /// assert_eq!(fb.payload, PayloadMatch::Payload(96));
+    /// assert_eq!(fb.feedback_type, "nack");
+    /// assert_eq!(fb.param, Some("pli"));
+    ///
+    /// let fb = RtcpFb::try_from("* goog-remb").unwrap();
+    /// assert_eq!(fb.payload, PayloadMatch::Any);
+    /// assert_eq!(fb.feedback_type, "goog-remb");
+    /// assert_eq!(fb.param, None);
+    ///
+    /// assert!(RtcpFb::try_from("96").is_err());
+    /// ```
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let (payload, feedback) = value
+            .split_once(' ')
+            .ok_or(SdpError::MissingField("rtcp-fb"))?;
+        let (feedback_type, param) = match feedback.split_once(' ') {
+            Some((t, p)) => (t, Some(p)),
+            None => (feedback, None)
+        };
+
+        Ok(Self {
+            payload: PayloadMatch::try_from(payload)?,
+            feedback_type,
+            param
+        })
+    }
+}
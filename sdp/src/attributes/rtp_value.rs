@@ -1,7 +1,6 @@
-use super::Codec;
-use anyhow::{
-    Result,
-    ensure
+use super::{
+    SdpError,
+    Codec
 };
 
 use std::{
@@ -69,13 +68,48 @@ use std::{
 /// [RFC3551](https://datatracker.ietf.org/doc/html/rfc3551)) 
 /// packetization is required, the "a=ptime:" attribute is used as given 
 /// in [Section 6.4](https://datatracker.ietf.org/doc/html/rfc8866#section-6.4).
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct RtpValue {
     pub codec: Codec,
     pub frequency: Option<u64>,
     pub channels: Option<u8>
 }
 
+impl RtpValue {
+    /// the static payload types of the audio/video profile.
+    ///
+    /// a static payload type needs no "a=rtpmap:" attribute, 
+    /// only the codecs known here are mapped, see 
+    /// [RFC3551](https://datatracker.ietf.org/doc/html/rfc3551#section-6).
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::attributes::*;
+    ///
+    /// let pcmu = RtpValue::from_static(0).unwrap();
+    /// assert_eq!(pcmu.codec, Codec::Pcmu);
+    /// assert_eq!(pcmu.frequency, Some(8000));
+    /// assert_eq!(RtpValue::from_static(8).unwrap().codec, Codec::Pcma);
+    /// assert!(RtpValue::from_static(96).is_none());
+    /// ```
+    pub fn from_static(pt: u8) -> Option<Self> {
+        let codec = match pt {
+            0 => Codec::Pcmu,
+            8 => Codec::Pcma,
+            9 => Codec::G722,
+            13 => Codec::Cn,
+            _ => return None
+        };
+
+        Some(Self {
+            frequency: Some(8000),
+            channels: None,
+            codec,
+        })
+    }
+}
+
 impl fmt::Display for RtpValue {
     /// # Unit Test
     ///
@@ -106,7 +140,7 @@ impl fmt::Display for RtpValue {
 }
 
 impl<'a> TryFrom<&'a str> for RtpValue {
-    type Error = anyhow::Error;
+    type Error = SdpError;
     /// # Unit Test
     ///
     /// ```
@@ -122,7 +156,10 @@ impl<'a> TryFrom<&'a str> for RtpValue {
     /// ```
     fn try_from(value: &'a str) -> Result<Self, Self::Error> {
         let values = value.split('/').collect::<Vec<&str>>();
-        ensure!(!values.is_empty(), "invalid attributes rtpmap!");
+        if values.is_empty() {
+            return Err(SdpError::MissingField("attributes rtpmap"))
+        }
+
         Ok(Self {
             codec: Codec::try_from(values[0])?,
             frequency: if let Some(c) = values.get(1) { Some(c.parse()?) } else { None },
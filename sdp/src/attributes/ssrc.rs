@@ -0,0 +1,190 @@
+use super::SdpError;
+use std::{
+    convert::TryFrom,
+    fmt
+};
+
+/// Source-Specific Media Attribute
+///
+/// a=ssrc:<ssrc-id> <attribute>
+/// a=ssrc:<ssrc-id> <attribute>:<value>
+///
+/// The "ssrc" attribute indicates a property (known as a 
+/// "source-level attribute") of a media source (RTP stream) 
+/// within an RTP session, see 
+/// [RFC5576](https://datatracker.ietf.org/doc/html/rfc5576#section-4.1).
+/// 
+/// The same ssrc-id usually appears on several lines with 
+/// different attributes, the attributes of all lines are 
+/// grouped under a single ssrc-id.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Ssrc<'a> {
+    pub id: u32,
+    pub attributes: Vec<(&'a str, Option<&'a str>)>
+}
+
+/// Source Group Attribute
+///
+/// a=ssrc-group:<semantics> <ssrc-id> ...
+///
+/// The "ssrc-group" attribute expresses a relationship among 
+/// several sources of an RTP session, for example "FID" 
+/// associates a retransmission (RTX) source with its primary 
+/// source, see 
+/// [RFC5576](https://datatracker.ietf.org/doc/html/rfc5576#section-4.2).
+#[derive(Debug, PartialEq, Eq)]
+pub struct SsrcGroup<'a> {
+    pub semantics: &'a str,
+    pub ssrcs: Vec<u32>
+}
+
+/// Media Stream Identification Attribute
+///
+/// a=msid:<stream id> [<track id>]
+///
+/// The "msid" attribute associates the media description with 
+/// a media stream and a track within it, see 
+/// [RFC8830](https://datatracker.ietf.org/doc/html/rfc8830#section-2).
+#[derive(Debug, PartialEq, Eq)]
+pub struct Msid<'a> {
+    pub stream: &'a str,
+    pub track: Option<&'a str>
+}
+
+impl<'a> Ssrc<'a> {
+    /// the value of a source-level attribute.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::attributes::*;
+    ///
+    /// let ssrc = Ssrc {
+    ///     id: 1,
+    ///     attributes: vec![("cname", Some("panda"))]
+    /// };
+    ///
+    /// assert_eq!(ssrc.get("cname"), Some("panda"));
+    /// assert_eq!(ssrc.get("label"), None);
+    /// ```
+    pub fn get(&self, name: &str) -> Option<&'a str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| *k == name)
+            .and_then(|(_, v)| *v)
+    }
+
+    /// the canonical name of the source.
+    pub fn cname(&self) -> Option<&'a str> {
+        self.get("cname")
+    }
+}
+
+impl fmt::Display for SsrcGroup<'_> {
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::attributes::*;
+    ///
+    /// let group = SsrcGroup {
+    ///     semantics: "FID",
+    ///     ssrcs: vec![1, 2]
+    /// };
+    ///
+    /// assert_eq!(format!("{}", group), "FID 1 2");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.semantics)?;
+        for ssrc in &self.ssrcs {
+            write!(f, " {}", ssrc)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a str> for SsrcGroup<'a> {
+    type Error = SdpError;
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::attributes::*;
+    /// use std::convert::*;
+    ///
+    /// let group = SsrcGroup::try_from("FID 1234 5678").unwrap();
+    /// assert_eq!(group.semantics, "FID");
+    /// assert_eq!(group.ssrcs, vec![1234, 5678]);
+    ///
+    /// assert!(SsrcGroup::try_from("FID").is_err());
+    /// assert!(SsrcGroup::try_from("FID a").is_err());
+    /// ```
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let mut values = value.split(' ');
+        let semantics = values
+            .next()
+            .ok_or(SdpError::MissingField("ssrc-group"))?;
+        let mut ssrcs = Vec::with_capacity(2);
+        for ssrc in values {
+            ssrcs.push(ssrc.parse()?);
+        }
+
+        if ssrcs.is_empty() {
+            return Err(SdpError::MissingField("ssrc-group"))
+        }
+
+        Ok(Self {
+            semantics,
+            ssrcs
+        })
+    }
+}
+
+impl fmt::Display for Msid<'_> {
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::attributes::*;
+    ///
+    /// let msid = Msid {
+    ///     stream: "stream",
+    ///     track: Some("track")
+    /// };
+    ///
+    /// assert_eq!(format!("{}", msid), "stream track");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.stream)?;
+        if let Some(track) = self.track {
+            write!(f, " {}", track)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Msid<'a> {
+    type Error = SdpError;
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::attributes::*;
+    /// use std::convert::*;
+    ///
+    /// let msid = Msid::try_from("stream track").unwrap();
+    /// assert_eq!(msid.stream, "stream");
+    /// assert_eq!(msid.track, Some("track"));
+    ///
+    /// let msid = Msid::try_from("stream").unwrap();
+    /// assert_eq!(msid.track, None);
+    /// ```
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        if value.is_empty() {
+            return Err(SdpError::MissingField("msid"))
+        }
+
+        Ok(match value.split_once(' ') {
+            Some((stream, track)) => Self { stream, track: Some(track) },
+            None => Self { stream: value, track: None }
+        })
+    }
+}
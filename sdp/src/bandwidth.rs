@@ -1,15 +1,20 @@
 use super::util::tuple2_from_split;
-use anyhow::anyhow;
+use super::error::SdpError;
 use std::{
     convert::TryFrom,
     fmt
 };
 
 /// Bandwidth Kind
+///
+/// CT and AS values are given in kilobits per second, TIAS
+/// ([RFC3890](https://datatracker.ietf.org/doc/html/rfc3890))
+/// values are given in bits per second.
 #[derive(Debug, PartialEq, Eq)]
 pub enum BwKind {
     CT,
-    AS
+    AS,
+    TIAS
 }
 
 /// Bandwidth
@@ -39,6 +44,39 @@ pub struct Bandwidth {
     pub bandwidth: usize
 }
 
+impl Bandwidth {
+    /// bandwidth normalized to bits per second.
+    ///
+    /// CT and AS are in kilobits per second and are multiplied
+    /// by 1000, TIAS is already in bits per second. a value too 
+    /// large to be represented saturates at `u64::MAX`.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::bandwidth::*;
+    /// use std::convert::*;
+    ///
+    /// let as_bw = Bandwidth::try_from("AS:128").unwrap();
+    /// let ct_bw = Bandwidth::try_from("CT:128").unwrap();
+    /// let tias_bw = Bandwidth::try_from("TIAS:128000").unwrap();
+    ///
+    /// assert_eq!(as_bw.bits_per_second(), 128000);
+    /// assert_eq!(ct_bw.bits_per_second(), 128000);
+    /// assert_eq!(tias_bw.bits_per_second(), as_bw.bits_per_second());
+    ///
+    /// let huge = Bandwidth::try_from("AS:18446744073709552").unwrap();
+    /// assert_eq!(huge.bits_per_second(), u64::MAX);
+    /// ```
+    pub fn bits_per_second(&self) -> u64 {
+        let value = self.bandwidth as u64;
+        match self.bwtype {
+            BwKind::CT | BwKind::AS => value.saturating_mul(1000),
+            BwKind::TIAS => value
+        }
+    }
+}
+
 impl fmt::Display for Bandwidth {
     /// # Unit Test
     ///
@@ -64,7 +102,7 @@ impl fmt::Display for Bandwidth {
 }
 
 impl<'a> TryFrom<&'a str> for Bandwidth {
-    type Error = anyhow::Error;
+    type Error = SdpError;
     /// # Unit Test
     ///
     /// ```
@@ -78,7 +116,7 @@ impl<'a> TryFrom<&'a str> for Bandwidth {
     /// assert_eq!(instance.bandwidth, 128);
     /// ```
     fn try_from(value: &'a str) -> Result<Self, Self::Error> {
-        let (t, w) = tuple2_from_split(value, ':', "invalid band width!")?;
+        let (t, w) = tuple2_from_split(value, ':', "band width")?;
         Ok(Self {
             bwtype: BwKind::try_from(t)?,
             bandwidth: w.parse()?,
@@ -97,13 +135,14 @@ impl fmt::Display for BwKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", match self {
             Self::CT => "CT",
-            Self::AS => "AS"
+            Self::AS => "AS",
+            Self::TIAS => "TIAS"
         })
     }
 }
 
 impl<'a> TryFrom<&'a str> for BwKind {
-    type Error = anyhow::Error;
+    type Error = SdpError;
     /// # Unit Test
     ///
     /// ```
@@ -112,12 +151,14 @@ impl<'a> TryFrom<&'a str> for BwKind {
     ///
     /// let kind: BwKind = BwKind::try_from("AS").unwrap();
     /// assert_eq!(kind, BwKind::AS);
+    /// assert_eq!(BwKind::try_from("TIAS").unwrap(), BwKind::TIAS);
     /// ```
     fn try_from(value: &'a str) -> Result<Self, Self::Error> {
         match value {
             "CT" => Ok(Self::CT),
             "AS" => Ok(Self::AS),
-            _ => Err(anyhow!("invalid band width type!"))
+            "TIAS" => Ok(Self::TIAS),
+            _ => Err(SdpError::InvalidValue("band width type"))
         }
     }
 }
@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+use super::{
+    attributes::{
+        Attributes,
+        PayloadMatch,
+        RtpValue,
+        RtcpFb
+    },
+    media::{
+        Media,
+        Port
+    },
+    connection::Connection,
+    error::SdpError,
+    timing::Timing,
+    origin::Origin,
+    Sdp
+};
+
+/// Sdp builder.
+///
+/// In an answer, the media descriptions MUST appear in the same 
+/// order as in the offer, and the answer MUST contain exactly the 
+/// same number of "m=" lines as the offer, see 
+/// [RFC3264](https://datatracker.ietf.org/doc/html/rfc3264#section-6).
+/// 
+/// The builder seeds the media descriptions from the offer, so that 
+/// the order is always kept, the caller only decides which payloads 
+/// of each media description are accepted, a media description is 
+/// rejected by setting its port to zero. a media description 
+/// that is not accepted stays rejected.
+#[derive(Debug, Default)]
+pub struct SdpBuilder<'a> {
+    sdp: Sdp<'a>,
+    offered: Vec<Offered<'a>>
+}
+
+/// the offered port and payloads of a media description.
+#[derive(Debug)]
+struct Offered<'a> {
+    port: Port,
+    fmts: Vec<u8>,
+    rtpmap: HashMap<u8, RtpValue>,
+    fmtp: HashMap<u8, HashMap<&'a str, &'a str>>,
+    rtcp_fb: Vec<RtcpFb<'a>>
+}
+
+impl<'a> SdpBuilder<'a> {
+    /// create an empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// create an answer builder for the offer.
+    ///
+    /// every media description of the offer is copied as rejected, 
+    /// with the port set to zero and the first offered payload, 
+    /// the media identification is kept, `accept` restores the 
+    /// offered port.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::builder::SdpBuilder;
+    /// use sdp::media::Encoding;
+    /// use sdp::Sdp;
+    /// use std::convert::*;
+    ///
+    /// let offer = Sdp::try_from(
+    ///     "v=0\r\n\
+    ///      m=audio 9 UDP/TLS/RTP/SAVPF 111\r\n\
+    ///      m=video 9 UDP/TLS/RTP/SAVPF 96 97\r\n\
+    ///      a=rtpmap:96 VP8/90000\r\n\
+    ///      a=rtpmap:97 VP9/90000\r\n"
+    /// ).unwrap();
+    ///
+    /// // a media description that is not accepted is rejected.
+    /// let answer = SdpBuilder::answer_to(&offer).build();
+    /// assert!(answer.media[0].is_rejected());
+    /// assert_eq!(answer.media[0].fmts, vec![111]);
+    /// assert!(answer.media[1].is_rejected());
+    /// assert_eq!(answer.media[1].fmts, vec![96]);
+    ///
+    /// let mut builder = SdpBuilder::answer_to(&offer);
+    /// builder.reject(0).unwrap();
+    /// builder.accept(1, &[97]).unwrap();
+    /// assert!(builder.accept(1, &[98]).is_err());
+    ///
+    /// let answer = builder.build();
+    /// assert_eq!(answer.media.len(), 2);
+    /// assert_eq!(answer.media[0].encoding, Encoding::Audio);
+    /// assert_eq!(answer.media[0].port.num, 0);
+    /// assert_eq!(answer.media[1].encoding, Encoding::Video);
+    /// assert_eq!(answer.media[1].fmts, vec![97]);
+    /// assert_eq!(
+    ///     format!("{}", answer),
+    ///     "v=0\r\n\
+    ///      s=-\r\n\
+    ///      m=audio 0 UDP/TLS/RTP/SAVPF 111\r\n\
+    ///      m=video 9 UDP/TLS/RTP/SAVPF 97\r\n\
+    ///      a=rtpmap:97 VP9/90000\r\n"
+    /// );
+    /// ```
+    pub fn answer_to(offer: &Sdp<'a>) -> Self {
+        let mut builder = Self::new();
+        for media in &offer.media {
+            builder.sdp.media.push(Media {
+                encoding: media.encoding,
+                port: Port {
+                    num: 0,
+                    count: None
+                },
+                protos: media.protos.clone(),
+                fmts: media.fmts.iter().take(1).copied().collect(),
+                information: None,
+                connection: None,
+                bandwidth: None,
+                encryption_key: None,
+                attributes: Attributes {
+                    mid: media.attributes.mid,
+                    ..Default::default()
+                }
+            });
+
+            builder.offered.push(Offered {
+                port: Port {
+                    num: media.port.num,
+                    count: media.port.count
+                },
+                fmts: media.fmts.clone(),
+                rtpmap: media.attributes.rtpmap.clone(),
+                fmtp: media.attributes.fmtp.clone(),
+                rtcp_fb: media.attributes.rtcp_fb.clone()
+            });
+        }
+
+        builder
+    }
+
+    /// set the origin of the session.
+    pub fn origin(&mut self, origin: Origin<'a>) -> &mut Self {
+        self.sdp.origin = Some(origin);
+        self
+    }
+
+    /// set the name of the session.
+    pub fn session_name(&mut self, name: &'a str) -> &mut Self {
+        self.sdp.session_name = Some(name);
+        self
+    }
+
+    /// set the session level connection information.
+    pub fn connection(&mut self, connection: Connection) -> &mut Self {
+        self.sdp.connection = Some(connection);
+        self
+    }
+
+    /// set the timing of the session.
+    pub fn timing(&mut self, timing: Timing) -> &mut Self {
+        self.sdp.timing = Some(timing);
+        self
+    }
+
+    /// the media description at the index, for the caller to 
+    /// fill in the attributes of the answer.
+    pub fn media_mut(&mut self, index: usize) -> Option<&mut Media<'a>> {
+        self.sdp.media.get_mut(index)
+    }
+
+    /// accept the payloads of the media description at the index.
+    ///
+    /// the payloads must have been offered, they are kept in the 
+    /// given order, which is the order of preference. the offered 
+    /// port and the rtpmap, fmtp and rtcp-fb attributes of the 
+    /// accepted payloads are restored, accepting no payload is an 
+    /// error, the media description is rejected instead.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::builder::SdpBuilder;
+    /// use sdp::Sdp;
+    /// use std::convert::*;
+    ///
+    /// let offer = Sdp::try_from(
+    ///     "v=0\r\n\
+    ///      m=video 9 UDP/TLS/RTP/SAVPF 96 97 102\r\n\
+    ///      a=rtpmap:96 VP8/90000\r\n\
+    ///      a=rtpmap:97 rtx/90000\r\n\
+    ///      a=rtpmap:102 H264/90000\r\n\
+    ///      a=fmtp:97 apt=96\r\n\
+    ///      a=fmtp:102 packetization-mode=1;profile-level-id=42e01f\r\n\
+    ///      a=rtcp-fb:96 nack\r\n\
+    ///      a=rtcp-fb:102 nack pli\r\n\
+    ///      a=rtcp-fb:* transport-cc\r\n"
+    /// ).unwrap();
+    ///
+    /// let mut builder = SdpBuilder::answer_to(&offer);
+    /// builder.accept(0, &[96, 97]).unwrap();
+    /// assert_eq!(
+    ///     format!("{}", builder.build()),
+    ///     "v=0\r\n\
+    ///      s=-\r\n\
+    ///      m=video 9 UDP/TLS/RTP/SAVPF 96 97\r\n\
+    ///      a=rtpmap:96 VP8/90000\r\n\
+    ///      a=rtpmap:97 rtx/90000\r\n\
+    ///      a=fmtp:97 apt=96\r\n\
+    ///      a=rtcp-fb:96 nack\r\n\
+    ///      a=rtcp-fb:* transport-cc\r\n"
+    /// );
+    ///
+    /// let mut builder = SdpBuilder::answer_to(&offer);
+    /// builder.accept(0, &[102]).unwrap();
+    /// assert_eq!(
+    ///     format!("{}", builder.build()),
+    ///     "v=0\r\n\
+    ///      s=-\r\n\
+    ///      m=video 9 UDP/TLS/RTP/SAVPF 102\r\n\
+    ///      a=rtpmap:102 H264/90000\r\n\
+    ///      a=fmtp:102 packetization-mode=1;profile-level-id=42e01f\r\n\
+    ///      a=rtcp-fb:102 nack pli\r\n\
+    ///      a=rtcp-fb:* transport-cc\r\n"
+    /// );
+    /// ```
+    pub fn accept(&mut self, index: usize, fmts: &[u8]) -> Result<&mut Self, SdpError> {
+        let offered = self.offered
+            .get(index)
+            .ok_or(SdpError::InvalidValue("media index"))?;
+        if fmts.is_empty() || fmts.iter().any(|f| !offered.fmts.contains(f)) {
+            return Err(SdpError::InvalidValue("media fmts"))
+        }
+
+        let media = &mut self.sdp.media[index];
+        media.port.num = offered.port.num;
+        media.port.count = offered.port.count;
+        media.fmts = fmts.to_vec();
+        media.attributes.rtpmap = offered.rtpmap.clone();
+        media.attributes.fmtp = offered.fmtp
+            .iter()
+            .filter(|(k, _)| fmts.contains(k))
+            .map(|(k, v)| (*k, v.clone()))
+            .collect();
+        media.attributes.rtcp_fb = offered.rtcp_fb
+            .iter()
+            .filter(|fb| match fb.payload {
+                PayloadMatch::Payload(pt) => fmts.contains(&pt),
+                PayloadMatch::Any => true
+            })
+            .cloned()
+            .collect();
+        Ok(self)
+    }
+
+    /// reject the media description at the index.
+    ///
+    /// An offered stream is rejected by setting the port in the 
+    /// corresponding "m=" line to zero, the format list must still 
+    /// be present, by convention only the first offered format is 
+    /// kept.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::builder::SdpBuilder;
+    /// use sdp::Sdp;
+    /// use std::convert::*;
+    ///
+    /// let offer = Sdp::try_from(
+    ///     "v=0\r\n\
+    ///      m=video 9 UDP/TLS/RTP/SAVPF 96 97\r\n\
+    ///      a=mid:0\r\n\
+    ///      a=rtpmap:96 VP8/90000\r\n"
+    /// ).unwrap();
+    ///
+    /// let mut builder = SdpBuilder::answer_to(&offer);
+    /// builder.reject(0).unwrap();
+    ///
+    /// let answer = builder.build();
+    /// assert!(answer.media[0].is_rejected());
+    /// assert_eq!(
+    ///     format!("{}", answer),
+    ///     "v=0\r\n\
+    ///      s=-\r\n\
+    ///      m=video 0 UDP/TLS/RTP/SAVPF 96\r\n\
+    ///      a=mid:0\r\n"
+    /// );
+    /// ```
+    pub fn reject(&mut self, index: usize) -> Result<&mut Self, SdpError> {
+        let offered = self.offered
+            .get(index)
+            .ok_or(SdpError::InvalidValue("media index"))?;
+        let media = &mut self.sdp.media[index];
+        media.port.num = 0;
+        media.port.count = None;
+        media.fmts = offered.fmts.iter().take(1).copied().collect();
+        media.attributes = Attributes {
+            mid: media.attributes.mid,
+            ..Default::default()
+        };
+
+        Ok(self)
+    }
+
+    /// build the session description.
+    ///
+    /// the rtpmap attributes of payloads that are not accepted 
+    /// are removed.
+    pub fn build(mut self) -> Sdp<'a> {
+        for media in &mut self.sdp.media {
+            let fmts = &media.fmts;
+            media.attributes
+                .rtpmap
+                .retain(|k, _| fmts.contains(k));
+        }
+
+        self.sdp
+    }
+}
@@ -1,7 +1,7 @@
 use super::util::tuple3_from_split;
 use std::net::IpAddr;
-use anyhow::ensure;
 use super::{
+    error::SdpError,
     NetKind,
     AddrKind
 };
@@ -11,7 +11,7 @@ use std::{
     fmt
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct Addr {
     pub ip: IpAddr,
     /// IPv6 multicast does not use TTL scoping, and hence the TTL value MUST
@@ -25,7 +25,7 @@ pub struct Addr {
 ///
 /// The "c=" line (connection-field) contains information necessary to
 /// establish a network connection.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct Connection {
     /// <nettype>  is a text string giving the type of network.  Initially,
     /// "IN" is defined to have the meaning "Internet".
@@ -71,7 +71,7 @@ impl fmt::Display for Connection {
 }
 
 impl<'a> TryFrom<&'a str> for Connection {
-    type Error = anyhow::Error;
+    type Error = SdpError;
     /// # Unit Test
     ///
     /// ```
@@ -91,7 +91,7 @@ impl<'a> TryFrom<&'a str> for Connection {
     /// assert_eq!(instance.connection_address.count, None);
     /// ```
     fn try_from(value: &'a str) -> Result<Self, Self::Error> {
-        let (n, a, c) = tuple3_from_split(value, ' ', "invalid connection information!")?;
+        let (n, a, c) = tuple3_from_split(value, ' ', "connection information")?;
         Ok(Self {
             nettype: NetKind::try_from(n)?,
             addrtype: AddrKind::try_from(a)?,
@@ -132,7 +132,7 @@ impl fmt::Display for Addr {
 }
 
 impl<'a> TryFrom<&'a str> for Addr {
-    type Error = anyhow::Error;
+    type Error = SdpError;
     /// # Unit Test
     ///
     /// ```
@@ -150,12 +150,14 @@ impl<'a> TryFrom<&'a str> for Addr {
     /// assert_eq!(instance.count, Some(2));
     /// ```
     fn try_from(value: &'a str) -> Result<Self, Self::Error> {
-        let values = value.split('/').collect::<Vec<&str>>();
-        ensure!(!values.is_empty(), "invalid connection information!");
+        let mut values = value.split('/');
+        let ip = values
+            .next()
+            .ok_or(SdpError::MissingField("connection information"))?;
         Ok(Self {
-            ip: values[0].parse()?,
-            ttl: if let Some(t) = values.get(1) { Some(t.parse()?) } else { None },
-            count: if let Some(c) =  values.get(2) { Some(c.parse()?) } else { None}
+            ip: ip.parse()?,
+            ttl: if let Some(t) = values.next() { Some(t.parse()?) } else { None },
+            count: if let Some(c) = values.next() { Some(c.parse()?) } else { None }
         })
     }
 }
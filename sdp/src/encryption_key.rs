@@ -0,0 +1,85 @@
+use super::error::SdpError;
+use std::{
+    convert::TryFrom,
+    fmt
+};
+
+/// Encryption Key ("k=")
+///
+/// k=<method>
+/// k=<method>:<encryption key>
+///
+/// The "k=" line (key-field) is obsolete and MUST NOT be used.  It is
+/// included in this document for legacy reasons.  One MUST NOT include
+/// a "k=" line in an SDP, and MUST discard it if it is received in an
+/// SDP, see 
+/// [RFC8866](https://datatracker.ietf.org/doc/html/rfc8866#section-5.12).
+///
+/// the line is only kept so that a description is not changed when 
+/// it is parsed and written again, the key should be ignored.
+#[derive(Debug, PartialEq, Eq)]
+pub struct EncryptionKey<'a> {
+    /// the method, one of "clear", "base64", "uri" and "prompt".
+    pub method: &'a str,
+    pub key: Option<&'a str>
+}
+
+impl fmt::Display for EncryptionKey<'_> {
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::encryption_key::*;
+    ///
+    /// let key = EncryptionKey {
+    ///     method: "clear",
+    ///     key: Some("secret")
+    /// };
+    ///
+    /// let prompt = EncryptionKey {
+    ///     method: "prompt",
+    ///     key: None
+    /// };
+    ///
+    /// assert_eq!(format!("{}", key), "clear:secret");
+    /// assert_eq!(format!("{}", prompt), "prompt");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.method)?;
+        if let Some(key) = self.key {
+            write!(f, ":{}", key)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a str> for EncryptionKey<'a> {
+    type Error = SdpError;
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::encryption_key::*;
+    /// use std::convert::*;
+    ///
+    /// let key = EncryptionKey::try_from("clear:secret").unwrap();
+    /// assert_eq!(key.method, "clear");
+    /// assert_eq!(key.key, Some("secret"));
+    ///
+    /// let key = EncryptionKey::try_from("uri:http://example.com/key").unwrap();
+    /// assert_eq!(key.key, Some("http://example.com/key"));
+    ///
+    /// let key = EncryptionKey::try_from("prompt").unwrap();
+    /// assert_eq!(key.key, None);
+    /// assert!(EncryptionKey::try_from("").is_err());
+    /// ```
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        if value.is_empty() {
+            return Err(SdpError::MissingField("encryption key"))
+        }
+
+        Ok(match value.split_once(':') {
+            Some((method, key)) => Self { method, key: Some(key) },
+            None => Self { method: value, key: None }
+        })
+    }
+}
@@ -0,0 +1,76 @@
+use thiserror::Error;
+use std::{
+    net::AddrParseError,
+    num::{
+        ParseFloatError,
+        ParseIntError
+    }
+};
+
+/// sdp parse error.
+///
+/// the error returned by all sdp line parsers, it converts into
+/// `anyhow::Error` so the `?` operator still works for callers
+/// using anyhow.
+///
+/// # Unit Test
+///
+/// ```
+/// use sdp::connection::Connection;
+/// use sdp::error::SdpError;
+/// use std::convert::*;
+///
+/// let err = Connection::try_from("IN IPX 0.0.0.0").unwrap_err();
+/// assert_eq!(err, SdpError::InvalidAddrKind);
+///
+/// let err = Connection::try_from("IN IP4").unwrap_err();
+/// assert_eq!(err, SdpError::MissingField("connection information"));
+///
+/// let err = Connection::try_from("IN IP4 0.0.0").unwrap_err();
+/// assert!(matches!(err, SdpError::InvalidAddr(_)));
+///
+/// let err: anyhow::Error = SdpError::InvalidNetKind.into();
+/// assert_eq!(err.to_string(), "invalid nettype!");
+/// ```
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SdpError {
+    /// a required sub-field is missing.
+    #[error("invalid {0}!")]
+    MissingField(&'static str),
+    /// the nettype is not "IN".
+    #[error("invalid nettype!")]
+    InvalidNetKind,
+    /// the addrtype is not "IP4" or "IP6".
+    #[error("invalid addrtype!")]
+    InvalidAddrKind,
+    /// the address is not a valid ip address.
+    #[error("invalid address: {0}")]
+    InvalidAddr(#[from] AddrParseError),
+    /// the value is not a valid integer.
+    #[error("invalid number: {0}")]
+    ParseInt(#[from] ParseIntError),
+    /// the value is not a valid float.
+    #[error("invalid number: {0}")]
+    ParseFloat(#[from] ParseFloatError),
+    /// the value is not one of the known values.
+    #[error("invalid {0}!")]
+    InvalidValue(&'static str),
+    /// a group references a mid that no media description has.
+    #[error("unknown mid: {0}")]
+    UnknownMid(String),
+    /// a line is not in the order mandated by the RFC.
+    #[error("out of order line: {0}")]
+    OutOfOrder(String),
+    /// a line that may appear at most once is repeated.
+    #[error("duplicate line {line}: {value}, first at line {first}")]
+    Duplicate {
+        value: String,
+        first: usize,
+        line: usize
+    },
+    /// the description exceeds a parser limit.
+    #[error("too many {0}!")]
+    TooMany(&'static str),
+}
+
+pub type Result<T, E = SdpError> = std::result::Result<T, E>;
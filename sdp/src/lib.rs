@@ -1,28 +1,37 @@
 pub mod attributes;
+pub mod encryption_key;
 pub mod repeat_times;
 pub mod time_zones;
 pub mod connection;
 pub mod bandwidth;
+pub mod error;
 pub mod origin;
 pub mod timing;
 pub mod media;
+pub mod builder;
+pub mod negotiate;
 pub mod util;
 
 use repeat_times::RepeatTimes;
-use attributes::Attributes;
+use attributes::{
+    PayloadMatch,
+    Attributes,
+    Direction,
+    Group,
+    Range
+};
 use connection::Connection;
 use time_zones::TimeZones;
 use bandwidth::Bandwidth;
+use encryption_key::EncryptionKey;
 use timing::Timing;
 use origin::Origin;
+use error::SdpError;
 use media::Media;
-use anyhow::{
-    ensure,
-    anyhow
-};
 
 use std::{
     convert::TryFrom,
+    borrow::Cow,
     fmt
 };
 
@@ -40,19 +49,20 @@ pub enum Key {
     Timing,
     RepeatTimes,
     TimeZones,
+    EncryptionKey,
     Attributes,
     Media,
 }
 
 /// Network type.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum NetKind {
     /// Internet
     IN,
 }
 
 /// Address type.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum AddrKind {
     /// Ipv4
     IP4,
@@ -60,6 +70,82 @@ pub enum AddrKind {
     IP6,
 }
 
+/// the default maximum number of media descriptions.
+pub const DEFAULT_MAX_MEDIA: usize = 64;
+
+/// the default maximum number of attribute lines.
+pub const DEFAULT_MAX_ATTRIBUTES: usize = 1024;
+
+/// parser limits.
+///
+/// a signaling server parses descriptions of untrusted peers, 
+/// the limits bound the number of media descriptions and the 
+/// number of attribute lines, of the session and of all media 
+/// descriptions together, so a huge description is rejected 
+/// instead of growing the description without a limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    pub max_media: usize,
+    pub max_attributes: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_media: DEFAULT_MAX_MEDIA,
+            max_attributes: DEFAULT_MAX_ATTRIBUTES,
+        }
+    }
+}
+
+/// line ending of the serializer.
+///
+/// the specification terminates every line with CRLF, which is 
+/// the default, some strict SIP stacks reject the bare LF. the 
+/// bare LF is only meant for debugging, the parser accepts both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    #[default]
+    Crlf,
+    Lf,
+}
+
+/// session description writer with the line ending, 
+/// see `Sdp::display_with`.
+pub struct SdpDisplay<'s, 'a> {
+    sdp: &'s Sdp<'a>,
+    ending: LineEnding,
+}
+
+/// rewrite the CRLF line endings to LF.
+///
+/// a line ending is always written in one piece, 
+/// the values of the lines never contain CR or LF.
+struct LfWriter<'f, 'g>(&'f mut fmt::Formatter<'g>);
+
+impl fmt::Write for LfWriter<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for (i, part) in s.split("\r\n").enumerate() {
+            if i > 0 {
+                self.0.write_str("\n")?;
+            }
+
+            self.0.write_str(part)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for SdpDisplay<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.ending {
+            LineEnding::Crlf => write!(f, "{}", self.sdp),
+            LineEnding::Lf => fmt::Write::write_fmt(&mut LfWriter(f), format_args!("{}", self.sdp))
+        }
+    }
+}
+
 /// SDP: Session Description Protocol
 ///
 /// An SDP description is denoted by the media type "application/sdp"
@@ -156,15 +242,698 @@ pub struct Sdp<'a> {
     pub repeat_times: Option<RepeatTimes>,
     /// Time Zones ("z=")
     pub time_zones: Option<TimeZones>,
+    /// Encryption Key ("k=")
+    /// the line is obsolete, it is only kept for round-tripping.
+    pub encryption_key: Option<EncryptionKey<'a>>,
     /// Attributes ("a=")
     pub attributes: Attributes<'a>,
     /// Media ("m=")
-    pub media: Option<Media>,
+    /// Each media description starts with an "m=" line, the "c=",
+    /// "b=" and "a=" lines that follow it belong to that media.
+    pub media: Vec<Media<'a>>,
 }
 
 impl<'a> Sdp<'a> {
-    pub fn handle_line(&mut self, key: Key, data: &'a str) -> anyhow::Result<()> {
-        Ok(match key {
+    /// session bandwidth in bits per second.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::Sdp;
+    /// use std::convert::*;
+    ///
+    /// let kbps = Sdp::try_from("v=0\r\nb=AS:256\r\n").unwrap();
+    /// let bps = Sdp::try_from("v=0\r\nb=TIAS:256000\r\n").unwrap();
+    /// let none = Sdp::try_from("v=0\r\n").unwrap();
+    ///
+    /// assert_eq!(kbps.total_bandwidth_bps(), Some(256000));
+    /// assert_eq!(kbps.total_bandwidth_bps(), bps.total_bandwidth_bps());
+    /// assert_eq!(none.total_bandwidth_bps(), None);
+    /// ```
+    pub fn total_bandwidth_bps(&self) -> Option<u64> {
+        self.bandwidth
+            .as_ref()
+            .map(Bandwidth::bits_per_second)
+    }
+
+    /// the email address of the session contact.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::Sdp;
+    /// use std::convert::*;
+    ///
+    /// let sdp = Sdp::try_from(
+    ///     "v=0\r\n\
+    ///      e=Jane Doe <j.doe@example.com>\r\n\
+    ///      p=+1 617 555-6011 (Jane Doe)\r\n"
+    /// ).unwrap();
+    ///
+    /// assert_eq!(sdp.email_address(), Some("j.doe@example.com"));
+    /// assert_eq!(sdp.email_display_name(), Some("Jane Doe"));
+    /// assert_eq!(sdp.phone_number(), Some("+1 617 555-6011"));
+    /// assert_eq!(sdp.phone_display_name(), Some("Jane Doe"));
+    ///
+    /// let sdp = Sdp::try_from(
+    ///     "v=0\r\n\
+    ///      e=j.doe@example.com (Jane Doe)\r\n\
+    ///      p=+1 617 555-6011\r\n"
+    /// ).unwrap();
+    ///
+    /// assert_eq!(sdp.email_address(), Some("j.doe@example.com"));
+    /// assert_eq!(sdp.email_display_name(), Some("Jane Doe"));
+    /// assert_eq!(sdp.phone_number(), Some("+1 617 555-6011"));
+    /// assert_eq!(sdp.phone_display_name(), None);
+    /// ```
+    pub fn email_address(&self) -> Option<&'a str> {
+        self.email.map(|e| util::contact(e).0)
+    }
+
+    /// the display name of the session contact email.
+    pub fn email_display_name(&self) -> Option<&'a str> {
+        self.email.and_then(|e| util::contact(e).1)
+    }
+
+    /// the phone number of the session contact.
+    pub fn phone_number(&self) -> Option<&'a str> {
+        self.phone.map(|p| util::contact(p).0)
+    }
+
+    /// the display name of the session contact phone.
+    pub fn phone_display_name(&self) -> Option<&'a str> {
+        self.phone.and_then(|p| util::contact(p).1)
+    }
+
+    /// character set of the session text fields.
+    ///
+    /// the "a=charset:" attribute specifies the character set 
+    /// used in the "s=", "i=" and "e=" fields, they are UTF-8 
+    /// when it is absent. the description is parsed from a str, 
+    /// a description in another character set must be decoded 
+    /// before parsing, see `Sdp::sniff_charset`.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::Sdp;
+    /// use std::convert::*;
+    ///
+    /// let sdp = Sdp::try_from(
+    ///     "v=0\r\n\
+    ///      s=Caf\u{e9}\r\n\
+    ///      a=charset:ISO-8859-1\r\n\
+    ///      m=audio 9 UDP 0\r\n"
+    /// ).unwrap();
+    ///
+    /// assert_eq!(sdp.charset(), Some("ISO-8859-1"));
+    /// assert_eq!(sdp.session_name, Some("Caf\u{e9}"));
+    /// assert_eq!(sdp.media[0].charset(&sdp), Some("ISO-8859-1"));
+    /// assert_eq!(Sdp::try_from("v=0\r\n").unwrap().charset(), None);
+    /// ```
+    pub fn charset(&self) -> Option<&'a str> {
+        self.attributes.charset
+    }
+
+    /// whether the agent is an ICE lite implementation.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::Sdp;
+    /// use std::convert::*;
+    ///
+    /// let sdp = Sdp::try_from(
+    ///     "v=0\r\n\
+    ///      a=ice-lite\r\n\
+    ///      a=ice-options:trickle renomination\r\n"
+    /// ).unwrap();
+    ///
+    /// assert!(sdp.ice_lite());
+    /// assert_eq!(sdp.ice_options(), vec!["trickle", "renomination"]);
+    ///
+    /// let sdp = Sdp::try_from("v=0\r\n").unwrap();
+    /// assert!(!sdp.ice_lite());
+    /// assert!(sdp.ice_options().is_empty());
+    /// ```
+    pub fn ice_lite(&self) -> bool {
+        self.attributes.ice_lite
+    }
+
+    /// ICE options of the session.
+    pub fn ice_options(&self) -> Vec<&'a str> {
+        self.attributes.ice_options.clone()
+    }
+
+    /// RTSP aggregate control url of the session.
+    pub fn control(&self) -> Option<&'a str> {
+        self.attributes.control
+    }
+
+    /// time range of the session.
+    pub fn range(&self) -> Option<Range> {
+        self.attributes.range
+    }
+
+    /// find the session character set in an undecoded description.
+    ///
+    /// the session level "a=charset:" line is ASCII, so it can be
+    /// found before the description is decoded, the caller decodes 
+    /// the description with it and then parses the result.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::Sdp;
+    ///
+    /// let raw = b"v=0\r\ns=Caf\xe9\r\na=charset:ISO-8859-1\r\n";
+    /// assert_eq!(Sdp::sniff_charset(raw), Some("ISO-8859-1"));
+    /// assert_eq!(Sdp::sniff_charset(b"v=0\r\n"), None);
+    /// ```
+    pub fn sniff_charset(raw: &[u8]) -> Option<&str> {
+        raw.split(|b| *b == b'\n')
+            .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+            .take_while(|line| !line.starts_with(b"m="))
+            .find_map(|line| line.strip_prefix(b"a=charset:"))
+            .and_then(|charset| std::str::from_utf8(charset).ok())
+    }
+
+    /// media grouping of the session.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::Sdp;
+    /// use std::convert::*;
+    ///
+    /// let sdp = Sdp::try_from(
+    ///     "v=0\r\n\
+    ///      a=group:BUNDLE audio video\r\n"
+    /// ).unwrap();
+    ///
+    /// let bundle = &sdp.groups()[0];
+    /// assert_eq!(bundle.semantics, "BUNDLE");
+    /// assert_eq!(bundle.mids, vec!["audio", "video"]);
+    /// ```
+    pub fn groups(&self) -> &[Group<'a>] {
+        &self.attributes.groups
+    }
+
+    /// the media description identified by the mid.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::media::Encoding;
+    /// use sdp::Sdp;
+    /// use std::convert::*;
+    ///
+    /// let sdp = Sdp::try_from(
+    ///     "v=0\r\n\
+    ///      a=group:BUNDLE 0 1\r\n\
+    ///      m=audio 9 UDP 0\r\n\
+    ///      a=mid:0\r\n\
+    ///      m=video 9 UDP 96\r\n\
+    ///      a=mid:1\r\n"
+    /// ).unwrap();
+    ///
+    /// let audio = sdp.media_by_mid("0").unwrap();
+    /// let video = sdp.media_by_mid("1").unwrap();
+    ///
+    /// assert_eq!(audio.encoding, Encoding::Audio);
+    /// assert_eq!(audio.mid(), Some("0"));
+    /// assert_eq!(video.encoding, Encoding::Video);
+    /// assert_eq!(video.mid(), Some("1"));
+    /// assert!(sdp.media_by_mid("2").is_none());
+    /// ```
+    pub fn media_by_mid(&self, mid: &str) -> Option<&Media<'a>> {
+        self.media
+            .iter()
+            .find(|m| m.mid() == Some(mid))
+    }
+
+    /// validate the session description.
+    ///
+    /// every identification tag referenced by a group must be
+    /// the mid of a media description of the session.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::error::SdpError;
+    /// use sdp::Sdp;
+    /// use std::convert::*;
+    ///
+    /// let sdp = Sdp::try_from(
+    ///     "v=0\r\n\
+    ///      a=group:BUNDLE audio video\r\n\
+    ///      m=audio 9 UDP 0\r\n\
+    ///      a=mid:audio\r\n\
+    ///      m=video 9 UDP 96\r\n\
+    ///      a=mid:video\r\n"
+    /// ).unwrap();
+    ///
+    /// assert!(sdp.validate().is_ok());
+    ///
+    /// let sdp = Sdp::try_from(
+    ///     "v=0\r\n\
+    ///      a=group:BUNDLE audio data\r\n\
+    ///      m=audio 9 UDP 0\r\n\
+    ///      a=mid:audio\r\n"
+    /// ).unwrap();
+    ///
+    /// assert_eq!(
+    ///     sdp.validate(), 
+    ///     Err(SdpError::UnknownMid("data".to_string()))
+    /// );
+    /// ```
+    pub fn validate(&self) -> error::Result<()> {
+        for group in self.groups() {
+            for mid in &group.mids {
+                if self.media_by_mid(mid).is_none() {
+                    return Err(SdpError::UnknownMid(mid.to_string()))
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// merge an answer into the offer.
+    ///
+    /// the media descriptions of the answer correspond to the media 
+    /// descriptions of the offer in order, the offered payloads are 
+    /// narrowed to the payloads accepted by the answer, the direction 
+    /// is the reverse of the answered direction and the connection 
+    /// is the connection of the answerer. a media description rejected 
+    /// by the answer is rejected and marked inactive.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::attributes::Direction;
+    /// use sdp::Sdp;
+    /// use std::convert::*;
+    ///
+    /// let mut offer = Sdp::try_from(
+    ///     "v=0\r\n\
+    ///      c=IN IP4 10.0.0.1\r\n\
+    ///      m=video 9 UDP 96 97\r\n\
+    ///      a=sendrecv\r\n\
+    ///      a=rtpmap:96 VP8/90000\r\n\
+    ///      a=rtpmap:97 H264/90000\r\n\
+    ///      a=rtcp-fb:96 nack\r\n\
+    ///      a=rtcp-fb:97 nack\r\n\
+    ///      m=video 9 UDP 96\r\n\
+    ///      a=sendonly\r\n"
+    /// ).unwrap();
+    ///
+    /// let answer = Sdp::try_from(
+    ///     "v=0\r\n\
+    ///      c=IN IP4 10.0.0.2\r\n\
+    ///      m=video 9 UDP 97\r\n\
+    ///      a=recvonly\r\n\
+    ///      a=rtpmap:97 H264/90000\r\n\
+    ///      m=video 0 UDP 96\r\n"
+    /// ).unwrap();
+    ///
+    /// offer.apply_answer(&answer).unwrap();
+    ///
+    /// let video = &offer.media[0];
+    /// assert_eq!(video.fmts, vec![97]);
+    /// assert_eq!(video.attributes.rtpmap.len(), 1);
+    /// assert_eq!(video.rtcp_fb().len(), 1);
+    /// assert_eq!(video.direction(&offer), Some(Direction::SendOnly));
+    /// assert_eq!(
+    ///     video.effective_connection(&offer).unwrap().connection_address.ip.to_string(), 
+    ///     "10.0.0.2"
+    /// );
+    ///
+    /// assert!(offer.media[1].is_rejected());
+    /// assert_eq!(offer.media[1].direction(&offer), Some(Direction::Inactive));
+    /// assert!(offer.apply_answer(&Sdp::try_from("v=0\r\n").unwrap()).is_err());
+    /// ```
+    pub fn apply_answer(&mut self, answer: &Sdp) -> error::Result<()> {
+        if answer.media.len() != self.media.len() {
+            return Err(SdpError::InvalidValue("answer media"))
+        }
+
+        for (media, remote) in self.media.iter_mut().zip(answer.media.iter()) {
+            if remote.is_rejected() {
+                media.port.num = 0;
+                media.port.count = None;
+                media.attributes.set_direction(Direction::Inactive);
+                continue
+            }
+
+            media.fmts.retain(|f| remote.fmts.contains(f));
+            let fmts = &media.fmts;
+            media.attributes.rtpmap.retain(|k, _| fmts.contains(k));
+            media.attributes.fmtp.retain(|k, _| fmts.contains(k));
+            media.attributes.rtcp_fb.retain(|fb| match fb.payload {
+                PayloadMatch::Payload(pt) => fmts.contains(&pt),
+                PayloadMatch::Any => true
+            });
+
+            if let Some(direction) = remote.direction(answer) {
+                media.attributes.set_direction(direction.reverse());
+            }
+
+            if let Some(connection) = remote.effective_connection(answer) {
+                media.connection = Some(*connection);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// validate the line order of a session description.
+    ///
+    /// the parser is lenient and accepts the lines in any order, 
+    /// some peers reject a description that does not follow the 
+    /// order of [RFC8866](https://datatracker.ietf.org/doc/html/rfc8866#section-5):
+    ///
+    /// ```text
+    /// session: v o s i u e* p* c b* (t r*)+ z k a*
+    /// media:   m i c* b* k a*
+    /// ```
+    ///
+    /// lines with an unknown type are ignored, the first 
+    /// offending line is returned.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::error::SdpError;
+    /// use sdp::Sdp;
+    ///
+    /// let sdp = "v=0\r\n\
+    ///     o=- 0 0 IN IP4 127.0.0.1\r\n\
+    ///     s=-\r\n\
+    ///     t=0 0\r\n\
+    ///     r=7d 1h 0 25h\r\n\
+    ///     t=0 0\r\n\
+    ///     a=group:BUNDLE 0\r\n\
+    ///     m=audio 9 UDP 0\r\n\
+    ///     c=IN IP4 0.0.0.0\r\n\
+    ///     a=mid:0\r\n\
+    ///     a=sendrecv\r\n";
+    /// assert!(Sdp::validate_order(sdp).is_ok());
+    ///
+    /// let sdp = "v=0\r\ns=-\r\no=- 0 0 IN IP4 127.0.0.1\r\nt=0 0\r\n";
+    /// assert_eq!(
+    ///     Sdp::validate_order(sdp), 
+    ///     Err(SdpError::OutOfOrder("o=- 0 0 IN IP4 127.0.0.1".to_string()))
+    /// );
+    ///
+    /// let sdp = "v=0\r\ns=-\r\nt=0 0\r\nm=audio 9 UDP 0\r\na=mid:0\r\nc=IN IP4 0.0.0.0\r\n";
+    /// assert_eq!(
+    ///     Sdp::validate_order(sdp), 
+    ///     Err(SdpError::OutOfOrder("c=IN IP4 0.0.0.0".to_string()))
+    /// );
+    ///
+    /// assert!(Sdp::validate_order("s=-\r\nv=0\r\n").is_err());
+    /// assert!(Sdp::validate_order("v=0\r\nm=audio 9 UDP 0\r\nt=0 0\r\n").is_err());
+    /// ```
+    #[rustfmt::skip]
+    pub fn validate_order(value: &str) -> error::Result<()> {
+        const SESSION: (&str, &str) = ("vosiuepcbtrzka", "epbtra");
+        const MEDIA: (&str, &str) = ("micbka", "cba");
+
+        let (mut order, mut repeatable) = SESSION;
+        let mut last: Option<usize> = None;
+        for line in value.lines().filter(|l| !l.is_empty()) {
+            let out_of_order = || SdpError::OutOfOrder(line.to_string());
+            let key = line.chars().next().unwrap_or_default();
+            if key == 'm' {
+                if last.is_none() {
+                    return Err(out_of_order())
+                }
+
+                (order, repeatable) = MEDIA;
+                last = Some(0);
+                continue
+            }
+
+            let rank = match order.find(key) {
+                Some(rank) => rank,
+                None if SESSION.0.contains(key) => return Err(out_of_order()),
+                None => continue
+            };
+
+            let in_order = match last {
+                None => key == 'v',
+                Some(l) if rank > l => true,
+                Some(l) if rank == l => repeatable.contains(key),
+                Some(l) => key == 't' && order[l..].starts_with('r')
+            };
+
+            if !in_order {
+                return Err(out_of_order())
+            }
+
+            last = Some(rank);
+        }
+
+        Ok(())
+    }
+
+    /// validate that the singleton lines appear at most once.
+    ///
+    /// the parser is lenient and keeps the last of the repeated 
+    /// lines, which masks a malformed description, in strict mode 
+    /// the "v=", "o=", "s=", "i=", "u=", "c=", "z=" and "k=" lines 
+    /// of the session and the "i=" and "k=" lines of each media 
+    /// description may appear at most once, see
+    /// [RFC8866](https://datatracker.ietf.org/doc/html/rfc8866#section-5).
+    ///
+    /// the line numbers start at one, the first repeated 
+    /// line is returned with the line number of the first 
+    /// occurrence.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::error::SdpError;
+    /// use sdp::Sdp;
+    ///
+    /// let sdp = "v=0\r\n\
+    ///     o=- 0 0 IN IP4 127.0.0.1\r\n\
+    ///     s=-\r\n\
+    ///     t=0 0\r\n\
+    ///     m=audio 9 UDP 0\r\n\
+    ///     i=audio\r\n\
+    ///     m=video 9 UDP 96\r\n\
+    ///     i=video\r\n";
+    /// assert!(Sdp::validate_singletons(sdp).is_ok());
+    ///
+    /// let sdp = "v=0\r\n\
+    ///     o=- 0 0 IN IP4 127.0.0.1\r\n\
+    ///     s=-\r\n\
+    ///     o=- 1 1 IN IP4 127.0.0.1\r\n\
+    ///     t=0 0\r\n";
+    /// assert_eq!(
+    ///     Sdp::validate_singletons(sdp), 
+    ///     Err(SdpError::Duplicate {
+    ///         value: "o=- 1 1 IN IP4 127.0.0.1".to_string(),
+    ///         first: 2,
+    ///         line: 4
+    ///     })
+    /// );
+    ///
+    /// let sdp = "v=0\r\nm=audio 9 UDP 0\r\ni=a\r\ni=b\r\n";
+    /// assert!(Sdp::validate_singletons(sdp).is_err());
+    /// ```
+    #[rustfmt::skip]
+    pub fn validate_singletons(value: &str) -> error::Result<()> {
+        const SESSION: &str = "vosiuczk";
+        const MEDIA: &str = "ik";
+
+        let mut singletons = SESSION;
+        let mut seen: Vec<(char, usize)> = Vec::with_capacity(SESSION.len());
+        for (i, line) in value.lines().enumerate() {
+            let key = match line.chars().next() {
+                Some(k) => k,
+                None => continue
+            };
+
+            if key == 'm' {
+                singletons = MEDIA;
+                seen.clear();
+                continue
+            }
+
+            if !singletons.contains(key) {
+                continue
+            }
+
+            if let Some((_, first)) = seen.iter().find(|(k, _)| *k == key) {
+                return Err(SdpError::Duplicate {
+                    value: line.to_string(),
+                    first: *first,
+                    line: i + 1
+                })
+            }
+
+            seen.push((key, i + 1));
+        }
+
+        Ok(())
+    }
+
+    /// append a trickled candidate to the media description.
+    ///
+    /// With trickle ICE the candidates are gathered after the offer
+    /// or answer was sent, they are added to the media description 
+    /// at the given index as they arrive. the candidate is the 
+    /// attribute value, a "candidate:" prefix, as in the candidate 
+    /// string of the browsers, is stripped.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::Sdp;
+    /// use std::convert::*;
+    ///
+    /// let mut sdp = Sdp::try_from(
+    ///     "v=0\r\n\
+    ///      s=-\r\n\
+    ///      m=audio 9 UDP 0\r\n"
+    /// ).unwrap();
+    ///
+    /// let candidate = "1 1 UDP 2130706431 10.0.1.1 8998 typ host".to_string();
+    /// sdp.add_candidate(0, candidate).unwrap();
+    /// sdp.add_candidate(0, "candidate:2 1 UDP 1694498815 192.0.2.3 45664 typ srflx").unwrap();
+    /// sdp.end_of_candidates(0).unwrap();
+    /// assert!(sdp.add_candidate(1, "").is_err());
+    ///
+    /// assert_eq!(sdp.media[0].candidates().len(), 2);
+    /// assert!(sdp.media[0].end_of_candidates());
+    /// assert_eq!(
+    ///     format!("{}", sdp),
+    ///     "v=0\r\n\
+    ///      s=-\r\n\
+    ///      m=audio 9 UDP 0\r\n\
+    ///      a=candidate:1 1 UDP 2130706431 10.0.1.1 8998 typ host\r\n\
+    ///      a=candidate:2 1 UDP 1694498815 192.0.2.3 45664 typ srflx\r\n\
+    ///      a=end-of-candidates\r\n"
+    /// );
+    /// ```
+    pub fn add_candidate<T>(&mut self, index: usize, candidate: T) -> error::Result<()>
+    where
+        T: Into<Cow<'a, str>>
+    {
+        let candidate = match candidate.into() {
+            Cow::Borrowed(c) => Cow::Borrowed(c.strip_prefix("candidate:").unwrap_or(c)),
+            Cow::Owned(c) => match c.strip_prefix("candidate:") {
+                Some(c) => Cow::Owned(c.to_string()),
+                None => Cow::Owned(c)
+            }
+        };
+
+        self.media
+            .get_mut(index)
+            .ok_or(SdpError::InvalidValue("media index"))?
+            .attributes
+            .candidates
+            .push(candidate);
+        Ok(())
+    }
+
+    /// mark that no more candidates will be trickled for the media 
+    /// description at the given index.
+    pub fn end_of_candidates(&mut self, index: usize) -> error::Result<()> {
+        self.media
+            .get_mut(index)
+            .ok_or(SdpError::InvalidValue("media index"))?
+            .attributes
+            .end_of_candidates = true;
+        Ok(())
+    }
+
+    /// parse a session description with the limits.
+    ///
+    /// the `TryFrom` implementation parses with the default 
+    /// limits, which are generous for any real description.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::error::SdpError;
+    /// use sdp::{Limits, Sdp};
+    /// use std::convert::*;
+    ///
+    /// let source = "v=0\r\n\
+    ///     o=- 0 0 IN IP4 127.0.0.1\r\n\
+    ///     s=-\r\n\
+    ///     t=0 0\r\n\
+    ///     a=ice-lite\r\n\
+    ///     m=video 9 UDP/TLS/RTP/SAVPF 96\r\n\
+    ///     a=mid:0\r\n\
+    ///     m=video 9 UDP/TLS/RTP/SAVPF 96\r\n\
+    ///     a=mid:1\r\n";
+    ///
+    /// let limits = Limits { max_media: 2, max_attributes: 3 };
+    /// assert_eq!(Sdp::parse_with(source, &limits).unwrap().media.len(), 2);
+    ///
+    /// let limits = Limits { max_media: 1, ..Limits::default() };
+    /// let err = Sdp::parse_with(source, &limits).unwrap_err();
+    /// assert_eq!(err, SdpError::TooMany("media"));
+    ///
+    /// let limits = Limits { max_attributes: 2, ..Limits::default() };
+    /// let err = Sdp::parse_with(source, &limits).unwrap_err();
+    /// assert_eq!(err, SdpError::TooMany("attributes"));
+    ///
+    /// let huge = format!("{}{}", source, "m=video 9 UDP/TLS/RTP/SAVPF 96\r\n".repeat(64));
+    /// assert_eq!(Sdp::try_from(huge.as_str()).unwrap_err(), SdpError::TooMany("media"));
+    ///
+    /// let err = Sdp::try_from("v=0\r\na\r\n").unwrap_err();
+    /// assert_eq!(err, SdpError::InvalidValue("line"));
+    ///
+    /// let err = Sdp::try_from("v=0\r\n€\r\n").unwrap_err();
+    /// assert_eq!(err, SdpError::InvalidValue("line"));
+    ///
+    /// let err = Sdp::try_from("v=0\r\nab=1\r\n").unwrap_err();
+    /// assert_eq!(err, SdpError::InvalidValue("line"));
+    /// ```
+    #[rustfmt::skip]
+    pub fn parse_with(value: &'a str, limits: &Limits) -> error::Result<Self> {
+        let mut sdp = Self::default();
+        let mut attributes = 0;
+        for line in value.lines() {
+            if !line.is_empty() {
+                let key = line.get(..2)
+                    .filter(|k| k.ends_with('='))
+                    .ok_or(SdpError::InvalidValue("line"))?;
+                let data = &line[2..];
+                if let Ok(k) = Key::try_from(key) {
+                    match k {
+                        Key::Media if sdp.media.len() >= limits.max_media => {
+                            return Err(SdpError::TooMany("media"))
+                        },
+                        Key::Attributes if attributes >= limits.max_attributes => {
+                            return Err(SdpError::TooMany("attributes"))
+                        },
+                        Key::Attributes => attributes += 1,
+                        _ => ()
+                    }
+
+                    sdp.handle_line(k, data)?;
+                }   
+            }
+        }
+
+        Ok(sdp)
+    }
+
+    pub fn handle_line(&mut self, key: Key, data: &'a str) -> error::Result<()> {
+        if let Some(media) = self.media.last_mut() {
+            if Self::handle_media_line(media, &key, data)? {
+                return Ok(())
+            }
+        }
+
+        match key {
             Key::Origin => self.origin = Some(Origin::try_from(data)?),
             Key::SessionName => self.session_name = util::placeholder(data),
             Key::SessionInfo => self.session_info = util::placeholder(data),
@@ -176,27 +945,169 @@ impl<'a> Sdp<'a> {
             Key::Timing => self.timing = Some(Timing::try_from(data)?),
             Key::RepeatTimes => self.repeat_times = Some(RepeatTimes::try_from(data)?),
             Key::TimeZones => self.time_zones = Some(TimeZones::try_from(data)?),
+            Key::EncryptionKey => self.encryption_key = Some(EncryptionKey::try_from(data)?),
             Key::Attributes => self.attributes.handle(data)?,
-            Key::Media => self.media = Some(Media::try_from(data)?),
-        })
+            Key::Media => self.media.push(Media::try_from(data)?),
+        }
+
+        Ok(())
+    }
+
+    /// write the session description with the line ending.
+    ///
+    /// the `Display` of the session description uses CRLF.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::{Sdp, LineEnding};
+    /// use std::convert::*;
+    ///
+    /// let sdp = Sdp::try_from(
+    ///     "v=0\n\
+    ///      s=-\n\
+    ///      m=video 9 UDP 96\n\
+    ///      a=rtpmap:96 VP8/90000\n"
+    /// ).unwrap();
+    ///
+    /// let crlf = "v=0\r\ns=-\r\nm=video 9 UDP 96\r\na=rtpmap:96 VP8/90000\r\n";
+    /// assert_eq!(format!("{}", sdp), crlf);
+    /// assert_eq!(format!("{}", sdp.display_with(LineEnding::default())), crlf);
+    ///
+    /// let lf = format!("{}", sdp.display_with(LineEnding::Lf));
+    /// assert_eq!(lf, "v=0\ns=-\nm=video 9 UDP 96\na=rtpmap:96 VP8/90000\n");
+    /// assert!(!lf.contains('\r'));
+    /// ```
+    pub fn display_with<'s>(&'s self, ending: LineEnding) -> SdpDisplay<'s, 'a> {
+        SdpDisplay {
+            sdp: self,
+            ending,
+        }
+    }
+
+    /// lines following an "m=" line belong to that media description,
+    /// returns false if the key is a session level only key.
+    fn handle_media_line(media: &mut Media<'a>, key: &Key, data: &'a str) -> error::Result<bool> {
+        match key {
+            Key::SessionInfo => media.information = util::placeholder(data),
+            Key::Connection => media.connection = Some(Connection::try_from(data)?),
+            Key::Bandwidth => media.bandwidth = Some(Bandwidth::try_from(data)?),
+            Key::EncryptionKey => media.encryption_key = Some(EncryptionKey::try_from(data)?),
+            Key::Attributes => media.attributes.handle(data)?,
+            _ => return Ok(false)
+        }
+
+        Ok(true)
     }
 }
 
 impl<'a> TryFrom<&'a str> for Sdp<'a> {
-    type Error = anyhow::Error;
-    #[rustfmt::skip]
+    type Error = SdpError;
     fn try_from(value: &'a str) -> Result<Self, Self::Error> {
-        let mut sdp = Self::default();
-        for line in value.lines() {
-            if !line.is_empty() {
-                let (key, data) = line.split_at(2);
-                if let Ok(k) = Key::try_from(key) {
-                    sdp.handle_line(k, data)?;
-                }   
+        Self::parse_with(value, &Limits::default())
+    }
+}
+
+impl fmt::Display for Sdp<'_> {
+    /// the session description is written in the order required by
+    /// the specification, every line is terminated by CRLF.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::Sdp;
+    /// use std::convert::*;
+    ///
+    /// let source = "v=0\r\n\
+    ///     o=- 9216395717180620054 2 IN IP4 127.0.0.1\r\n\
+    ///     s=-\r\n\
+    ///     i=meeting\r\n\
+    ///     c=IN IP4 127.0.0.1\r\n\
+    ///     t=0 0\r\n\
+    ///     k=clear:secret\r\n\
+    ///     m=video 9 UDP/TLS/RTP/SAVPF 96\r\n\
+    ///     i=camera\r\n\
+    ///     b=AS:256\r\n\
+    ///     k=prompt\r\n\
+    ///     a=rtpmap:96 VP8/90000\r\n";
+    ///
+    /// let sdp = Sdp::try_from(source).unwrap();
+    /// assert_eq!(sdp.encryption_key.as_ref().unwrap().key, Some("secret"));
+    /// assert_eq!(sdp.media[0].encryption_key.as_ref().unwrap().method, "prompt");
+    /// assert_eq!(sdp.media[0].information, Some("camera"));
+    /// assert_eq!(sdp.session_info, Some("meeting"));
+    /// assert_eq!(format!("{}", sdp), source);
+    /// ```
+    #[rustfmt::skip]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v=0\r\n")?;
+
+        if let Some(origin) = &self.origin {
+            write!(f, "{}{}\r\n", Key::Origin, origin)?;
+        }
+
+        write!(f, "{}{}\r\n", Key::SessionName, self.session_name.unwrap_or("-"))?;
+
+        for (key, value) in [
+            (Key::SessionInfo, self.session_info),
+            (Key::Uri, self.uri),
+            (Key::Email, self.email),
+            (Key::Phone, self.phone)
+        ] {
+            if let Some(value) = value {
+                write!(f, "{}{}\r\n", key, value)?;
             }
         }
 
-        Ok(sdp)
+        if let Some(connection) = &self.connection {
+            write!(f, "{}{}\r\n", Key::Connection, connection)?;
+        }
+
+        if let Some(bandwidth) = &self.bandwidth {
+            write!(f, "{}{}\r\n", Key::Bandwidth, bandwidth)?;
+        }
+
+        if let Some(timing) = &self.timing {
+            write!(f, "{}{}\r\n", Key::Timing, timing)?;
+        }
+
+        if let Some(repeat_times) = &self.repeat_times {
+            write!(f, "{}{}\r\n", Key::RepeatTimes, repeat_times)?;
+        }
+
+        if let Some(time_zones) = &self.time_zones {
+            write!(f, "{}{}\r\n", Key::TimeZones, time_zones)?;
+        }
+
+        if let Some(encryption_key) = &self.encryption_key {
+            write!(f, "{}{}\r\n", Key::EncryptionKey, encryption_key)?;
+        }
+
+        write!(f, "{}", self.attributes)?;
+
+        for media in &self.media {
+            write!(f, "{}{}\r\n", Key::Media, media)?;
+
+            if let Some(information) = media.information {
+                write!(f, "{}{}\r\n", Key::SessionInfo, information)?;
+            }
+
+            if let Some(connection) = &media.connection {
+                write!(f, "{}{}\r\n", Key::Connection, connection)?;
+            }
+
+            if let Some(bandwidth) = &media.bandwidth {
+                write!(f, "{}{}\r\n", Key::Bandwidth, bandwidth)?;
+            }
+
+            if let Some(encryption_key) = &media.encryption_key {
+                write!(f, "{}{}\r\n", Key::EncryptionKey, encryption_key)?;
+            }
+
+            write!(f, "{}", media.attributes)?;
+        }
+
+        Ok(())
     }
 }
 
@@ -214,7 +1125,7 @@ impl fmt::Display for NetKind {
 }
 
 impl<'a> TryFrom<&'a str> for NetKind {
-    type Error = anyhow::Error;
+    type Error = SdpError;
     /// # Unit Test
     ///
     /// ```
@@ -225,8 +1136,10 @@ impl<'a> TryFrom<&'a str> for NetKind {
     /// assert_eq!(NetKind::try_from("in").is_ok(), false);
     /// ```
     fn try_from(value: &'a str) -> Result<Self, Self::Error> {
-        ensure!(value == "IN", "invalid nettype!");
-        Ok(Self::IN)
+        match value {
+            "IN" => Ok(Self::IN),
+            _ => Err(SdpError::InvalidNetKind)
+        }
     }
 }
 
@@ -248,7 +1161,7 @@ impl fmt::Display for AddrKind {
 }
 
 impl<'a> TryFrom<&'a str> for AddrKind {
-    type Error = anyhow::Error;
+    type Error = SdpError;
     /// # Unit Test
     ///
     /// ```
@@ -263,7 +1176,7 @@ impl<'a> TryFrom<&'a str> for AddrKind {
         match value {
             "IP4" => Ok(Self::IP4),
             "IP6" => Ok(Self::IP6),
-            _ => Err(anyhow!("invalid addrtype!"))
+            _ => Err(SdpError::InvalidAddrKind)
         }
     }
 }
@@ -292,6 +1205,7 @@ impl fmt::Display for Key {
             Self::Timing =>          "t=",
             Self::RepeatTimes =>     "r=",
             Self::TimeZones =>       "z=",
+            Self::EncryptionKey =>   "k=",
             Self::Attributes =>      "a=",
             Self::Media =>           "m=",
         })
@@ -299,7 +1213,7 @@ impl fmt::Display for Key {
 }
 
 impl<'a> TryFrom<&'a str> for Key {
-    type Error = anyhow::Error;
+    type Error = SdpError;
     /// # Unit Test
     ///
     /// ```
@@ -329,9 +1243,10 @@ impl<'a> TryFrom<&'a str> for Key {
             "t=" => Ok(Self::Timing),
             "r=" => Ok(Self::RepeatTimes),
             "z=" => Ok(Self::TimeZones),
+            "k=" => Ok(Self::EncryptionKey),
             "a=" => Ok(Self::Attributes),
             "m=" => Ok(Self::Media),
-            _ => Err(anyhow!("invalid sdp key!"))
+            _ => Err(SdpError::InvalidValue("sdp key"))
         }
     }
 }
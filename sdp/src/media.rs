@@ -1,10 +1,27 @@
-use anyhow::{
-    ensure,
-    anyhow
+use super::{
+    attributes::{
+        Attributes,
+        SsrcGroup,
+        ExtMap,
+        Direction,
+        PayloadMatch,
+        RtpValue,
+        RtcpFb,
+        Range,
+        Ssrc,
+        Msid
+    },
+    encryption_key::EncryptionKey,
+    connection::Connection,
+    bandwidth::Bandwidth,
+    error::SdpError,
+    Sdp
 };
 
 use std::{
+    collections::HashMap,
     convert::TryFrom,
+    borrow::Cow,
     fmt
 };
 
@@ -12,7 +29,7 @@ use std::{
 /// 
 /// <media> is the media type.  Currently defined media are "audio",
 /// "video", "text", "application", and "message"
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Encoding {
     Audio,
     Video,
@@ -48,7 +65,7 @@ pub enum Encoding {
 /// Modulation (PCM) audio and RTP PCM audio; another might be TCP/RTP
 /// PCM audio.  In addition, relays and monitoring tools that are
 /// transport-protocol-specific but format-independent are possible.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Proto {
     Udp,
     Tls,
@@ -119,6 +136,20 @@ pub struct Port {
     pub count: Option<u8>
 }
 
+/// media format.
+///
+/// a payload type of the "m=" line joined with the attributes
+/// that describe it, the "a=rtpmap:", the "a=fmtp:" parameters
+/// and the "a=rtcp-fb:" feedbacks, including the feedbacks of
+/// the wildcard payload type "*".
+#[derive(Debug)]
+pub struct Format<'m, 'a> {
+    pub pt: u8,
+    pub rtpmap: Option<&'m RtpValue>,
+    pub fmtp: Option<&'m HashMap<&'a str, &'a str>>,
+    pub rtcp_fb: Vec<&'m RtcpFb<'a>>,
+}
+
 /// Media Descriptions ("m=")
 ///
 /// m=<media> <port> <proto> <fmt> ...
@@ -128,7 +159,7 @@ pub struct Port {
 /// either the next "m=" field or by the end of the session description.
 /// A media field has several sub-fields:
 #[derive(Debug)]
-pub struct Media {
+pub struct Media<'a> {
     pub encoding: Encoding,
     pub port: Port,
     pub protos: Vec<Proto>,
@@ -155,10 +186,381 @@ pub struct Media {
     /// For media using other transport protocols, the <fmt> field is
     /// protocol specific.  Rules for interpretation of the <fmt> sub-
     /// field MUST be defined when registering new protocols.
-    pub fmts: Vec<u8>
+    pub fmts: Vec<u8>,
+    /// media title ("i="), the session information 
+    /// field used under the media description.
+    pub information: Option<&'a str>,
+    /// media level connection information ("c=").
+    pub connection: Option<Connection>,
+    /// media level bandwidth ("b=").
+    pub bandwidth: Option<Bandwidth>,
+    /// media level encryption key ("k="), obsolete.
+    pub encryption_key: Option<EncryptionKey<'a>>,
+    /// media level attributes ("a=").
+    pub attributes: Attributes<'a>
+}
+
+impl<'a> Media<'a> {
+    /// effective connection of the media.
+    ///
+    /// If a session has no meaningful connection data at the media
+    /// level, the session level "c=" line applies to the media
+    /// description, a media level "c=" line overrides it.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::Sdp;
+    /// use std::convert::*;
+    ///
+    /// let sdp = Sdp::try_from(
+    ///     "v=0\r\n\
+    ///      c=IN IP4 192.168.1.1\r\n\
+    ///      m=audio 9 UDP 0\r\n\
+    ///      c=IN IP4 10.0.0.1\r\n\
+    ///      m=video 9 UDP 96\r\n"
+    /// ).unwrap();
+    ///
+    /// let audio = sdp.media[0].effective_connection(&sdp).unwrap();
+    /// let video = sdp.media[1].effective_connection(&sdp).unwrap();
+    ///
+    /// assert_eq!(audio.connection_address.ip.to_string(), "10.0.0.1");
+    /// assert_eq!(video.connection_address.ip.to_string(), "192.168.1.1");
+    /// ```
+    pub fn effective_connection<'s>(&'s self, sdp: &'s Sdp) -> Option<&'s Connection> {
+        self.connection
+            .as_ref()
+            .or(sdp.connection.as_ref())
+    }
+
+    /// rtcp feedback capabilities of the media.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::Sdp;
+    /// use sdp::attributes::PayloadMatch;
+    /// use std::convert::*;
+    ///
+    /// let sdp = Sdp::try_from(
+    ///     "v=0\r\n\
+    ///      m=video 9 UDP 96\r\n\
+    ///      a=rtcp-fb:96 nack pli\r\n\
+    ///      a=rtcp-fb:* goog-remb\r\n"
+    /// ).unwrap();
+    ///
+    /// let fbs = sdp.media[0].rtcp_fb();
+    /// assert_eq!(fbs.len(), 2);
+    /// assert_eq!(fbs[0].payload, PayloadMatch::Payload(96));
+    /// assert_eq!(fbs[0].feedback_type, "nack");
+    /// assert_eq!(fbs[0].param, Some("pli"));
+    /// assert_eq!(fbs[1].payload, PayloadMatch::Any);
+    /// assert_eq!(fbs[1].feedback_type, "goog-remb");
+    /// ```
+    pub fn rtcp_fb(&self) -> &[RtcpFb<'a>] {
+        &self.attributes.rtcp_fb
+    }
+
+    /// formats of the media, in the order of the "m=" line.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::Sdp;
+    /// use sdp::attributes::Codec;
+    /// use std::convert::*;
+    ///
+    /// let sdp = Sdp::try_from(
+    ///     "v=0\r\n\
+    ///      m=video 9 UDP 96 102\r\n\
+    ///      a=rtpmap:96 VP8/90000\r\n\
+    ///      a=rtpmap:102 H264/90000\r\n\
+    ///      a=fmtp:96 max-fr=30\r\n\
+    ///      a=fmtp:102 packetization-mode=1\r\n\
+    ///      a=rtcp-fb:96 nack pli\r\n\
+    ///      a=rtcp-fb:102 ccm fir\r\n\
+    ///      a=rtcp-fb:* goog-remb\r\n"
+    /// ).unwrap();
+    ///
+    /// let formats = sdp.media[0].formats();
+    /// assert_eq!(formats.len(), 2);
+    ///
+    /// assert_eq!(formats[0].pt, 96);
+    /// assert_eq!(formats[0].rtpmap.unwrap().codec, Codec::Vp8);
+    /// assert_eq!(formats[0].fmtp.unwrap().get("max-fr"), Some(&"30"));
+    /// assert_eq!(formats[0].rtcp_fb.len(), 2);
+    /// assert_eq!(formats[0].rtcp_fb[0].feedback_type, "nack");
+    /// assert_eq!(formats[0].rtcp_fb[1].feedback_type, "goog-remb");
+    ///
+    /// assert_eq!(formats[1].pt, 102);
+    /// assert_eq!(formats[1].rtpmap.unwrap().frequency, Some(90000));
+    /// assert_eq!(formats[1].fmtp.unwrap().get("packetization-mode"), Some(&"1"));
+    /// assert_eq!(formats[1].rtcp_fb.len(), 2);
+    /// assert_eq!(formats[1].rtcp_fb[0].param, Some("fir"));
+    ///
+    /// let sdp = Sdp::try_from("v=0\r\nm=audio 9 UDP 0\r\n").unwrap();
+    /// let formats = sdp.media[0].formats();
+    /// assert!(formats[0].rtpmap.is_none());
+    /// assert!(formats[0].fmtp.is_none());
+    /// assert!(formats[0].rtcp_fb.is_empty());
+    /// ```
+    pub fn formats(&self) -> Vec<Format<'_, 'a>> {
+        self.fmts
+            .iter()
+            .map(|pt| Format {
+                rtpmap: self.attributes.rtpmap.get(pt),
+                fmtp: self.attributes.fmtp.get(pt),
+                rtcp_fb: self.attributes.rtcp_fb
+                    .iter()
+                    .filter(|fb| match fb.payload {
+                        PayloadMatch::Payload(p) => p == *pt,
+                        PayloadMatch::Any => true
+                    })
+                    .collect(),
+                pt: *pt,
+            })
+            .collect()
+    }
+
+    /// rtp header extensions of the media.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::Sdp;
+    /// use std::convert::*;
+    ///
+    /// let sdp = Sdp::try_from(
+    ///     "v=0\r\n\
+    ///      m=video 9 UDP 96\r\n\
+    ///      a=extmap:3 http://www.webrtc.org/experiments/rtp-hdrext/abs-send-time\r\n\
+    ///      a=extmap:5 http://www.ietf.org/id/draft-holmer-rmcat-transport-wide-cc-extensions-01\r\n"
+    /// ).unwrap();
+    ///
+    /// let twcc = sdp.media[0]
+    ///     .extmaps()
+    ///     .iter()
+    ///     .find(|e| e.uri.ends_with("transport-wide-cc-extensions-01"))
+    ///     .unwrap();
+    /// assert_eq!(twcc.id, 5);
+    /// ```
+    pub fn extmaps(&self) -> &[ExtMap<'a>] {
+        &self.attributes.extmap
+    }
+
+    /// sources of the media, grouped by ssrc-id.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::Sdp;
+    /// use std::convert::*;
+    ///
+    /// let sdp = Sdp::try_from(
+    ///     "v=0\r\n\
+    ///      m=video 9 UDP 96 97\r\n\
+    ///      a=msid:stream track\r\n\
+    ///      a=ssrc-group:FID 1234 5678\r\n\
+    ///      a=ssrc:1234 cname:panda\r\n\
+    ///      a=ssrc:1234 msid:stream track\r\n\
+    ///      a=ssrc:5678 cname:panda\r\n"
+    /// ).unwrap();
+    ///
+    /// let media = &sdp.media[0];
+    /// assert_eq!(media.ssrcs().len(), 2);
+    /// assert_eq!(media.ssrcs()[0].id, 1234);
+    /// assert_eq!(media.ssrcs()[0].cname(), Some("panda"));
+    /// assert_eq!(media.ssrcs()[0].get("msid"), Some("stream track"));
+    ///
+    /// let fid = &media.ssrc_groups()[0];
+    /// assert_eq!(fid.semantics, "FID");
+    /// assert_eq!(fid.ssrcs, vec![1234, 5678]);
+    ///
+    /// let msid = media.msid().unwrap();
+    /// assert_eq!(msid.stream, "stream");
+    /// assert_eq!(msid.track, Some("track"));
+    /// ```
+    pub fn ssrcs(&self) -> &[Ssrc<'a>] {
+        &self.attributes.ssrcs
+    }
+
+    /// source groups of the media.
+    pub fn ssrc_groups(&self) -> &[SsrcGroup<'a>] {
+        &self.attributes.ssrc_groups
+    }
+
+    /// media stream identification of the media.
+    pub fn msid(&self) -> Option<&Msid<'a>> {
+        self.attributes.msid.as_ref()
+    }
+
+    /// packet time (millisecond) of the media.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::Sdp;
+    /// use std::convert::*;
+    ///
+    /// let sdp = Sdp::try_from(
+    ///     "v=0\r\n\
+    ///      m=audio 9 UDP/TLS/RTP/SAVPF 111 0\r\n\
+    ///      a=rtpmap:111 opus/48000/2\r\n\
+    ///      a=ptime:20\r\n\
+    ///      a=maxptime:120\r\n\
+    ///      m=audio 9 UDP 8\r\n"
+    /// ).unwrap();
+    ///
+    /// assert_eq!(sdp.media[0].ptime(), Some(20));
+    /// assert_eq!(sdp.media[0].maxptime(), Some(120));
+    /// assert_eq!(sdp.media[1].ptime(), None);
+    /// assert_eq!(sdp.media[1].maxptime(), None);
+    /// ```
+    pub fn ptime(&self) -> Option<u32> {
+        self.attributes.ptime.and_then(|t| u32::try_from(t).ok())
+    }
+
+    /// maximum packet time (millisecond) of the media.
+    pub fn maxptime(&self) -> Option<u32> {
+        self.attributes.maxptime.and_then(|t| u32::try_from(t).ok())
+    }
+
+    /// whether the media description is rejected.
+    ///
+    /// a media description with the port set to zero is a 
+    /// rejected or disabled stream, no media is sent for it.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::Sdp;
+    /// use std::convert::*;
+    ///
+    /// let sdp = Sdp::try_from(
+    ///     "v=0\r\n\
+    ///      m=audio 0 UDP 0\r\n\
+    ///      m=video 9 UDP 96\r\n"
+    /// ).unwrap();
+    ///
+    /// assert!(sdp.media[0].is_rejected());
+    /// assert!(!sdp.media[1].is_rejected());
+    /// ```
+    pub fn is_rejected(&self) -> bool {
+        self.port.num == 0
+    }
+
+    /// character set of the media text fields.
+    ///
+    /// a media level "a=charset:" attribute overrides the 
+    /// session level attribute.
+    pub fn charset(&self, sdp: &Sdp<'a>) -> Option<&'a str> {
+        self.attributes
+            .charset
+            .or_else(|| sdp.charset())
+    }
+
+    /// media direction of the media.
+    ///
+    /// a media level direction attribute overrides the 
+    /// session level attribute.
+    pub fn direction(&self, sdp: &Sdp<'a>) -> Option<Direction> {
+        self.attributes
+            .direction()
+            .or_else(|| sdp.attributes.direction())
+    }
+
+    /// identification tag of the media.
+    pub fn mid(&self) -> Option<&'a str> {
+        self.attributes.mid
+    }
+
+    /// RTSP control url of the media.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::attributes::*;
+    /// use sdp::Sdp;
+    /// use std::convert::*;
+    ///
+    /// let sdp = Sdp::try_from(
+    ///     "v=0\r\n\
+    ///      a=control:rtsp://example.com/movie\r\n\
+    ///      a=range:npt=0-34.5\r\n\
+    ///      m=video 0 RTP/AVP 96\r\n\
+    ///      a=control:trackID=1\r\n\
+    ///      a=range:npt=now-\r\n\
+    ///      m=audio 0 RTP/AVP 0\r\n"
+    /// ).unwrap();
+    ///
+    /// assert_eq!(sdp.control(), Some("rtsp://example.com/movie"));
+    /// assert_eq!(sdp.media[0].control(), Some("trackID=1"));
+    /// assert_eq!(sdp.media[1].control(), None);
+    ///
+    /// let range = sdp.range().unwrap();
+    /// assert_eq!(range.start, Some(NptTime::Seconds(0.0)));
+    /// assert_eq!(range.stop, Some(NptTime::Seconds(34.5)));
+    ///
+    /// let range = sdp.media[0].range(&sdp).unwrap();
+    /// assert_eq!(range.start, Some(NptTime::Now));
+    /// assert_eq!(range.stop, None);
+    /// assert_eq!(sdp.media[1].range(&sdp), sdp.range());
+    ///
+    /// // a clock range is kept as it is.
+    /// let sdp = Sdp::try_from(
+    ///     "v=0\r\n\
+    ///      m=video 0 RTP/AVP 96\r\n\
+    ///      a=range:clock=19961108T142300Z-19961108T143520Z\r\n"
+    /// ).unwrap();
+    ///
+    /// assert_eq!(sdp.media[0].range(&sdp), None);
+    /// assert!(format!("{}", sdp).contains("a=range:clock=19961108T142300Z-19961108T143520Z\r\n"));
+    /// ```
+    pub fn control(&self) -> Option<&'a str> {
+        self.attributes.control
+    }
+
+    /// time range of the media.
+    ///
+    /// a media level "a=range:" attribute overrides the 
+    /// session level attribute.
+    pub fn range(&self, sdp: &Sdp<'a>) -> Option<Range> {
+        self.attributes
+            .range
+            .or_else(|| sdp.range())
+    }
+
+    /// ice candidates of the media, including trickled candidates.
+    pub fn candidates(&self) -> &[Cow<'a, str>] {
+        &self.attributes.candidates
+    }
+
+    /// whether all candidates of the media have been gathered.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::Sdp;
+    /// use std::convert::*;
+    ///
+    /// let sdp = Sdp::try_from(
+    ///     "v=0\r\n\
+    ///      m=audio 9 UDP 0\r\n\
+    ///      a=candidate:1 1 UDP 2130706431 10.0.1.1 8998 typ host\r\n\
+    ///      a=end-of-candidates\r\n\
+    ///      m=video 9 UDP 96\r\n"
+    /// ).unwrap();
+    ///
+    /// assert_eq!(sdp.media[0].candidates().len(), 1);
+    /// assert!(sdp.media[0].end_of_candidates());
+    /// assert!(!sdp.media[1].end_of_candidates());
+    /// ```
+    pub fn end_of_candidates(&self) -> bool {
+        self.attributes.end_of_candidates
+    }
 }
 
-impl fmt::Display for Media {
+impl fmt::Display for Media<'_> {
     /// # Unit Test
     ///
     /// ```
@@ -180,7 +582,12 @@ impl fmt::Display for Media {
     ///     fmts: vec![
     ///         96, 97, 98, 99, 100, 101,
     ///         102, 121, 127, 120, 125
-    ///     ]
+    ///     ],
+    ///     information: None,
+    ///     connection: None,
+    ///     bandwidth: None,
+    ///     encryption_key: None,
+    ///     attributes: Default::default()
     /// };
     ///
     /// assert_eq!(
@@ -222,8 +629,8 @@ impl fmt::Display for Media {
     }
 }
 
-impl<'a> TryFrom<&'a str> for Media {
-    type Error = anyhow::Error;
+impl<'a> TryFrom<&'a str> for Media<'a> {
+    type Error = SdpError;
     /// # Unit Test
     ///
     /// ```
@@ -250,24 +657,32 @@ impl<'a> TryFrom<&'a str> for Media {
     /// );
     /// ```
     fn try_from(value: &'a str) -> Result<Self, Self::Error> {
-        let values = value.split(' ').collect::<Vec<&str>>();
-        ensure!(values.len() >= 3, "invalid media!");
+        let mut values = value.split(' ');
+        let mut next = || values
+            .next()
+            .ok_or(SdpError::MissingField("media"));
+        let (encoding, port, proto) = (next()?, next()?, next()?);
 
         let mut protos = Vec::with_capacity(5);
-        for p in values[2].split('/') {
+        for p in proto.split('/') {
             protos.push(Proto::try_from(p)?);
         }
 
         let mut fmts = Vec::with_capacity(30);
-        for f in values[3..].iter() {
+        for f in values {
             fmts.push(f.parse()?);
         }
 
         Ok(Self {
-            encoding: Encoding::try_from(values[0])?,
-            port: Port::try_from(values[1])?,
+            encoding: Encoding::try_from(encoding)?,
+            port: Port::try_from(port)?,
             protos,
-            fmts
+            fmts,
+            information: None,
+            connection: None,
+            bandwidth: None,
+            encryption_key: None,
+            attributes: Attributes::default()
         })
     }
 }
@@ -304,7 +719,7 @@ impl fmt::Display for Port {
 }
 
 impl<'a> TryFrom<&'a str> for Port {
-    type Error = anyhow::Error;
+    type Error = SdpError;
     /// # Unit Test
     ///
     /// ```
@@ -321,11 +736,13 @@ impl<'a> TryFrom<&'a str> for Port {
     /// assert_eq!(port.count, Some(2));
     /// ```
     fn try_from(value: &'a str) -> Result<Self, Self::Error> {
-        let values = value.split('/').collect::<Vec<&str>>();
-        ensure!(!values.is_empty(), "invalid media port!");
+        let mut values = value.split('/');
+        let num = values
+            .next()
+            .ok_or(SdpError::MissingField("media port"))?;
         Ok(Self {
-            num: values[0].parse()?,
-            count: match values.get(1) {
+            num: num.parse()?,
+            count: match values.next() {
                 Some(c) => Some(c.parse()?),
                 None => None
             }
@@ -357,7 +774,7 @@ impl fmt::Display for Encoding {
 }
 
 impl<'a> TryFrom<&'a str> for Encoding {
-    type Error = anyhow::Error;
+    type Error = SdpError;
     /// # Unit Test
     ///
     /// ```
@@ -378,7 +795,7 @@ impl<'a> TryFrom<&'a str> for Encoding {
             "video" =>          Ok(Self::Video),
             "message" =>        Ok(Self::Message),
             "application" =>    Ok(Self::Application),
-            _ => Err(anyhow!("invalid media type!"))
+            _ => Err(SdpError::InvalidValue("media type"))
         }
     }
 }
@@ -408,7 +825,7 @@ impl fmt::Display for Proto {
 }
 
 impl<'a> TryFrom<&'a str> for Proto {
-    type Error = anyhow::Error;
+    type Error = SdpError;
     /// # Unit Test
     ///
     /// ```
@@ -430,7 +847,7 @@ impl<'a> TryFrom<&'a str> for Proto {
             "AVP" =>    Ok(Self::Avp),
             "SAVP" =>   Ok(Self::Savp),
             "SAVPF" =>  Ok(Self::Savpf),
-            _ => Err(anyhow!("invalid media proto!"))
+            _ => Err(SdpError::InvalidValue("media proto"))
         }
     }
 }
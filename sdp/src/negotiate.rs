@@ -0,0 +1,210 @@
+use super::{
+    attributes::{
+        Codec,
+        RtpValue
+    },
+    media::Media,
+    Sdp
+};
+
+/// local codec capability.
+///
+/// the codec and the clock rate the local side can receive and
+/// send, the frequency matches any clock rate when it is none.
+/// the parameters are the format parameters the offer must have
+/// with the same value, such as "packetization-mode" of H264.
+#[derive(Debug, Clone)]
+pub struct CodecCapability<'a> {
+    pub codec: Codec,
+    pub frequency: Option<u64>,
+    pub parameters: Vec<(&'a str, &'a str)>,
+}
+
+/// the accepted payload types of a media description.
+///
+/// the payload types are the numbers of the offer, in the order
+/// of preference of the offer, an empty list means that no codec
+/// is supported and the media description should be rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectedMedia<'a> {
+    pub index: usize,
+    pub mid: Option<&'a str>,
+    pub fmts: Vec<u8>,
+}
+
+impl<'a> CodecCapability<'a> {
+    /// create a capability without format parameters.
+    pub fn new(codec: Codec, frequency: Option<u64>) -> Self {
+        Self {
+            parameters: Vec::new(),
+            frequency,
+            codec,
+        }
+    }
+
+    /// whether the offered payload type matches the capability.
+    #[rustfmt::skip]
+    fn matches(&self, media: &Media, pt: u8) -> bool {
+        let rtp = match rtpmap(media, pt) {
+            Some(r) => r,
+            None => return false
+        };
+
+        if rtp.codec != self.codec {
+            return false
+        }
+
+        if self.frequency.is_some() && rtp.frequency != self.frequency {
+            return false
+        }
+
+        let fmtp = media.attributes.fmtp.get(&pt);
+        self.parameters.iter().all(|(k, v)| {
+            fmtp.and_then(|p| p.get(k)) == Some(v)
+        })
+    }
+}
+
+/// the rtpmap of the payload type, a static payload 
+/// type without rtpmap uses the profile mapping.
+fn rtpmap(media: &Media, pt: u8) -> Option<RtpValue> {
+    media.attributes
+        .rtpmap
+        .get(&pt)
+        .copied()
+        .or_else(|| RtpValue::from_static(pt))
+}
+
+/// whether the codec is a repair or redundancy format.
+fn is_repair(codec: Codec) -> bool {
+    matches!(codec, Codec::Rtx | Codec::Red | Codec::Ulpfec)
+}
+
+/// intersect the codecs of the offer with the local codecs.
+///
+/// every media description of the offer is selected, in order.
+/// a media codec is accepted if a local capability matches it,
+/// then a retransmission payload type is accepted if the local
+/// side supports rtx and its associated payload type ("apt") is
+/// accepted, red and ulpfec are accepted if the local side
+/// supports them and a media codec is accepted, see
+/// [RFC3264](https://datatracker.ietf.org/doc/html/rfc3264#section-6.1).
+/// a static payload type without rtpmap, such as PCMU (0) 
+/// or PCMA (8), is matched by its profile mapping.
+///
+/// the result is the input of `SdpBuilder::accept` and
+/// `SdpBuilder::reject`.
+///
+/// # Unit Test
+///
+/// ```
+/// use sdp::attributes::Codec;
+/// use sdp::builder::SdpBuilder;
+/// use sdp::negotiate::*;
+/// use sdp::Sdp;
+/// use std::convert::*;
+///
+/// let offer = Sdp::try_from(
+///     "v=0\r\n\
+///      m=video 9 UDP/TLS/RTP/SAVPF 96 97 102 103\r\n\
+///      a=mid:0\r\n\
+///      a=rtpmap:96 VP8/90000\r\n\
+///      a=rtpmap:97 rtx/90000\r\n\
+///      a=fmtp:97 apt=96\r\n\
+///      a=rtpmap:102 H264/90000\r\n\
+///      a=fmtp:102 packetization-mode=1;profile-level-id=42e01f\r\n\
+///      a=rtpmap:103 rtx/90000\r\n\
+///      a=fmtp:103 apt=102\r\n\
+///      m=video 9 UDP/TLS/RTP/SAVPF 96\r\n\
+///      a=mid:1\r\n\
+///      a=rtpmap:96 VP8/90000\r\n"
+/// ).unwrap();
+///
+/// let mut h264 = CodecCapability::new(Codec::H264, Some(90000));
+/// h264.parameters.push(("packetization-mode", "1"));
+/// let local = [h264, CodecCapability::new(Codec::Rtx, None)];
+///
+/// let selected = negotiate_codecs(&offer, &local);
+/// assert_eq!(selected, vec![
+///     SelectedMedia { index: 0, mid: Some("0"), fmts: vec![102, 103] },
+///     SelectedMedia { index: 1, mid: Some("1"), fmts: vec![] },
+/// ]);
+///
+/// // the answer keeps the parameters of the accepted payload types.
+/// let mut builder = SdpBuilder::answer_to(&offer);
+/// for media in &selected {
+///     match media.fmts.is_empty() {
+///         true => builder.reject(media.index).unwrap(),
+///         false => builder.accept(media.index, &media.fmts).unwrap()
+///     };
+/// }
+///
+/// assert_eq!(
+///     format!("{}", builder.build()),
+///     "v=0\r\n\
+///      s=-\r\n\
+///      m=video 9 UDP/TLS/RTP/SAVPF 102 103\r\n\
+///      a=mid:0\r\n\
+///      a=rtpmap:102 H264/90000\r\n\
+///      a=rtpmap:103 rtx/90000\r\n\
+///      a=fmtp:102 packetization-mode=1;profile-level-id=42e01f\r\n\
+///      a=fmtp:103 apt=102\r\n\
+///      m=video 0 UDP/TLS/RTP/SAVPF 96\r\n\
+///      a=mid:1\r\n"
+/// );
+///
+/// let mut h264 = CodecCapability::new(Codec::H264, Some(90000));
+/// h264.parameters.push(("packetization-mode", "0"));
+/// assert!(negotiate_codecs(&offer, &[h264])[0].fmts.is_empty());
+///
+/// let offer = Sdp::try_from(
+///     "v=0\r\n\
+///      m=audio 9 UDP/TLS/RTP/SAVPF 111 0 8 126\r\n\
+///      a=rtpmap:111 opus/48000/2\r\n\
+///      a=rtpmap:126 telephone-event/8000\r\n"
+/// ).unwrap();
+///
+/// let local = [
+///     CodecCapability::new(Codec::Pcma, Some(8000)),
+///     CodecCapability::new(Codec::Opus, Some(48000)),
+/// ];
+///
+/// assert_eq!(negotiate_codecs(&offer, &local)[0].fmts, vec![111, 8]);
+/// ```
+#[rustfmt::skip]
+pub fn negotiate_codecs<'a>(offer: &Sdp<'a>, local: &[CodecCapability]) -> Vec<SelectedMedia<'a>> {
+    let supports = |codec: Codec| local.iter().any(|c| c.codec == codec);
+    offer.media
+        .iter()
+        .enumerate()
+        .map(|(index, media)| {
+            let primary = media.fmts
+                .iter()
+                .copied()
+                .filter(|pt| rtpmap(media, *pt)
+                    .map(|r| !is_repair(r.codec))
+                    .unwrap_or(false))
+                .filter(|pt| local.iter().any(|c| c.matches(media, *pt)))
+                .collect::<Vec<u8>>();
+            let fmts = media.fmts
+                .iter()
+                .copied()
+                .filter(|pt| match rtpmap(media, *pt).map(|r| r.codec) {
+                    Some(Codec::Rtx) => supports(Codec::Rtx) && media.attributes.fmtp
+                        .get(pt)
+                        .and_then(|p| p.get("apt"))
+                        .and_then(|apt| apt.parse::<u8>().ok())
+                        .map(|apt| primary.contains(&apt))
+                        .unwrap_or(false),
+                    Some(c) if is_repair(c) => supports(c) && !primary.is_empty(),
+                    _ => primary.contains(pt)
+                })
+                .collect();
+            SelectedMedia {
+                mid: media.mid(),
+                index,
+                fmts,
+            }
+        })
+        .collect()
+}
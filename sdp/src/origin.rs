@@ -1,6 +1,6 @@
 use std::net::IpAddr;
-use anyhow::ensure;
 use super::{
+    error::SdpError,
     NetKind,
     AddrKind,
     util::placeholder
@@ -34,7 +34,7 @@ pub struct Origin<'a> {
     /// increased when a modification is made to the session description.
     /// Again, as with <sess-id> it is RECOMMENDED that a timestamp be
     /// used.
-    pub sess_version: u8,
+    pub sess_version: u64,
     /// <nettype>  is a text string giving the type of network.  Initially,
     /// "IN" is defined to have the meaning "Internet".
     pub nettype: NetKind,
@@ -54,6 +54,68 @@ pub struct Origin<'a> {
     pub unicast_address: IpAddr,
 }
 
+impl<'a> Origin<'a> {
+    /// whether the two origins describe the same session.
+    ///
+    /// the tuple of <username>, <sess-id>, <nettype>, <addrtype>, 
+    /// and <unicast-address> identifies the session, the 
+    /// <sess-version> is not part of the identity.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::origin::*;
+    /// use std::convert::*;
+    ///
+    /// let offer = Origin::try_from("- 9216395717180620054 2 IN IP4 127.0.0.1").unwrap();
+    /// let update = Origin::try_from("- 9216395717180620054 3 IN IP4 127.0.0.1").unwrap();
+    /// let other = Origin::try_from("- 1145141919810 2 IN IP4 127.0.0.1").unwrap();
+    /// let panda = Origin::try_from("panda 9216395717180620054 2 IN IP4 127.0.0.1").unwrap();
+    ///
+    /// assert!(offer.same_session(&update));
+    /// assert!(!offer.same_session(&other));
+    /// assert!(!offer.same_session(&panda));
+    /// ```
+    pub fn same_session(&self, other: &Self) -> bool {
+        self.username == other.username &&
+        self.sess_id == other.sess_id &&
+        self.nettype == other.nettype &&
+        self.addrtype == other.addrtype &&
+        self.unicast_address == other.unicast_address
+    }
+
+    /// whether the origin is a newer version of the same session.
+    ///
+    /// the <sess-version> is increased when a modification is 
+    /// made to the session description, an origin of a different 
+    /// session is never newer.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use sdp::origin::*;
+    /// use std::convert::*;
+    ///
+    /// let offer = Origin::try_from("- 9216395717180620054 2 IN IP4 127.0.0.1").unwrap();
+    /// let update = Origin::try_from("- 9216395717180620054 3 IN IP4 127.0.0.1").unwrap();
+    /// let other = Origin::try_from("- 1145141919810 3 IN IP4 127.0.0.1").unwrap();
+    ///
+    /// assert!(update.is_newer_than(&offer));
+    /// assert!(!offer.is_newer_than(&update));
+    /// assert!(!offer.is_newer_than(&offer));
+    /// assert!(!other.is_newer_than(&offer));
+    ///
+    /// // an ntp timestamp is a common version.
+    /// let ntp = Origin::try_from("- 9216395717180620054 3849125393 IN IP4 127.0.0.1").unwrap();
+    /// assert_eq!(ntp.sess_version, 3849125393);
+    /// assert!(ntp.is_newer_than(&update));
+    /// ```
+    pub fn is_newer_than(&self, other: &Self) -> bool {
+        self.same_session(other) && 
+        self.sess_version > other.sess_version
+    }
+}
+
 impl<'a> fmt::Display for Origin<'a> {
     /// # Unit Test
     ///
@@ -88,7 +150,7 @@ impl<'a> fmt::Display for Origin<'a> {
 }
 
 impl<'a> TryFrom<&'a str> for Origin<'a> {
-    type Error = anyhow::Error;
+    type Error = SdpError;
     /// # Unit Test
     ///
     /// ```
@@ -109,15 +171,23 @@ impl<'a> TryFrom<&'a str> for Origin<'a> {
     /// assert_eq!(instance.unicast_address, addr);
     /// ```
     fn try_from(value: &'a str) -> Result<Self, Self::Error> {
-        let values = value.split(' ').collect::<Vec<&str>>();
-        ensure!(values.len() == 6, "invalid origin!");
+        let mut values = value.split(' ');
+        let mut next = || values
+            .next()
+            .ok_or(SdpError::MissingField("origin"));
+        let (username, sess_id, sess_version) = (next()?, next()?, next()?);
+        let (nettype, addrtype, unicast_address) = (next()?, next()?, next()?);
+        if next().is_ok() {
+            return Err(SdpError::MissingField("origin"))
+        }
+
         Ok(Self {
-            sess_id: values[1],
-            sess_version: values[2].parse()?,
-            unicast_address: values[5].parse()?,
-            nettype: NetKind::try_from(values[3])?,
-            addrtype: AddrKind::try_from(values[4])?,
-            username: placeholder(values[0]),
+            sess_id,
+            sess_version: sess_version.parse()?,
+            unicast_address: unicast_address.parse()?,
+            nettype: NetKind::try_from(nettype)?,
+            addrtype: AddrKind::try_from(addrtype)?,
+            username: placeholder(username),
         })
     }
 }
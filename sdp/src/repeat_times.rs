@@ -1,8 +1,5 @@
 use super::util::short_time;
-use anyhow::{
-    ensure,
-    Result
-};
+use super::error::SdpError;
 
 use std::{
     convert::TryFrom,
@@ -79,7 +76,7 @@ impl fmt::Display for RepeatTimes {
 }
 
 impl<'a> TryFrom<&'a str> for RepeatTimes {
-    type Error = anyhow::Error;
+    type Error = SdpError;
     /// # Unit Test
     ///
     /// ```
@@ -102,7 +99,10 @@ impl<'a> TryFrom<&'a str> for RepeatTimes {
     /// ```
     fn try_from(value: &'a str) -> Result<Self, Self::Error> {
         let values = value.split(' ').collect::<Vec<&str>>();
-        ensure!(values.len() == 4, "invalid timing!");
+        if values.len() != 4 {
+            return Err(SdpError::MissingField("repeat times"))
+        }
+
         Ok(Self {
             repeat_interval: short_time(values[0])?,
             active_duration: short_time(values[1])?,
@@ -1,4 +1,5 @@
 use super::util::short_time;
+use super::error::SdpError;
 use itertools::Itertools;
 use std::{
     convert::TryFrom,
@@ -78,7 +79,7 @@ impl fmt::Display for TimeZone {
 }
 
 impl<'a> TryFrom<(&'a str, &'a str)> for TimeZone {
-    type Error = anyhow::Error;
+    type Error = SdpError;
     /// # Unit Test
     ///
     /// ```
@@ -134,7 +135,7 @@ impl fmt::Display for TimeZones {
 }
 
 impl<'a> TryFrom<&'a str> for TimeZones {
-    type Error = anyhow::Error;
+    type Error = SdpError;
     /// # Unit Test
     ///
     /// ```
@@ -1,4 +1,5 @@
 use super::util::tuple2_from_split;
+use super::error::SdpError;
 use std::{
     convert::TryFrom,
     fmt
@@ -22,7 +23,7 @@ use std::{
 /// representation of Network Time Protocol (NTP) time values in seconds
 /// since 1900.  To convert these values to UNIX time, subtract
 /// decimal 2208988800.
-
+/// 
 /// NTP timestamps are elsewhere represented by 64-bit values, which wrap
 /// sometime in the year 2036.  Since SDP uses an arbitrary length
 /// decimal representation, this should not cause an issue (SDP
@@ -58,7 +59,7 @@ impl fmt::Display for Timing {
 }
 
 impl<'a> TryFrom<&'a str> for Timing {
-    type Error = anyhow::Error;
+    type Error = SdpError;
     /// # Unit Test
     ///
     /// ```
@@ -72,7 +73,7 @@ impl<'a> TryFrom<&'a str> for Timing {
     /// assert_eq!(instance.stop, 0);
     /// ```
     fn try_from(value: &'a str) -> Result<Self, Self::Error> {
-        let (sa, st) = tuple2_from_split(value, ' ', "invalid timing!")?;
+        let (sa, st) = tuple2_from_split(value, ' ', "timing")?;
         Ok(Self {
             start: sa.parse::<u64>()?,
             stop: st.parse::<u64>()?
@@ -1,6 +1,6 @@
-use anyhow::{
-    Result,
-    anyhow
+use super::error::{
+    SdpError,
+    Result
 };
 
 /// short char time representation.
@@ -15,9 +15,12 @@ use anyhow::{
 /// assert_eq!(short_time("1m").unwrap(), 60.0);
 /// assert_eq!(short_time("1s").unwrap(), 1.0);
 /// assert_eq!(short_time("100").unwrap(), 100.0);
+/// assert!(short_time("").is_err());
+/// assert!(short_time("1€").is_err());
 /// ```
 pub fn short_time(time: &str) -> Result<f64> {
-    let (value, last) = time.split_at(time.len() - 1);
+    let index = time.char_indices().last().map(|(i, _)| i).unwrap_or(0);
+    let (value, last) = time.split_at(index);
     Ok(match last {
         "d" => parse_f64(value)? * 86400.0,
         "h" => parse_f64(value)? * 3600.0,
@@ -57,11 +60,11 @@ pub fn placeholder(source: &str) -> Option<&str> {
 pub fn tuple2_from_split<'a>(
     value: &'a str, 
     pat: char,
-    msg: &'static str
+    field: &'static str
 ) -> Result<(&'a str, &'a str)> {
     let mut split = value.split(pat);
-    let v1 = split.next().ok_or_else(|| anyhow!(msg))?;
-    let v2 = split.next().ok_or_else(|| anyhow!(msg))?;
+    let v1 = split.next().ok_or(SdpError::MissingField(field))?;
+    let v2 = split.next().ok_or(SdpError::MissingField(field))?;
     Ok((v1, v2))
 }
 
@@ -77,15 +80,60 @@ pub fn tuple2_from_split<'a>(
 pub fn tuple3_from_split<'a>(
     value: &'a str, 
     pat: char, 
-    msg: &'static str
+    field: &'static str
 ) -> Result<(&'a str, &'a str, &'a str)> {
     let mut split = value.split(pat);
-    let v1 = split.next().ok_or_else(|| anyhow!(msg))?;
-    let v2 = split.next().ok_or_else(|| anyhow!(msg))?;
-    let v3 = split.next().ok_or_else(|| anyhow!(msg))?;
+    let v1 = split.next().ok_or(SdpError::MissingField(field))?;
+    let v2 = split.next().ok_or(SdpError::MissingField(field))?;
+    let v3 = split.next().ok_or(SdpError::MissingField(field))?;
     Ok((v1, v2, v3))
 }
 
+/// split a contact into the address and the display name.
+///
+/// email-address = address-and-comment / dispname-and-address
+///                 / address
+/// address-and-comment = addr-spec 1*SP "(" 1*email-safe ")"
+/// dispname-and-address = 1*email-safe 1*SP "<" addr-spec ">"
+///
+/// the phone number uses the same forms.
+///
+/// # Unit Test
+///
+/// ```
+/// use sdp::util::*;
+///
+/// assert_eq!(
+///     contact("Jane Doe <j.doe@example.com>"), 
+///     ("j.doe@example.com", Some("Jane Doe"))
+/// );
+///
+/// assert_eq!(
+///     contact("j.doe@example.com (Jane Doe)"), 
+///     ("j.doe@example.com", Some("Jane Doe"))
+/// );
+///
+/// assert_eq!(contact("+1 617 555-6011"), ("+1 617 555-6011", None));
+/// ```
+pub fn contact(value: &str) -> (&str, Option<&str>) {
+    let value = value.trim();
+    if let Some(rest) = value.strip_suffix('>') {
+        if let Some((name, address)) = rest.rsplit_once('<') {
+            let name = name.trim();
+            return (address.trim(), Some(name).filter(|n| !n.is_empty()))
+        }
+    }
+
+    if let Some(rest) = value.strip_suffix(')') {
+        if let Some((address, name)) = rest.split_once('(') {
+            let name = name.trim();
+            return (address.trim(), Some(name).filter(|n| !n.is_empty()))
+        }
+    }
+
+    (value, None)
+}
+
 fn parse_f64(value: &str) -> Result<f64> {
     Ok(value.parse::<f64>()?)
 }
\ No newline at end of file
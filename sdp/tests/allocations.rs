@@ -0,0 +1,84 @@
+use std::cell::Cell;
+use std::convert::TryFrom;
+use std::alloc::{
+    GlobalAlloc,
+    System,
+    Layout
+};
+
+use sdp::{
+    connection::Connection,
+    origin::Origin,
+    timing::Timing,
+    Sdp
+};
+
+const OFFER: &str = "v=0\r\n\
+    o=- 9216395717180620054 2 IN IP4 127.0.0.1\r\n\
+    s=-\r\n\
+    c=IN IP4 0.0.0.0\r\n\
+    t=0 0\r\n\
+    a=group:BUNDLE 0 1\r\n\
+    m=audio 9 UDP/TLS/RTP/SAVPF 0\r\n\
+    c=IN IP4 0.0.0.0\r\n\
+    a=mid:0\r\n\
+    a=sendrecv\r\n\
+    a=ptime:20\r\n\
+    m=video 9 UDP/TLS/RTP/SAVPF 96 97\r\n\
+    c=IN IP4 0.0.0.0\r\n\
+    a=mid:1\r\n\
+    a=rtpmap:96 VP8/90000\r\n\
+    a=rtpmap:97 H264/90000\r\n\
+    a=rtcp-fb:96 nack pli\r\n";
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// counts the allocations of the current thread,
+/// the tests run in parallel on their own threads.
+struct Counter;
+
+unsafe impl GlobalAlloc for Counter {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|c| c.set(c.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counter = Counter;
+
+/// the number of allocations made by the function.
+fn allocations<F: FnOnce()>(f: F) -> usize {
+    let before = ALLOCATIONS.with(|c| c.get());
+    f();
+    ALLOCATIONS.with(|c| c.get()) - before
+}
+
+#[test]
+fn origin() {
+    let origin = "- 9216395717180620054 2 IN IP4 127.0.0.1";
+    assert_eq!(allocations(|| { Origin::try_from(origin).unwrap(); }), 0);
+}
+
+#[test]
+fn connection() {
+    assert_eq!(allocations(|| { Connection::try_from("IN IP4 0.0.0.0").unwrap(); }), 0);
+}
+
+#[test]
+fn timing() {
+    assert_eq!(allocations(|| { Timing::try_from("0 0").unwrap(); }), 0);
+}
+
+#[test]
+fn offer() {
+    // the session itself still collects the media and attributes
+    // into vectors, only the lines are borrowed.
+    assert!(allocations(|| { Sdp::try_from(OFFER).unwrap(); }) > 0);
+}
@@ -1,5 +1,12 @@
 use std::convert::TryFrom;
-use stun::Payload;
+
+use stun::{
+    attribute::UserName,
+    LazyMessageReader,
+    MessageReader,
+    Payload
+};
+
 use criterion::*;
 
 const CHANNEL_BIND: [u8; 108] = [
@@ -167,14 +174,28 @@ fn decoder(bytes: &[u8]) {
     Payload::try_from(bytes).unwrap();
 }
 
+fn eager_username(bytes: &[u8]) {
+    let message = MessageReader::try_from(bytes).unwrap();
+    black_box(message.get::<UserName>().unwrap().unwrap());
+}
+
+fn lazy_username(bytes: &[u8]) {
+    let message = LazyMessageReader::try_from(bytes).unwrap();
+    black_box(message.get::<UserName>().unwrap().unwrap());
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
     let channel_bind = &CHANNEL_BIND[..];
     let channel_data = &CHANNEL_DATA[..];
+
     let mut group = c.benchmark_group("stun_decoder");
     group.throughput(Throughput::Bytes(channel_bind.len() as u64));
     group.bench_function("decoder_channel_bind", |b| b.iter(|| decoder(channel_bind)));
     group.throughput(Throughput::Bytes(channel_data.len() as u64));
     group.bench_function("decoder_channel_data", |b| b.iter(|| decoder(channel_data)));
+    group.throughput(Throughput::Bytes(channel_bind.len() as u64));
+    group.bench_function("eager_username", |b| b.iter(|| eager_username(channel_bind)));
+    group.bench_function("lazy_username", |b| b.iter(|| lazy_username(channel_bind)));
     group.finish();
 }
 
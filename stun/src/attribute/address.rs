@@ -4,11 +4,7 @@ use bytes::{
     BytesMut
 };
 
-use anyhow::{
-    anyhow, 
-    ensure,
-    Result
-};
+use crate::StunError;
 
 use std::net::{
     IpAddr, 
@@ -116,8 +112,11 @@ impl Addr {
     /// assert_eq!(addr, source);
     /// ```
     #[rustfmt::skip]
-    pub fn try_from(packet: &[u8], token: &[u8], is_xor: bool) -> Result<SocketAddr> {
-        ensure!(packet.len() >= 4, "buf len < 4");
+    pub fn try_from(packet: &[u8], token: &[u8], is_xor: bool) -> Result<SocketAddr, StunError> {
+        if packet.len() < 4 {
+            return Err(StunError::TruncatedAttribute)
+        }
+
         let port = u16::from_be_bytes([
             packet[2], 
             packet[3]
@@ -126,7 +125,7 @@ impl Addr {
         let ip_addr = match packet[1] {
             FAMILY_IPV4 => from_bytes_v4(packet)?,
             FAMILY_IPV6 => from_bytes_v6(packet)?,
-            _ => return Err(anyhow!("missing family")),
+            f => return Err(StunError::UnknownFamily(f)),
         };
 
         let dyn_addr = SocketAddr::new(ip_addr, port);
@@ -139,16 +138,20 @@ impl Addr {
 }
 
 /// Bytes as IpAddrV4.
-fn from_bytes_v4(packet: &[u8]) -> Result<IpAddr> {
-    ensure!(packet.len() >= 8, "buf len < 8");
-    let buf: [u8; 4] = packet[4..8].try_into()?;
+fn from_bytes_v4(packet: &[u8]) -> Result<IpAddr, StunError> {
+    let buf: [u8; 4] = packet
+        .get(4..8)
+        .and_then(|b| b.try_into().ok())
+        .ok_or(StunError::TruncatedAttribute)?;
     Ok(IpAddr::V4(buf.into()))
 }
 
 /// Bytes as IpAddrV6.
-fn from_bytes_v6(packet: &[u8]) -> Result<IpAddr> {
-    ensure!(packet.len() >= 20, "buf len < 20");
-    let buf: [u8; 16] = packet[4..20].try_into()?;
+fn from_bytes_v6(packet: &[u8]) -> Result<IpAddr, StunError> {
+    let buf: [u8; 16] = packet
+        .get(4..20)
+        .and_then(|b| b.try_into().ok())
+        .ok_or(StunError::TruncatedAttribute)?;
     Ok(IpAddr::V6(buf.into()))
 }
 
@@ -1,6 +1,8 @@
 use num_enum::TryFromPrimitive;
-use anyhow::ensure;
-use crate::util;
+use crate::{
+    StunError,
+    util
+};
 use bytes::{
     BufMut, 
     BytesMut
@@ -17,25 +19,28 @@ use std::convert::{
 };
 
 /// error type.
+///
+/// the value is the decimal status code, such as 401.
 #[repr(u16)]
 #[derive(TryFromPrimitive)]
 #[derive(PartialEq, Eq)]
 #[derive(Copy, Clone, Debug)]
 pub enum Kind {
-    TryAlternate = 0x0300,
-    BadRequest = 0x0400,
-    Unauthorized = 0x0401,
-    Forbidden = 0x0403,
-    RequestTimedout = 0x0408,
-    UnknownAttribute = 0x0420,
-    AllocationMismatch = 0x0437,
-    StaleNonce = 0x0438,
-    AddressFamilyNotSupported = 0x0440,
-    WrongCredentials = 0x0441,
-    UnsupportedTransportAddress = 0x0442,
-    AllocationQuotaReached = 0x0486,
-    ServerError = 0x0500,
-    InsufficientCapacity = 0x0508,
+    TryAlternate = 300,
+    BadRequest = 400,
+    Unauthorized = 401,
+    Forbidden = 403,
+    RequestTimedout = 408,
+    UnknownAttribute = 420,
+    AllocationMismatch = 437,
+    StaleNonce = 438,
+    AddressFamilyNotSupported = 440,
+    WrongCredentials = 441,
+    UnsupportedTransportAddress = 442,
+    PeerAddressFamilyMismatch = 443,
+    AllocationQuotaReached = 486,
+    ServerError = 500,
+    InsufficientCapacity = 508,
 }
 
 /// stun message error attribute. 
@@ -64,6 +69,9 @@ impl Error<'_> {
 
     /// encode the error type as bytes.
     ///
+    /// the code is written as the class, the hundreds digit, 
+    /// and the number, the code modulo 100.
+    ///
     /// # Unit Test
     ///
     /// ```
@@ -82,16 +90,25 @@ impl Error<'_> {
     /// let error = Error::from(ErrKind::TryAlternate);
     /// error.into(&mut buf);
     /// assert_eq!(&buf[..], &buffer);
+    ///
+    /// let mut buf = BytesMut::with_capacity(1280);
+    /// Error::from(ErrKind::PeerAddressFamilyMismatch).into(&mut buf);
+    /// assert_eq!(&buf[..4], &[0x00, 0x00, 0x04, 0x2b]);
+    ///
+    /// let mut buf = BytesMut::with_capacity(1280);
+    /// Error::from(ErrKind::AllocationQuotaReached).into(&mut buf);
+    /// assert_eq!(&buf[..4], &[0x00, 0x00, 0x04, 0x56]);
     /// ```
     pub fn into(self, buf: &mut BytesMut) {
         buf.put_u16(0x0000);
-        buf.put_u16(self.code);
+        buf.put_u8((self.code / 100) as u8);
+        buf.put_u8((self.code % 100) as u8);
         buf.put(self.message.as_bytes());
     }
 }
 
 impl<'a> TryFrom<&'a [u8]> for Error<'a> {
-    type Error = anyhow::Error;
+    type Error = StunError;
     /// # Unit Test
     ///
     /// ```
@@ -109,19 +126,28 @@ impl<'a> TryFrom<&'a [u8]> for Error<'a> {
     /// let error = Error::try_from(&buffer[..]).unwrap();
     /// assert_eq!(error.code, ErrKind::TryAlternate as u16);
     /// assert_eq!(error.message, "Try Alternate");
+    ///
+    /// let error = Error::try_from(&[0x00u8, 0x00, 0x04, 0x2b][..]).unwrap();
+    /// assert_eq!(error.code, 443);
     /// ```
     #[rustfmt::skip]
     fn try_from(packet: &'a [u8]) -> Result<Self, Self::Error> {
-        ensure!(packet.len() >= 4, "buffer len < 4");
-        ensure!(util::as_u16(&packet[..2]) == 0x0000, "missing reserved");
+        if packet.len() < 4 {
+            return Err(StunError::TruncatedAttribute)
+        }
+
+        if util::as_u16(&packet[..2]) != 0x0000 {
+            return Err(StunError::MissingReserved)
+        }
+
         Ok(Self { 
-            code: util::as_u16(&packet[2..4]),
+            code: (packet[2] & 0x07) as u16 * 100 + packet[3] as u16,
             message: std::str::from_utf8(&packet[4..])?,
         })
     }
 }
 
-impl Into<&'static str> for Kind {
+impl From<Kind> for &'static str {
     /// # Unit Test
     ///
     /// ```
@@ -130,23 +156,27 @@ impl Into<&'static str> for Kind {
     /// 
     /// let err: &'static str = ErrKind::TryAlternate.into();
     /// assert_eq!(err, "Try Alternate");
+    ///
+    /// let err: &'static str = ErrKind::PeerAddressFamilyMismatch.into();
+    /// assert_eq!(err, "Peer Address Family Mismatch");
     /// ```
-    fn into(self) -> &'static str {
-        match self {
-            Self::TryAlternate => "Try Alternate",
-            Self::BadRequest => "Bad Request",
-            Self::Unauthorized => "Unauthorized",
-            Self::Forbidden => "Forbidden",
-            Self::RequestTimedout => "Request Timed out",
-            Self::UnknownAttribute => "Unknown Attribute",
-            Self::AllocationMismatch => "Allocation Mismatch",
-            Self::StaleNonce => "Stale Nonce",
-            Self::AddressFamilyNotSupported => "Address Family not Supported",
-            Self::WrongCredentials => "Wrong Credentials",
-            Self::UnsupportedTransportAddress => "Unsupported Transport Address",
-            Self::AllocationQuotaReached => "Allocation Quota Reached",
-            Self::ServerError => "Server Error",
-            Self::InsufficientCapacity => "Insufficient Capacity",
+    fn from(val: Kind) -> Self {
+        match val {
+            Kind::TryAlternate => "Try Alternate",
+            Kind::BadRequest => "Bad Request",
+            Kind::Unauthorized => "Unauthorized",
+            Kind::Forbidden => "Forbidden",
+            Kind::RequestTimedout => "Request Timed out",
+            Kind::UnknownAttribute => "Unknown Attribute",
+            Kind::AllocationMismatch => "Allocation Mismatch",
+            Kind::StaleNonce => "Stale Nonce",
+            Kind::AddressFamilyNotSupported => "Address Family not Supported",
+            Kind::WrongCredentials => "Wrong Credentials",
+            Kind::UnsupportedTransportAddress => "Unsupported Transport Address",
+            Kind::PeerAddressFamilyMismatch => "Peer Address Family Mismatch",
+            Kind::AllocationQuotaReached => "Allocation Quota Reached",
+            Kind::ServerError => "Server Error",
+            Kind::InsufficientCapacity => "Insufficient Capacity",
         }
     }
 }
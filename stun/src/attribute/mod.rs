@@ -5,7 +5,10 @@ use num_enum::TryFromPrimitive;
 use std::convert::TryFrom;
 use std::net::SocketAddr;
 pub use address::Addr;
-use crate::util;
+use crate::{
+    StunError,
+    util
+};
 use bytes::{
     BytesMut,
     BufMut
@@ -20,6 +23,7 @@ pub use error::{
 #[repr(u16)]
 #[derive(TryFromPrimitive)]
 #[derive(PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug)]
 pub enum AttrKind {
     UserName = 0x0006,
     Data = 0x0013,
@@ -37,6 +41,8 @@ pub enum AttrKind {
     ReqeestedTransport = 0x0019,
     Fingerprint = 0x8028,
     ChannelNumber = 0x000C,
+    EvenPort = 0x0018,
+    ReservationToken = 0x0022,
 }
 
 /// dyn stun/turn message attribute.
@@ -47,11 +53,20 @@ pub trait Property<'a> {
     /// get current attribute type.
     fn kind() -> AttrKind;
     /// write the current attribute to the buffer.
-    fn into(value: Self::Inner, buf: &mut BytesMut, t: &'a [u8]);
+    fn into(value: Self::Inner, buf: &mut BytesMut, t: &[u8]);
     /// convert buffer to current attribute.
     fn try_from(buf: &'a [u8], t: &'a [u8]) -> Result<Self::Inner, Self::Error>;
 }
 
+/// check that the attribute value holds at least `size` bytes.
+fn check_size(buf: &[u8], size: usize) -> Result<(), StunError> {
+    if buf.len() < size {
+        return Err(StunError::TruncatedAttribute)
+    }
+
+    Ok(())
+}
+
 /// The USERNAME attribute is used for message integrity.  It identifies
 /// the username and password combination used in the message-integrity
 /// check.
@@ -65,7 +80,7 @@ pub trait Property<'a> {
 pub struct UserName;
 impl<'a> Property<'a> for UserName {
     type Inner = &'a str;
-    type Error = anyhow::Error;
+    type Error = StunError;
     fn kind() -> AttrKind {
         AttrKind::UserName
     }
@@ -88,7 +103,7 @@ impl<'a> Property<'a> for UserName {
 pub struct Data;
 impl<'a> Property<'a> for Data {
     type Inner = &'a [u8];
-    type Error = anyhow::Error;
+    type Error = StunError;
     fn kind() -> AttrKind {
         AttrKind::Data
     }
@@ -119,7 +134,7 @@ impl<'a> Property<'a> for Data {
 pub struct Realm;
 impl<'a> Property<'a> for Realm {
     type Inner = &'a str;
-    type Error = anyhow::Error;
+    type Error = StunError;
     fn kind() -> AttrKind {
         AttrKind::Realm
     }
@@ -144,7 +159,7 @@ impl<'a> Property<'a> for Realm {
 pub struct Nonce;
 impl<'a> Property<'a> for Nonce {
     type Inner = &'a str;
-    type Error = anyhow::Error;
+    type Error = StunError;
     fn kind() -> AttrKind {
         AttrKind::Nonce
     }
@@ -170,7 +185,7 @@ impl<'a> Property<'a> for Nonce {
 pub struct Software;
 impl<'a> Property<'a> for Software {
     type Inner = &'a str;
-    type Error = anyhow::Error;
+    type Error = StunError;
     fn kind() -> AttrKind {
         AttrKind::Software
     }
@@ -215,7 +230,7 @@ impl<'a> Property<'a> for Software {
 pub struct MessageIntegrity;
 impl<'a> Property<'a> for MessageIntegrity {
     type Inner = &'a [u8];
-    type Error = anyhow::Error;
+    type Error = StunError;
     fn kind() -> AttrKind {
         AttrKind::MessageIntegrity
     }
@@ -236,7 +251,7 @@ impl<'a> Property<'a> for MessageIntegrity {
 pub struct XorPeerAddress;
 impl<'a> Property<'a> for XorPeerAddress {
     type Inner = SocketAddr;
-    type Error = anyhow::Error;
+    type Error = StunError;
     fn kind() -> AttrKind {
         AttrKind::XorPeerAddress
     }
@@ -257,7 +272,7 @@ impl<'a> Property<'a> for XorPeerAddress {
 pub struct XorRelayedAddress;
 impl<'a> Property<'a> for XorRelayedAddress {
     type Inner = SocketAddr;
-    type Error = anyhow::Error;
+    type Error = StunError;
     fn kind() -> AttrKind {
         AttrKind::XorRelayedAddress
     }
@@ -317,7 +332,7 @@ impl<'a> Property<'a> for XorRelayedAddress {
 pub struct XorMappedAddress;
 impl<'a> Property<'a> for XorMappedAddress {
     type Inner = SocketAddr;
-    type Error = anyhow::Error;
+    type Error = StunError;
     fn kind() -> AttrKind {
         AttrKind::XorMappedAddress
     }
@@ -366,7 +381,7 @@ impl<'a> Property<'a> for XorMappedAddress {
 pub struct MappedAddress;
 impl<'a> Property<'a> for MappedAddress {
     type Inner = SocketAddr;
-    type Error = anyhow::Error;
+    type Error = StunError;
     fn kind() -> AttrKind {
         AttrKind::MappedAddress
     }
@@ -387,7 +402,7 @@ impl<'a> Property<'a> for MappedAddress {
 pub struct ResponseOrigin;
 impl<'a> Property<'a> for ResponseOrigin {
     type Inner = SocketAddr;
-    type Error = anyhow::Error;
+    type Error = StunError;
     fn kind() -> AttrKind {
         AttrKind::ResponseOrigin
     }
@@ -425,7 +440,7 @@ impl<'a> Property<'a> for ResponseOrigin {
 pub struct ErrorCode;
 impl<'a> Property<'a> for ErrorCode {
     type Inner = Error<'a>;
-    type Error = anyhow::Error;
+    type Error = StunError;
     fn kind() -> AttrKind {
         AttrKind::ErrorCode
     }
@@ -447,7 +462,7 @@ impl<'a> Property<'a> for ErrorCode {
 pub struct Lifetime;
 impl<'a> Property<'a> for Lifetime {
     type Inner = u32;
-    type Error = anyhow::Error;
+    type Error = StunError;
     fn kind() -> AttrKind {
         AttrKind::Lifetime
     }
@@ -457,6 +472,7 @@ impl<'a> Property<'a> for Lifetime {
     }
 
     fn try_from(buf: &'a [u8], _: &'a [u8]) -> Result<Self::Inner, Self::Error> {
+        check_size(buf, 4)?;
         Ok(util::as_u32(buf))
     }
 }
@@ -484,7 +500,7 @@ impl<'a> Property<'a> for Lifetime {
 pub struct ReqeestedTransport;
 impl<'a> Property<'a> for ReqeestedTransport {
     type Inner = u8;
-    type Error = anyhow::Error;
+    type Error = StunError;
     fn kind() -> AttrKind {
         AttrKind::ReqeestedTransport
     }
@@ -494,6 +510,7 @@ impl<'a> Property<'a> for ReqeestedTransport {
     }
 
     fn try_from(buf: &'a [u8], _: &'a [u8]) -> Result<Self::Inner, Self::Error> {
+        check_size(buf, 1)?;
         Ok(buf[0])
     }
 }
@@ -506,8 +523,8 @@ impl<'a> Property<'a> for ReqeestedTransport {
 /// that the FINGERPRINT test will not report a false positive on a
 /// packet containing a CRC-32 generated by an application protocol.)
 /// The 32-bit CRC is the one defined in ITU V.42 [ITU.V42.2002], which
-/// has a generator polynomial of x^32 + x^26 + x^23 + x^22 + x^16 + x^12
-/// + x^11 + x^10 + x^8 + x^7 + x^5 + x^4 + x^2 + x + 1.  See the sample
+/// has a generator polynomial of x^32 + x^26 + x^23 + x^22 + x^16 +
+/// x^12 + x^11 + x^10 + x^8 + x^7 + x^5 + x^4 + x^2 + x + 1.  See the sample
 /// code for the CRC-32 in Section 8 of [RFC1952].
 /// 
 /// When present, the FINGERPRINT attribute MUST be the last attribute in
@@ -532,7 +549,7 @@ impl<'a> Property<'a> for ReqeestedTransport {
 pub struct Fingerprint;
 impl<'a> Property<'a> for Fingerprint {
     type Inner = u32;
-    type Error = anyhow::Error;
+    type Error = StunError;
     fn kind() -> AttrKind {
         AttrKind::Fingerprint
     }
@@ -542,6 +559,7 @@ impl<'a> Property<'a> for Fingerprint {
     }
 
     fn try_from(buf: &'a [u8], _: &'a [u8]) -> Result<Self::Inner, Self::Error> {
+        check_size(buf, 4)?;
         Ok(util::as_u32(buf))
     }
 }
@@ -562,7 +580,7 @@ impl<'a> Property<'a> for Fingerprint {
 pub struct ChannelNumber;
 impl<'a> Property<'a> for ChannelNumber {
     type Inner = u16;
-    type Error = anyhow::Error;
+    type Error = StunError;
     fn kind() -> AttrKind {
         AttrKind::ChannelNumber
     }
@@ -572,6 +590,100 @@ impl<'a> Property<'a> for ChannelNumber {
     }
 
     fn try_from(buf: &'a [u8], _: &'a [u8]) -> Result<Self::Inner, Self::Error> {
+        check_size(buf, 2)?;
         Ok(util::as_u16(buf))
     }
 }
+
+/// This attribute allows the client to request that the port in the
+/// relayed transport address be even and (optionally) that the server
+/// reserve the next-higher port number.  The value portion of this
+/// attribute is 1 byte long.  Its format is:
+/// 
+/// ```bash
+///   0
+///   0 1 2 3 4 5 6 7
+///  +-+-+-+-+-+-+-+-+
+///  |R|    RFFU     |
+///  +-+-+-+-+-+-+-+-+
+/// ```
+///
+/// The fields are:
+/// 
+/// * R: If 1, the server is requested to reserve the next-higher port
+///   number (on the same IP address) for a subsequent allocation.  If 0,
+///   no such reservation is requested.
+/// 
+/// * RFFU: Reserved For Future Use.  These 7 bits MUST be set to zero on
+///   transmission and MUST be ignored on reception.
+///
+/// # Unit Test
+///
+/// ```
+/// use stun::attribute::*;
+/// use bytes::BytesMut;
+///
+/// let mut buf = BytesMut::with_capacity(1280);
+/// <EvenPort as Property>::into(true, &mut buf, &[]);
+/// assert_eq!(&buf[..], &[0x80]);
+/// assert_eq!(<EvenPort as Property>::try_from(&buf[..], &[]).unwrap(), true);
+/// assert_eq!(<EvenPort as Property>::try_from(&[0x00][..], &[]).unwrap(), false);
+/// ```
+pub struct EvenPort;
+impl<'a> Property<'a> for EvenPort {
+    type Inner = bool;
+    type Error = StunError;
+    fn kind() -> AttrKind {
+        AttrKind::EvenPort
+    }
+
+    fn into(value: Self::Inner, buf: &mut BytesMut, _: &[u8]) {
+        buf.put_u8(if value { 0x80 } else { 0x00 })
+    }
+
+    fn try_from(buf: &'a [u8], _: &'a [u8]) -> Result<Self::Inner, Self::Error> {
+        check_size(buf, 1)?;
+        Ok(buf[0] & 0x80 != 0)
+    }
+}
+
+/// The RESERVATION-TOKEN attribute contains a token that uniquely
+/// identifies a relayed transport address being held in reserve by the
+/// server.  The server includes this attribute in a success response to
+/// tell the client about the token, and the client includes this
+/// attribute in a subsequent Allocate request to request the server use
+/// that relayed transport address for the allocation.
+/// 
+/// The attribute value is 8 bytes and contains the token value.
+///
+/// # Unit Test
+///
+/// ```
+/// use stun::attribute::*;
+/// use bytes::BytesMut;
+///
+/// let mut buf = BytesMut::with_capacity(1280);
+/// <ReservationToken as Property>::into(0x0102030405060708, &mut buf, &[]);
+/// assert_eq!(&buf[..], &[1, 2, 3, 4, 5, 6, 7, 8]);
+/// assert_eq!(<ReservationToken as Property>::try_from(&buf[..], &[]).unwrap(), 0x0102030405060708);
+/// ```
+pub struct ReservationToken;
+impl<'a> Property<'a> for ReservationToken {
+    type Inner = u64;
+    type Error = StunError;
+    fn kind() -> AttrKind {
+        AttrKind::ReservationToken
+    }
+
+    fn into(value: Self::Inner, buf: &mut BytesMut, _: &[u8]) {
+        buf.put_u64(value)
+    }
+
+    fn try_from(buf: &'a [u8], _: &'a [u8]) -> Result<Self::Inner, Self::Error> {
+        if buf.len() != 8 {
+            return Err(StunError::TruncatedAttribute)
+        }
+
+        Ok(util::as_u64(buf))
+    }
+}
@@ -1,17 +1,27 @@
 use std::convert::TryFrom;
-use anyhow::ensure;
-use super::util;
+use super::{
+    StunError,
+    frame,
+    util
+};
 
 /// channel data message.
+///
+/// over UDP, the datagram may carry padding or other trailing 
+/// bytes after the message, they are not part of the message 
+/// and are ignored.
 pub struct ChannelData<'a> {
-    /// channnel data bytes.
+    /// channnel data message bytes, 
+    /// header included and trailing bytes excluded.
     pub buf: &'a [u8],
+    /// application data bytes.
+    pub body: &'a [u8],
     /// channel number.
     pub number: u16,
 }
 
 impl<'a> TryFrom<&'a [u8]> for ChannelData<'a> {
-    type Error = anyhow::Error;
+    type Error = StunError;
     /// # Unit Test
     ///
     /// ```
@@ -19,19 +29,73 @@ impl<'a> TryFrom<&'a [u8]> for ChannelData<'a> {
     /// use std::convert::TryFrom;
     /// 
     /// let buffer: [u8; 4] = [
-    ///     0x00, 0x01, 0x00, 0x00
+    ///     0x40, 0x01, 0x00, 0x00
     /// ];
     ///         
     /// let data = ChannelData::try_from(&buffer[..]).unwrap();
-    /// assert_eq!(data.number, 1);
+    /// assert_eq!(data.number, 0x4001);
+    /// assert!(data.body.is_empty());
+    ///
+    /// let buffer: [u8; 4] = [
+    ///     0x3f, 0xff, 0x00, 0x00
+    /// ];
+    ///
+    /// let err = ChannelData::try_from(&buffer[..]).err().unwrap();
+    /// assert_eq!(err, StunError::InvalidChannelNumber(0x3fff));
+    ///
+    /// let buffer: [u8; 4] = [
+    ///     0x7f, 0xff, 0x00, 0x00
+    /// ];
+    ///
+    /// assert!(ChannelData::try_from(&buffer[..]).is_ok());
+    ///
+    /// let buffer: [u8; 4] = [
+    ///     0x80, 0x00, 0x00, 0x00
+    /// ];
+    ///
+    /// assert!(ChannelData::try_from(&buffer[..]).is_err());
+    ///
+    /// let buffer: [u8; 7] = [
+    ///     0x40, 0x00, 0x00, 0x03,
+    ///     0x01, 0x02, 0x03
+    /// ];
+    ///
+    /// let data = ChannelData::try_from(&buffer[..]).unwrap();
+    /// assert_eq!(data.buf, &buffer[..]);
+    /// assert_eq!(data.body, &[0x01, 0x02, 0x03]);
+    ///
+    /// let buffer: [u8; 10] = [
+    ///     0x40, 0x00, 0x00, 0x03,
+    ///     0x01, 0x02, 0x03, 0x00,
+    ///     0xff, 0xff
+    /// ];
+    ///
+    /// let data = ChannelData::try_from(&buffer[..]).unwrap();
+    /// assert_eq!(data.buf, &buffer[..7]);
+    /// assert_eq!(data.body, &[0x01, 0x02, 0x03]);
+    ///
+    /// assert!(ChannelData::try_from(&buffer[..6]).is_err());
     /// ```
     #[rustfmt::skip]
     fn try_from(buf: &'a [u8]) -> Result<Self, Self::Error> {
         let len = buf.len();
-        ensure!(len >= 4, "data len < 4");
-        let size = util::as_u16(&buf[2..4]) as usize;
-        ensure!(size <= len - 4, "data body len < size");
+        if len < 4 {
+            return Err(StunError::TooShort)
+        }
+
+        // the channel number is in the range 0x4000 through 
+        // 0x7FFF, the first two bits are 0b01, which tells 
+        // channel data apart from a stun message.
         let number = util::as_u16(&buf[..2]);
-        Ok(Self { buf, number })
+        if !(0x4000..=0x7FFF).contains(&number) {
+            return Err(StunError::InvalidChannelNumber(number))
+        }
+
+        let (body, _) = frame::get(&buf[2..])?;
+        Ok(Self { 
+            buf: &buf[..body.len() + 4],
+            number,
+            body
+        })
     }
 }
@@ -0,0 +1,69 @@
+use thiserror::Error;
+use std::str::Utf8Error;
+
+/// stun/turn payload decode error.
+///
+/// the error returned when decoding a message, a channel data
+/// message or an attribute, so that the caller can decide by
+/// variant whether to drop the packet or report it.
+///
+/// # Unit Test
+///
+/// ```
+/// use stun::*;
+/// use std::convert::TryFrom;
+///
+/// let buffer: [u8; 20] = [
+///     0x00, 0x01, 0x00, 0x00,
+///     0x21, 0x12, 0xa4, 0x43,
+///     0x72, 0x6d, 0x49, 0x42,
+///     0x72, 0x52, 0x64, 0x48,
+///     0x57, 0x62, 0x4b, 0x2b
+/// ];
+///
+/// let err = MessageReader::try_from(&buffer[..]).err().unwrap();
+/// assert_eq!(err, StunError::BadCookie);
+///
+/// let err = MessageReader::try_from(&buffer[..19]).err().unwrap();
+/// assert_eq!(err, StunError::TooShort);
+/// ```
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum StunError {
+    /// the buffer is shorter than the message header or
+    /// the size given in the header.
+    #[error("buffer too short")]
+    TooShort,
+    /// the message is larger than the maximum message size.
+    #[error("message too large")]
+    TooLarge,
+    /// the message carries more than the maximum attribute count.
+    #[error("too many attributes")]
+    TooManyAttributes,
+    /// the magic cookie is missing.
+    #[error("missing cookie")]
+    BadCookie,
+    /// the attribute value is shorter than its type requires.
+    #[error("truncated attribute")]
+    TruncatedAttribute,
+    /// the attribute value is not valid UTF-8.
+    #[error("invalid utf8: {0}")]
+    Utf8(#[from] Utf8Error),
+    /// the message type is not supported.
+    #[error("unknown message kind: {0:#06x}")]
+    UnknownKind(u16),
+    /// the datagram is neither a stun message nor channel data.
+    #[error("unknown payload")]
+    UnknownPayload,
+    /// the channel number is outside 0x4000 through 0x7FFF.
+    #[error("invalid channel number: {0:#06x}")]
+    InvalidChannelNumber(u16),
+    /// the address family is neither IPv4 nor IPv6.
+    #[error("unknown address family: {0:#04x}")]
+    UnknownFamily(u8),
+    /// the reserved bits of the error code are not zero.
+    #[error("missing reserved")]
+    MissingReserved,
+    /// a comprehension-required attribute follows MESSAGE-INTEGRITY.
+    #[error("attribute after MessageIntegrity")]
+    AttributeAfterIntegrity,
+}
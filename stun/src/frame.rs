@@ -0,0 +1,115 @@
+use crate::{
+    StunError,
+    util
+};
+
+use bytes::{
+    BufMut,
+    BytesMut
+};
+
+/// the size of the length prefix.
+pub const PREFIX_SIZE: usize = 2;
+
+/// write a length prefixed frame.
+///
+/// the length prefix is reserved, the body is written
+/// by the closure, and then the length of the body is
+/// written back to the prefix. a body that does not fit
+/// in the 16 bit prefix is removed again.
+///
+/// # Unit Test
+///
+/// ```
+/// use stun::frame;
+/// use bytes::{BufMut, BytesMut};
+///
+/// let mut buf = BytesMut::new();
+/// let size = frame::put_with(&mut buf, |b| b.put(&b"panda"[..])).unwrap();
+/// assert_eq!(size, 5);
+/// assert_eq!(&buf[..], &[0x00, 0x05, 0x70, 0x61, 0x6e, 0x64, 0x61]);
+///
+/// let mut buf = BytesMut::from(&[0xffu8][..]);
+/// let err = frame::put_with(&mut buf, |b| b.put_bytes(0, 65536)).unwrap_err();
+/// assert_eq!(err, stun::StunError::TooLarge);
+/// assert_eq!(&buf[..], &[0xff]);
+/// ```
+pub fn put_with<F: FnOnce(&mut BytesMut)>(buf: &mut BytesMut, f: F) -> Result<usize, StunError> {
+    let os = buf.len();
+    buf.put_u16(0);
+    f(buf);
+
+    // compute the body size,
+    // back to the prefix write size.
+    let size = buf.len() - os - PREFIX_SIZE;
+    if size > u16::MAX as usize {
+        buf.truncate(os);
+        return Err(StunError::TooLarge)
+    }
+
+    buf[os..os + PREFIX_SIZE].copy_from_slice(&(size as u16).to_be_bytes());
+    Ok(size)
+}
+
+/// write a length prefixed frame of the body.
+///
+/// # Unit Test
+///
+/// ```
+/// use stun::frame;
+/// use bytes::BytesMut;
+///
+/// let mut buf = BytesMut::new();
+/// for size in [0, 1, 3, 255, 256, 65535] {
+///     let body = vec![0x5au8; size];
+///     assert_eq!(frame::put(&mut buf, &body).unwrap(), size);
+/// }
+///
+/// let mut rest = &buf[..];
+/// for size in [0, 1, 3, 255, 256, 65535] {
+///     let (body, next) = frame::get(rest).unwrap();
+///     assert_eq!(body, &vec![0x5au8; size][..]);
+///     rest = next;
+/// }
+///
+/// assert!(rest.is_empty());
+/// assert!(frame::put(&mut buf, &vec![0u8; 65536]).is_err());
+/// ```
+pub fn put(buf: &mut BytesMut, body: &[u8]) -> Result<usize, StunError> {
+    put_with(buf, |b| b.put(body))
+}
+
+/// read a length prefixed frame.
+///
+/// returns the body and the bytes that follow the body,
+/// the buffer must hold the prefix and the whole body.
+///
+/// # Unit Test
+///
+/// ```
+/// use stun::frame;
+/// use stun::StunError;
+///
+/// let buffer = [0x00u8, 0x02, 0x01, 0x02, 0x03];
+/// let (body, rest) = frame::get(&buffer[..]).unwrap();
+/// assert_eq!(body, &[0x01, 0x02]);
+/// assert_eq!(rest, &[0x03]);
+///
+/// let (body, rest) = frame::get(&[0x00, 0x00]).unwrap();
+/// assert!(body.is_empty() && rest.is_empty());
+///
+/// assert_eq!(frame::get(&[0x00]).unwrap_err(), StunError::TooShort);
+/// assert_eq!(frame::get(&buffer[..3]).unwrap_err(), StunError::TooShort);
+/// ```
+pub fn get(buf: &[u8]) -> Result<(&[u8], &[u8]), StunError> {
+    if buf.len() < PREFIX_SIZE {
+        return Err(StunError::TooShort)
+    }
+
+    let size = util::as_u16(&buf[..PREFIX_SIZE]) as usize;
+    if buf.len() - PREFIX_SIZE < size {
+        return Err(StunError::TooShort)
+    }
+
+    Ok(buf[PREFIX_SIZE..].split_at(size))
+}
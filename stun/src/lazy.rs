@@ -0,0 +1,304 @@
+use std::convert::TryFrom;
+use super::{
+    attribute::{
+        AttrKind,
+        Property
+    },
+    message::{
+        COOKIE,
+        Limits
+    },
+    StunError,
+    Kind,
+    frame,
+    util
+};
+
+/// the MESSAGE-INTEGRITY-SHA256 attribute type, it is not
+/// supported, but it may follow MESSAGE-INTEGRITY.
+const MESSAGE_INTEGRITY_SHA256: u16 = 0x001C;
+
+/// check the message header.
+///
+/// returns the message type and the transaction id,
+/// the attributes follow the 20 bytes header.
+#[rustfmt::skip]
+pub(crate) fn header<'a>(buf: &'a [u8], limits: &Limits) -> Result<(Kind, &'a [u8]), StunError> {
+    if buf.len() < 20 {
+        return Err(StunError::TooShort)
+    }
+
+    // message type
+    // message size
+    // check fixed magic cookie
+    // check if the message size is overflow
+    let kind = Kind::try_from(util::as_u16(&buf[..2]))
+        .map_err(|e| StunError::UnknownKind(e.number))?;
+    let size = util::as_u16(&buf[2..4]) as usize;
+    if buf[4..8] != COOKIE[..] {
+        return Err(StunError::BadCookie)
+    }
+
+    if buf.len() < size + 20 {
+        return Err(StunError::TooShort)
+    }
+
+    if size + 20 > limits.max_size {
+        return Err(StunError::TooLarge)
+    }
+
+    // get transaction id
+    Ok((kind, &buf[8..20]))
+}
+
+/// stun attribute iterator.
+///
+/// the attributes are read directly from the borrowed buffer,
+/// nothing is collected. the unsupported attributes and the
+/// attributes ignored after MESSAGE-INTEGRITY are skipped, an
+/// error is yielded once and ends the iteration.
+pub struct Attributes<'a> {
+    buf: &'a [u8],
+    offset: usize,
+    count: usize,
+    max_attributes: usize,
+    find_valid_offset: bool,
+    valid_offset: u16,
+    done: bool,
+}
+
+impl<'a> Attributes<'a> {
+    /// create the iterator over the attributes of the message,
+    /// the header must have been checked.
+    pub(crate) fn new(buf: &'a [u8], limits: &Limits) -> Self {
+        Self {
+            max_attributes: limits.max_attributes,
+            find_valid_offset: false,
+            valid_offset: 0,
+            done: false,
+            offset: 20,
+            count: 0,
+            buf,
+        }
+    }
+
+    /// the offset of the MESSAGE-INTEGRITY attribute,
+    /// it is known after the iteration.
+    pub(crate) fn valid_offset(&self) -> u16 {
+        self.valid_offset
+    }
+}
+
+impl<'a> Iterator for Attributes<'a> {
+    type Item = Result<(AttrKind, &'a [u8]), StunError>;
+    #[rustfmt::skip]
+    fn next(&mut self) -> Option<Self::Item> {
+        let count_size = self.buf.len();
+        while !self.done {
+
+            // if the buf length is not long enough to continue,
+            // jump out of the loop.
+            if count_size - self.offset < 4 {
+                break;
+            }
+
+            // every attribute costs work, including the skipped ones,
+            // so the number of attributes is capped.
+            self.count += 1;
+            if self.count > self.max_attributes {
+                self.done = true;
+                return Some(Err(StunError::TooManyAttributes))
+            }
+
+            // get attribute type
+            let offset = self.offset;
+            let key = util::as_u16(&self.buf[offset..offset + 2]);
+
+            // whether the MessageIntegrity attribute has been found,
+            // if found, record the current offset position.
+            let follows_integrity = self.find_valid_offset;
+            if !self.find_valid_offset {
+                self.valid_offset = offset as u16;
+            }
+
+            // with the exception of the MESSAGE-INTEGRITY-SHA256 and 
+            // FINGERPRINT attributes, which appear after MESSAGE-INTEGRITY, 
+            // agents MUST ignore all other attributes that follow 
+            // MESSAGE-INTEGRITY, a comprehension-required attribute 
+            // after it is treated as a malformed message.
+            if follows_integrity && key < 0x8000 && key != MESSAGE_INTEGRITY_SHA256 {
+                self.done = true;
+                return Some(Err(StunError::AttributeAfterIntegrity))
+            }
+
+            // check whether the current attribute is MessageIntegrity,
+            // if it is, mark this attribute has been found.
+            if key == AttrKind::MessageIntegrity as u16 {
+                self.find_valid_offset = true;
+            }
+
+            // get attribute body,
+            // if the attribute length has overflowed, jump out of the loop.
+            let value = match frame::get(&self.buf[offset + 2..]) {
+                Ok((value, _)) => value,
+                Err(_) => break
+            };
+
+            // if there are padding bytes, skip padding size.
+            let size = value.len();
+            self.offset = count_size.min(offset + 4 + size + util::pad_size(size));
+
+            // skip the attributes that are not supported.
+            let attrkind = match AttrKind::try_from(key) {
+                Err(_) => continue,
+                Ok(a) => a
+            };
+
+            // skip the ignored attributes after MessageIntegrity.
+            if follows_integrity && attrkind != AttrKind::Fingerprint {
+                continue;
+            }
+
+            return Some(Ok((attrkind, value)))
+        }
+
+        self.done = true;
+        None
+    }
+}
+
+/// lazy stun message reader.
+///
+/// only the header is checked when the message is created,
+/// the attributes are iterated over the borrowed buffer and
+/// decoded on demand, there is no allocation, which suits the
+/// hot relay path. `MessageReader` decodes all attributes
+/// eagerly and is more convenient for everything else.
+///
+/// # Unit Test
+///
+/// ```
+/// use stun::*;
+/// use stun::attribute::*;
+/// use std::convert::TryFrom;
+/// use std::alloc::{GlobalAlloc, System, Layout};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// struct Counter;
+/// static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+/// unsafe impl GlobalAlloc for Counter {
+///     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+///         ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+///         System.alloc(layout)
+///     }
+///
+///     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+///         System.dealloc(ptr, layout)
+///     }
+/// }
+///
+/// #[global_allocator]
+/// static GLOBAL: Counter = Counter;
+///
+/// let buffer = [
+///     0x00u8, 0x03, 0x00, 0x10,
+///     0x21, 0x12, 0xa4, 0x42,
+///     0x64, 0x4f, 0x5a, 0x78,
+///     0x6a, 0x56, 0x33, 0x62,
+///     0x4b, 0x52, 0x33, 0x31,
+///     0x00, 0x19, 0x00, 0x04,
+///     0x11, 0x00, 0x00, 0x00,
+///     0x00, 0x06, 0x00, 0x05,
+///     0x70, 0x61, 0x6e, 0x64,
+///     0x61, 0x00, 0x00, 0x00
+/// ];
+///
+/// let before = ALLOCATIONS.load(Ordering::Relaxed);
+/// let message = LazyMessageReader::try_from(&buffer[..]).unwrap();
+/// let username = message.get::<UserName>().unwrap().unwrap();
+/// let count = message.attributes().count();
+/// assert_eq!(ALLOCATIONS.load(Ordering::Relaxed), before);
+///
+/// assert_eq!(message.kind, Kind::AllocateRequest);
+/// assert_eq!(username, "panda");
+/// assert_eq!(count, 2);
+/// assert!(message.get::<Realm>().is_none());
+/// ```
+pub struct LazyMessageReader<'a> {
+    /// message type.
+    pub kind: Kind,
+    /// message transaction id.
+    pub token: &'a [u8],
+    /// message source bytes.
+    raw: &'a [u8],
+    /// message decode limits.
+    limits: Limits,
+}
+
+impl<'a> LazyMessageReader<'a> {
+    /// create the reader with the given limits,
+    /// only the header is checked.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use stun::*;
+    ///
+    /// let mut buffer = vec![
+    ///     0x00, 0x01, 0x00, 0x08,
+    ///     0x21, 0x12, 0xa4, 0x42,
+    ///     0x72, 0x6d, 0x49, 0x42,
+    ///     0x72, 0x52, 0x64, 0x48,
+    ///     0x57, 0x62, 0x4b, 0x2b,
+    ///     0x00, 0x06, 0x00, 0x00,
+    ///     0x00, 0x06, 0x00, 0x00
+    /// ];
+    ///
+    /// let limits = Limits {
+    ///     max_attributes: 1,
+    ///     ..Limits::default()
+    /// };
+    ///
+    /// let message = LazyMessageReader::try_from_with(&buffer[..], &limits).unwrap();
+    /// let mut attributes = message.attributes();
+    /// assert!(attributes.next().unwrap().is_ok());
+    /// assert_eq!(attributes.next().unwrap().unwrap_err(), StunError::TooManyAttributes);
+    /// assert!(attributes.next().is_none());
+    ///
+    /// buffer[4] = 0x00;
+    /// assert!(LazyMessageReader::try_from_with(&buffer[..], &limits).is_err());
+    /// ```
+    pub fn try_from_with(buf: &'a [u8], limits: &Limits) -> Result<Self, StunError> {
+        let (kind, token) = header(buf, limits)?;
+        Ok(Self {
+            limits: *limits,
+            raw: buf,
+            token,
+            kind,
+        })
+    }
+
+    /// iterate the attributes of the message.
+    pub fn attributes(&self) -> Attributes<'a> {
+        Attributes::new(self.raw, &self.limits)
+    }
+
+    /// get the first attribute of the type and decode it.
+    ///
+    /// the attributes are searched in order, the search stops
+    /// at a malformed attribute list and returns none.
+    pub fn get<T: Property<'a>>(&self) -> Option<Result<T::Inner, T::Error>> {
+        let kind = T::kind();
+        self.attributes()
+            .map_while(|a| a.ok())
+            .find(|(k, _)| k == &kind)
+            .map(|(_, v)| T::try_from(v, self.token))
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for LazyMessageReader<'a> {
+    type Error = StunError;
+    fn try_from(buf: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::try_from_with(buf, &Limits::default())
+    }
+}
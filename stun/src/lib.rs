@@ -44,21 +44,75 @@
 
 pub mod attribute;
 pub mod util;
+pub mod frame;
 mod message;
+mod lazy;
 mod channel;
+mod error;
 
-use anyhow::Result;
 use std::convert::TryFrom;
 use num_enum::TryFromPrimitive;
 pub use channel::ChannelData;
+pub use error::StunError;
 pub use message::*;
+pub use lazy::*;
+
+/// the default software identifier.
+///
+/// the name and the version of the crate, it is the 
+/// default value of the SOFTWARE attribute, which tells 
+/// which build is deployed remotely.
+///
+/// # Unit Test
+///
+/// ```
+/// assert!(stun::SOFTWARE.starts_with("stun-"));
+/// assert!(stun::SOFTWARE.ends_with(env!("CARGO_PKG_VERSION")));
+/// ```
+pub const SOFTWARE: &str = concat!(
+    env!("CARGO_PKG_NAME"), 
+    "-",
+    env!("CARGO_PKG_VERSION")
+);
+
+/// message class.
+///
+/// The message type field is decomposed further into the following
+/// structure:
+///
+/// ```bash
+///  0                 1
+///  2  3  4 5 6 7 8 9 0 1 2 3 4 5
+/// +--+--+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |M |M |M|M|M|C|M|M|M|C|M|M|M|M|
+/// |11|10|9|8|7|1|6|5|4|0|3|2|1|0|
+/// +--+--+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+///
+/// Here the bits in the message type field are shown as most significant
+/// (M11) through least significant (M0).  M11 through M0 represent a 12-
+/// bit encoding of the method.  C1 and C0 represent a 2-bit encoding of
+/// the class.  A class of 0b00 is a request, a class of 0b01 is an
+/// indication, a class of 0b10 is a success response, and a class of
+/// 0b11 is an error response.
+#[repr(u8)]
+#[derive(PartialEq, Eq, Hash, Debug)]
+#[derive(Copy, Clone)]
+pub enum Class {
+    Request = 0b00,
+    Indication = 0b01,
+    Response = 0b10,
+    Error = 0b11,
+}
 
 /// message type.
 #[repr(u16)]
 #[derive(TryFromPrimitive)]
 #[derive(PartialEq, Eq, Hash, Debug)]
+#[derive(Copy, Clone)]
 pub enum Kind {
     BindingRequest = 0x0001,
+    BindingIndication = 0x0011,
     BindingResponse = 0x0101,
     BindingError = 0x0111,
     AllocateRequest = 0x0003,
@@ -77,6 +131,78 @@ pub enum Kind {
     RefreshError = 0x0114,
 }
 
+impl Kind {
+    /// get the message class.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use stun::*;
+    /// use std::convert::TryFrom;
+    ///
+    /// let kinds = [
+    ///     (Kind::BindingRequest, 0x0001, Class::Request),
+    ///     (Kind::BindingIndication, 0x0011, Class::Indication),
+    ///     (Kind::BindingResponse, 0x0101, Class::Response),
+    ///     (Kind::BindingError, 0x0111, Class::Error),
+    ///     (Kind::AllocateRequest, 0x0003, Class::Request),
+    ///     (Kind::AllocateResponse, 0x0103, Class::Response),
+    ///     (Kind::AllocateError, 0x0113, Class::Error),
+    ///     (Kind::RefreshRequest, 0x0004, Class::Request),
+    ///     (Kind::RefreshResponse, 0x0104, Class::Response),
+    ///     (Kind::RefreshError, 0x0114, Class::Error),
+    ///     (Kind::SendIndication, 0x0016, Class::Indication),
+    ///     (Kind::DataIndication, 0x0017, Class::Indication),
+    ///     (Kind::CreatePermissionRequest, 0x0008, Class::Request),
+    ///     (Kind::CreatePermissionResponse, 0x0108, Class::Response),
+    ///     (Kind::CreatePermissionError, 0x0118, Class::Error),
+    ///     (Kind::ChannelBindRequest, 0x0009, Class::Request),
+    ///     (Kind::ChannelBindResponse, 0x0109, Class::Response),
+    ///     (Kind::ChannelBindError, 0x0119, Class::Error),
+    /// ];
+    ///
+    /// for (kind, value, class) in kinds.iter() {
+    ///     assert_eq!(*kind as u16, *value);
+    ///     assert_eq!(Kind::try_from(*value).unwrap(), *kind);
+    ///     assert_eq!(kind.class(), *class);
+    /// }
+    /// ```
+    #[rustfmt::skip]
+    pub fn class(self) -> Class {
+        let value = self as u16;
+        match ((value >> 7) & 0x2) | ((value >> 4) & 0x1) {
+            0b00 => Class::Request,
+            0b01 => Class::Indication,
+            0b10 => Class::Response,
+            _ => Class::Error,
+        }
+    }
+
+    /// get the message method.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use stun::*;
+    ///
+    /// assert_eq!(Kind::BindingRequest.method(), 0x001);
+    /// assert_eq!(Kind::BindingError.method(), 0x001);
+    /// assert_eq!(Kind::AllocateResponse.method(), 0x003);
+    /// assert_eq!(Kind::RefreshError.method(), 0x004);
+    /// assert_eq!(Kind::SendIndication.method(), 0x006);
+    /// assert_eq!(Kind::DataIndication.method(), 0x007);
+    /// assert_eq!(Kind::CreatePermissionRequest.method(), 0x008);
+    /// assert_eq!(Kind::ChannelBindResponse.method(), 0x009);
+    /// ```
+    #[rustfmt::skip]
+    pub fn method(self) -> u16 {
+        let value = self as u16;
+        (value & 0x000F) | 
+        ((value & 0x00E0) >> 1) | 
+        ((value & 0x3E00) >> 2)
+    }
+}
+
 /// stun message payload.
 pub enum Payload<'a> {
     /// stun message.
@@ -86,12 +212,59 @@ pub enum Payload<'a> {
 }
 
 impl<'a> TryFrom<&'a [u8]> for Payload<'a> {
-    type Error = anyhow::Error;
+    type Error = StunError;
+    /// demultiplex the datagram.
+    ///
+    /// stun messages and channel data share the same port, 
+    /// the first two bits of a stun message are 0b00, the 
+    /// first two bits of channel data are 0b01, any other 
+    /// value is neither of them.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use stun::*;
+    /// use std::convert::TryFrom;
+    ///
+    /// let buffer: [u8; 20] = [
+    ///     0x00, 0x01, 0x00, 0x00,
+    ///     0x21, 0x12, 0xa4, 0x42,
+    ///     0x72, 0x6d, 0x49, 0x42,
+    ///     0x72, 0x52, 0x64, 0x48,
+    ///     0x57, 0x62, 0x4b, 0x2b
+    /// ];
+    ///
+    /// match Payload::try_from(&buffer[..]) {
+    ///     Ok(Payload::Message(m)) => assert_eq!(m.kind, Kind::BindingRequest),
+    ///     _ => panic!("expected a stun message"),
+    /// }
+    ///
+    /// let buffer: [u8; 6] = [
+    ///     0x40, 0x00, 0x00, 0x02,
+    ///     0x01, 0x02
+    /// ];
+    ///
+    /// match Payload::try_from(&buffer[..]) {
+    ///     Ok(Payload::ChannelData(c)) => assert_eq!(c.number, 0x4000),
+    ///     _ => panic!("expected channel data"),
+    /// }
+    ///
+    /// let buffer: [u8; 4] = [
+    ///     0x80, 0x00, 0x00, 0x00
+    /// ];
+    ///
+    /// let err = Payload::try_from(&buffer[..]).err().unwrap();
+    /// assert_eq!(err, StunError::UnknownPayload);
+    /// ```
     fn try_from(buf: &'a [u8]) -> Result<Self, Self::Error> {
-        assert!(buf.len() >= 4);
-        Ok(match buf[0] >> 4 == 4 {
-            true => Self::ChannelData(ChannelData::try_from(buf)?),
-            false => Self::Message(MessageReader::try_from(buf)?),
+        if buf.len() < 4 {
+            return Err(StunError::TooShort)
+        }
+
+        Ok(match buf[0] >> 6 {
+            0b00 => Self::Message(MessageReader::try_from(buf)?),
+            0b01 => Self::ChannelData(ChannelData::try_from(buf)?),
+            _ => return Err(StunError::UnknownPayload)
         })
     }
 }
@@ -1,4 +1,8 @@
-use std::convert::TryFrom;
+use std::{
+    convert::TryFrom,
+    fmt
+};
+
 use anyhow::{
     Result,
     ensure,
@@ -7,25 +11,72 @@ use anyhow::{
 
 use super::attribute::{
     MessageIntegrity,
+    XorRelayedAddress,
+    XorMappedAddress,
+    XorPeerAddress,
+    ReservationToken,
+    ReqeestedTransport,
+    ResponseOrigin,
+    MappedAddress,
+    ChannelNumber,
+    Fingerprint,
+    ErrorCode,
+    EvenPort,
+    Lifetime,
+    Software,
+    UserName,
+    Realm,
+    Nonce,
     AttrKind,
     Property
 };
 
 use super::{
+    lazy::{
+        Attributes,
+        header
+    },
+    StunError,
     Kind,
+    frame,
     util
 };
 
+use super::util::{
+    Crypto,
+    RustCrypto
+};
+
 use bytes::{
     BytesMut,
     BufMut
 };
 
 const ZOER_BUF: [u8; 10] = [0u8; 10];
-const COOKIE: [u8; 4] = [0x21, 0x12, 0xA4, 0x42];
+pub(crate) const COOKIE: [u8; 4] = [0x21, 0x12, 0xA4, 0x42];
+
+/// stun message decode limits.
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    /// the maximum message size, header included.
+    pub max_size: usize,
+    /// the maximum number of attributes.
+    pub max_attributes: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_size: 65535,
+            max_attributes: 64,
+        }
+    }
+}
 
 /// (username, password, realm)
-type Auth = [u8; 16];
+/// the message integrity key, the long-term key 
+/// or the short-term password.
+type Auth = [u8];
 
 /// stun message reader.
 pub struct MessageReader<'a> {
@@ -43,11 +94,81 @@ pub struct MessageReader<'a> {
 
 /// stun message writer.
 pub struct MessageWriter<'a> {
-    token: &'a [u8],
+    token: [u8; 12],
     raw: &'a mut BytesMut,
 }
 
 impl<'a> MessageWriter<'a> {
+    /// create a new message with a random transaction id.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use stun::*;
+    /// use bytes::BytesMut;
+    ///
+    /// let mut buf = BytesMut::new();
+    /// let message = MessageWriter::new(Kind::BindingRequest, &mut buf);
+    /// assert_eq!(message.token().len(), 12);
+    /// assert_eq!(&buf[..8], &[0x00, 0x01, 0x00, 0x00, 0x21, 0x12, 0xa4, 0x42]);
+    /// ```
+    pub fn new(kind: Kind, raw: &'a mut BytesMut) -> Self {
+        Self::new_with(kind, raw, &mut util::RandomToken)
+    }
+
+    /// create a new message with the transaction id source.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use stun::*;
+    /// use stun::util::SeededToken;
+    /// use bytes::BytesMut;
+    /// use std::convert::TryFrom;
+    ///
+    /// let buffer = [
+    ///     0x00u8, 0x01, 0x00, 0x00, 
+    ///     0x21, 0x12, 0xa4, 0x42,
+    ///     0xbd, 0xd7, 0x32, 0x26, 
+    ///     0x2f, 0xeb, 0x6e, 0x95, 
+    ///     0x28, 0xef, 0xe3, 0x33
+    /// ];
+    ///
+    /// let mut buf = BytesMut::new();
+    /// let mut source = SeededToken::new(42);
+    /// let message = MessageWriter::new_with(Kind::BindingRequest, &mut buf, &mut source);
+    /// assert_eq!(message.token(), &buffer[8..]);
+    /// assert_eq!(&buf[..], &buffer[..]);
+    ///
+    /// // the response echoes the transaction id.
+    /// let request = MessageReader::try_from(&buffer[..]).unwrap();
+    /// let mut buf = BytesMut::new();
+    /// let response = MessageWriter::derive(Kind::BindingResponse, &request, &mut buf);
+    /// assert_eq!(response.token(), &buffer[8..]);
+    /// ```
+    #[rustfmt::skip]
+    pub fn new_with<T: util::TokenSource + ?Sized>(
+        kind: Kind, 
+        raw: &'a mut BytesMut, 
+        source: &mut T
+    ) -> Self {
+        let token = source.token();
+        unsafe { raw.set_len(0) }
+        raw.put_u16(kind as u16);
+        raw.put_u16(0);
+        raw.put(&COOKIE[..]);
+        raw.put(&token[..]);
+        Self {
+            raw,
+            token,
+        }
+    }
+
+    /// the transaction id of the message.
+    pub fn token(&self) -> &[u8] {
+        &self.token
+    }
+
     /// rely on old message to create new message.
     ///
     /// # Unit Test
@@ -81,9 +202,11 @@ impl<'a> MessageWriter<'a> {
         raw.put_u16(0);
         raw.put(&COOKIE[..]);
         raw.put(reader.token);
+        let mut token = [0u8; 12];
+        token.copy_from_slice(reader.token);
         Self {
             raw,
-            token: reader.token,
+            token,
         }
     }
     
@@ -126,21 +249,20 @@ impl<'a> MessageWriter<'a> {
     /// ```
     #[rustfmt::skip]
     pub fn append<T: Property<'a>>(&mut self, value: T::Inner) {
-        self.raw.put_u16(T::kind() as u16);
-        
-        // record the current position, 
-        // and then advance the internal cursor 2 bytes, 
-        // here is to reserve the position.
         let os = self.raw.len();
-        unsafe { self.raw.advance_mut(2) }
-        T::into(value, &mut self.raw, self.token);
+        self.raw.put_u16(T::kind() as u16);
         
-        // compute write index,
-        // back to source index write size.
-        let size = self.raw.len() - os - 2;
-        let size_buf = (size as u16).to_be_bytes();
-        self.raw[os] = size_buf[0];
-        self.raw[os + 1] = size_buf[1];
+        // the attribute value is a length prefixed frame, 
+        // a value that is too large for the length is dropped 
+        // together with the attribute type.
+        let token = self.token;
+        let size = match frame::put_with(self.raw, |b| T::into(value, b, &token)) {
+            Ok(size) => size,
+            Err(_) => {
+                self.raw.truncate(os);
+                return
+            }
+        };
         
         // if you need to padding, 
         // padding in the zero bytes.
@@ -180,7 +302,7 @@ impl<'a> MessageWriter<'a> {
     ///     0x9c, 0x5c, 0x19, 0x17,
     ///     0x98, 0xc6, 0xd9, 0xde, 
     ///     0x80, 0x28, 0x00, 0x04,
-    ///     0xed, 0x41, 0xb6, 0xbe
+    ///     0x4a, 0xa5, 0xab, 0x56
     /// ];
     /// 
     /// let mut buf = BytesMut::with_capacity(1280);
@@ -190,6 +312,41 @@ impl<'a> MessageWriter<'a> {
     /// assert_eq!(&buf[..], &result);
     /// ```
     pub fn try_into(&mut self, auth: Option<&Auth>) -> Result<()> {
+        self.try_into_with(auth, &RustCrypto)
+    }
+
+    /// try decoder bytes as message with the hashing backend.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use stun::*;
+    /// use stun::util::RustCrypto;
+    /// use bytes::BytesMut;
+    /// use std::convert::TryFrom;
+    /// 
+    /// let buffer = [
+    ///     0x00u8, 0x01, 0x00, 0x00, 
+    ///     0x21, 0x12, 0xa4, 0x42,
+    ///     0x72, 0x6d, 0x49, 0x42, 
+    ///     0x72, 0x52, 0x64, 0x48,
+    ///     0x57, 0x62, 0x4b, 0x2b
+    /// ];
+    /// 
+    /// let mut a = BytesMut::with_capacity(1280);
+    /// let mut b = BytesMut::with_capacity(1280);
+    /// let key = util::long_key("panda", "panda", "raspberry");
+    /// let old = MessageReader::try_from(&buffer[..]).unwrap();
+    ///
+    /// MessageWriter::derive(Kind::BindingRequest, &old, &mut a)
+    ///     .try_into(Some(&key))
+    ///     .unwrap();
+    /// MessageWriter::derive(Kind::BindingRequest, &old, &mut b)
+    ///     .try_into_with(Some(&key), &RustCrypto)
+    ///     .unwrap();
+    /// assert_eq!(&a[..], &b[..]);
+    /// ```
+    pub fn try_into_with(&mut self, auth: Option<&Auth>, crypto: &dyn Crypto) -> Result<()> {
         // write attribute list size.
         let size = (self.raw.len() - 20) as u16;
         let size_buf = size.to_be_bytes();
@@ -198,11 +355,51 @@ impl<'a> MessageWriter<'a> {
 
         // if need message integrity?
         if let Some(a) = auth {
-            self.integrity(a)?;
+            self.integrity(a, crypto)?;
         }
         
+        debug_assert!(self.audit().is_ok(), "stun message padding");
         Ok(())
     }
+
+    /// audit the padding of the encoded message.
+    ///
+    /// every attribute is padded to a 4 byte boundary with zero 
+    /// bytes and the message length is 4 byte aligned, some clients 
+    /// reject a message that is not, this is a correctness guard 
+    /// for the encoder, in debug builds every encoded message is 
+    /// audited.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use stun::*;
+    /// use stun::attribute::*;
+    /// use bytes::BytesMut;
+    /// use std::convert::TryFrom;
+    ///
+    /// let buffer = [
+    ///     0x00u8, 0x01, 0x00, 0x00,
+    ///     0x21, 0x12, 0xa4, 0x42,
+    ///     0x72, 0x6d, 0x49, 0x42,
+    ///     0x72, 0x52, 0x64, 0x48,
+    ///     0x57, 0x62, 0x4b, 0x2b
+    /// ];
+    ///
+    /// let mut buf = BytesMut::new();
+    /// let old = MessageReader::try_from(&buffer[..]).unwrap();
+    /// let mut message = MessageWriter::derive(Kind::BindingRequest, &old, &mut buf);
+    /// message.append::<UserName>("panda");
+    /// message.try_into(None).unwrap();
+    /// assert!(message.audit().is_ok());
+    ///
+    /// // "panda" is 5 bytes, followed by 3 zero bytes.
+    /// assert_eq!(&buf[20..32], b"\x00\x06\x00\x05panda\x00\x00\x00");
+    /// assert_eq!(buf.len() % 4, 0);
+    /// ```
+    pub fn audit(&self) -> Result<()> {
+        audit_padding(&self.raw[..])
+    }
     
     /// append MessageIntegrity attribute.
     ///
@@ -237,7 +434,7 @@ impl<'a> MessageWriter<'a> {
     ///     0x9c, 0x5c, 0x19, 0x17,
     ///     0x98, 0xc6, 0xd9, 0xde, 
     ///     0x80, 0x28, 0x00, 0x04,
-    ///     0xed, 0x41, 0xb6, 0xbe
+    ///     0x4a, 0xa5, 0xab, 0x56
     /// ];
     /// 
     /// let mut buf = BytesMut::from(&buffer[..]);
@@ -247,7 +444,7 @@ impl<'a> MessageWriter<'a> {
     /// assert_eq!(&buf[..], &result);
     /// ```
     #[rustfmt::skip]
-    fn integrity(&mut self, auth: &Auth) -> Result<()> {
+    fn integrity(&mut self, auth: &Auth, crypto: &dyn Crypto) -> Result<()> {
         assert!(self.raw.len() >= 20);
         
         // compute new size,
@@ -262,8 +459,8 @@ impl<'a> MessageWriter<'a> {
         // long key,
         // digest the message buffer,
         // create the new MessageIntegrity attribute.
-        let hmac_output = util::hmac_sha1(auth, vec![&self.raw])?.into_bytes();
-        let property_buf = hmac_output.as_slice();
+        let hmac_output = crypto.hmac_sha1(auth, vec![&self.raw])?;
+        let property_buf = &hmac_output[..];
 
         // write MessageIntegrity attribute.
         self.raw.put_u16(AttrKind::MessageIntegrity as u16);
@@ -279,15 +476,63 @@ impl<'a> MessageWriter<'a> {
         self.raw[2] = size_buf[0];
         self.raw[3] = size_buf[1];
 
-        // CRC Fingerprint
+        // CRC Fingerprint,
+        // the crc covers the message before the attribute.
+        let fingerprint = crypto.fingerprint(self.raw);
         self.raw.put_u16(AttrKind::Fingerprint as u16);
         self.raw.put_u16(4);
-        self.raw.put_u32(util::fingerprint(&self.raw));
+        self.raw.put_u32(fingerprint);
 
         Ok(())
     }
 }
 
+/// check the padding and alignment of an encoded message.
+///
+/// # Unit Test
+///
+/// ```
+/// use stun::*;
+///
+/// let mut buffer = [
+///     0x00u8, 0x01, 0x00, 0x0c,
+///     0x21, 0x12, 0xa4, 0x42,
+///     0x72, 0x6d, 0x49, 0x42,
+///     0x72, 0x52, 0x64, 0x48,
+///     0x57, 0x62, 0x4b, 0x2b,
+///     0x00, 0x06, 0x00, 0x05,
+///     0x70, 0x61, 0x6e, 0x64,
+///     0x61, 0x00, 0x00, 0x00
+/// ];
+///
+/// assert!(audit_padding(&buffer[..]).is_ok());
+/// assert!(audit_padding(&buffer[..31]).is_err());
+///
+/// buffer[31] = 0xff;
+/// assert!(audit_padding(&buffer[..]).is_err());
+/// ```
+#[rustfmt::skip]
+pub fn audit_padding(buf: &[u8]) -> Result<()> {
+    ensure!(buf.len() >= 20, "buf is too short");
+
+    let size = util::as_u16(&buf[2..4]) as usize;
+    ensure!(size.is_multiple_of(4), "message length is not aligned");
+    ensure!(size + 20 == buf.len(), "message length mismatch");
+
+    let mut offset = 20;
+    while offset < buf.len() {
+        ensure!(buf.len() - offset >= 4, "truncated attribute header");
+
+        let end = offset + 4 + util::as_u16(&buf[offset + 2..offset + 4]) as usize;
+        let psize = util::pad_size(end - offset - 4);
+        ensure!(end + psize <= buf.len(), "truncated attribute");
+        ensure!(buf[end..end + psize].iter().all(|b| *b == 0), "padding is not zero");
+        offset = end + psize;
+    }
+
+    Ok(())
+}
+
 impl<'a> MessageReader<'a> {
     /// get attribute.
     ///
@@ -363,8 +608,103 @@ impl<'a> MessageReader<'a> {
     /// let result = message.integrity(&util::long_key("panda", "panda", "raspberry")).is_ok();
     /// assert!(result);
     /// ```
-    #[rustfmt::skip]
     pub fn integrity(&self, auth: &Auth) -> Result<()> {
+        self.integrity_with(auth, &RustCrypto)
+    }
+
+    /// check MessageReaderIntegrity attribute with the hashing backend.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use stun::*;
+    /// use stun::util::RustCrypto;
+    /// use std::convert::TryFrom;
+    /// 
+    /// let buffer = [
+    ///     0x00u8, 0x03, 0x00, 0x50, 
+    ///     0x21, 0x12, 0xa4, 0x42, 
+    ///     0x64, 0x4f, 0x5a, 0x78, 
+    ///     0x6a, 0x56, 0x33, 0x62, 
+    ///     0x4b, 0x52, 0x33, 0x31, 
+    ///     0x00, 0x19, 0x00, 0x04, 
+    ///     0x11, 0x00, 0x00, 0x00, 
+    ///     0x00, 0x06, 0x00, 0x05, 
+    ///     0x70, 0x61, 0x6e, 0x64, 
+    ///     0x61, 0x00, 0x00, 0x00, 
+    ///     0x00, 0x14, 0x00, 0x09, 
+    ///     0x72, 0x61, 0x73, 0x70, 
+    ///     0x62, 0x65, 0x72, 0x72, 
+    ///     0x79, 0x00, 0x00, 0x00, 
+    ///     0x00, 0x15, 0x00, 0x10, 
+    ///     0x31, 0x63, 0x31, 0x33, 
+    ///     0x64, 0x32, 0x62, 0x32, 
+    ///     0x34, 0x35, 0x62, 0x33, 
+    ///     0x61, 0x37, 0x33, 0x34, 
+    ///     0x00, 0x08, 0x00, 0x14,
+    ///     0xd6, 0x78, 0x26, 0x99, 
+    ///     0x0e, 0x15, 0x56, 0x15, 
+    ///     0xe5, 0xf4, 0x24, 0x74, 
+    ///     0xe2, 0x3c, 0x26, 0xc5, 
+    ///     0xb1, 0x03, 0xb2, 0x6d
+    /// ];
+    /// 
+    /// let key = util::long_key("panda", "panda", "raspberry");
+    /// let message = MessageReader::try_from(&buffer[..]).unwrap();
+    /// assert!(message.integrity_with(&key, &RustCrypto).is_ok());
+    /// ```
+    ///
+    /// short-term credentials use the password as the key, 
+    /// the sample request of 
+    /// [RFC5769](https://datatracker.ietf.org/doc/html/rfc5769#section-2.1):
+    ///
+    /// ```
+    /// use stun::*;
+    /// use stun::attribute::*;
+    /// use stun::util::{Credential, RustCrypto};
+    /// use std::convert::TryFrom;
+    ///
+    /// let buffer = [
+    ///     0x00u8, 0x01, 0x00, 0x58,
+    ///     0x21, 0x12, 0xa4, 0x42,
+    ///     0xb7, 0xe7, 0xa7, 0x01,
+    ///     0xbc, 0x34, 0xd6, 0x86,
+    ///     0xfa, 0x87, 0xdf, 0xae,
+    ///     0x80, 0x22, 0x00, 0x10,
+    ///     0x53, 0x54, 0x55, 0x4e,
+    ///     0x20, 0x74, 0x65, 0x73,
+    ///     0x74, 0x20, 0x63, 0x6c,
+    ///     0x69, 0x65, 0x6e, 0x74,
+    ///     0x00, 0x24, 0x00, 0x04,
+    ///     0x6e, 0x00, 0x01, 0xff,
+    ///     0x80, 0x29, 0x00, 0x08,
+    ///     0x93, 0x2f, 0xf9, 0xb1,
+    ///     0x51, 0x26, 0x3b, 0x36,
+    ///     0x00, 0x06, 0x00, 0x09,
+    ///     0x65, 0x76, 0x74, 0x6a,
+    ///     0x3a, 0x68, 0x36, 0x76,
+    ///     0x59, 0x20, 0x20, 0x20,
+    ///     0x00, 0x08, 0x00, 0x14,
+    ///     0x9a, 0xea, 0xa7, 0x0c,
+    ///     0xbf, 0xd8, 0xcb, 0x56,
+    ///     0x78, 0x1e, 0xf2, 0xb5,
+    ///     0xb2, 0xd3, 0xf2, 0x49,
+    ///     0xc1, 0xb5, 0x71, 0xa2,
+    ///     0x80, 0x28, 0x00, 0x04,
+    ///     0xe5, 0x7a, 0x3b, 0xcf
+    /// ];
+    ///
+    /// let message = MessageReader::try_from(&buffer[..]).unwrap();
+    /// let username = message.get::<UserName>().unwrap().unwrap();
+    /// let credential = Credential::new(username, "VOkJxbRl1RmTxUk/WvJxBt", None);
+    /// let wrong = Credential::new(username, "VOkJxbRl1RmTxUk/WvJxBt", Some("realm"));
+    ///
+    /// assert_eq!(username, "evtj:h6vY");
+    /// assert!(message.integrity_with(&credential.key(&RustCrypto), &RustCrypto).is_ok());
+    /// assert!(message.integrity_with(&wrong.key(&RustCrypto), &RustCrypto).is_err());
+    /// ```
+    #[rustfmt::skip]
+    pub fn integrity_with(&self, auth: &Auth, crypto: &dyn Crypto) -> Result<()> {
         ensure!(!self.raw.is_empty(), "buf is empty");
         ensure!(self.valid_offset > 20, "buf is empty");
 
@@ -383,8 +723,8 @@ impl<'a> MessageReader<'a> {
         ];
 
         // digest the message buffer.
-        let hmac_output = util::hmac_sha1(auth, body)?.into_bytes();
-        let property_buf = hmac_output.as_slice();
+        let hmac_output = crypto.hmac_sha1(auth, body)?;
+        let property_buf = &hmac_output[..];
 
         // Compare local and original attribute.
         if integrity != property_buf {
@@ -393,10 +733,157 @@ impl<'a> MessageReader<'a> {
 
         Ok(())
     }
+
+    /// check the FINGERPRINT attribute of the message.
+    ///
+    /// the FINGERPRINT attribute must be the last attribute, 
+    /// it is the CRC-32 of the message up to the attribute, 
+    /// see [RFC8489](https://datatracker.ietf.org/doc/html/rfc8489#section-14.7).
+    /// a message without the attribute fails the check.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use stun::*;
+    /// use std::convert::TryFrom;
+    ///
+    /// let mut buffer = [
+    ///     0x00u8, 0x01, 0x00, 0x58,
+    ///     0x21, 0x12, 0xa4, 0x42,
+    ///     0xb7, 0xe7, 0xa7, 0x01,
+    ///     0xbc, 0x34, 0xd6, 0x86,
+    ///     0xfa, 0x87, 0xdf, 0xae,
+    ///     0x80, 0x22, 0x00, 0x10,
+    ///     0x53, 0x54, 0x55, 0x4e,
+    ///     0x20, 0x74, 0x65, 0x73,
+    ///     0x74, 0x20, 0x63, 0x6c,
+    ///     0x69, 0x65, 0x6e, 0x74,
+    ///     0x00, 0x24, 0x00, 0x04,
+    ///     0x6e, 0x00, 0x01, 0xff,
+    ///     0x80, 0x29, 0x00, 0x08,
+    ///     0x93, 0x2f, 0xf9, 0xb1,
+    ///     0x51, 0x26, 0x3b, 0x36,
+    ///     0x00, 0x06, 0x00, 0x09,
+    ///     0x65, 0x76, 0x74, 0x6a,
+    ///     0x3a, 0x68, 0x36, 0x76,
+    ///     0x59, 0x20, 0x20, 0x20,
+    ///     0x00, 0x08, 0x00, 0x14,
+    ///     0x9a, 0xea, 0xa7, 0x0c,
+    ///     0xbf, 0xd8, 0xcb, 0x56,
+    ///     0x78, 0x1e, 0xf2, 0xb5,
+    ///     0xb2, 0xd3, 0xf2, 0x49,
+    ///     0xc1, 0xb5, 0x71, 0xa2,
+    ///     0x80, 0x28, 0x00, 0x04,
+    ///     0xe5, 0x7a, 0x3b, 0xcf
+    /// ];
+    ///
+    /// let message = MessageReader::try_from(&buffer[..]).unwrap();
+    /// assert!(message.assert_fingerprint().is_ok());
+    ///
+    /// buffer[107] ^= 0x01;
+    /// let message = MessageReader::try_from(&buffer[..]).unwrap();
+    /// assert!(message.assert_fingerprint().is_err());
+    ///
+    /// let message = MessageReader::try_from(&[
+    ///     0x00u8, 0x01, 0x00, 0x00,
+    ///     0x21, 0x12, 0xa4, 0x42,
+    ///     0xb7, 0xe7, 0xa7, 0x01,
+    ///     0xbc, 0x34, 0xd6, 0x86,
+    ///     0xfa, 0x87, 0xdf, 0xae
+    /// ][..]).unwrap();
+    /// assert!(message.assert_fingerprint().is_err());
+    /// ```
+    pub fn assert_fingerprint(&self) -> Result<()> {
+        self.assert_fingerprint_with(&RustCrypto)
+    }
+
+    /// check the FINGERPRINT attribute with the hashing backend.
+    #[rustfmt::skip]
+    pub fn assert_fingerprint_with(&self, crypto: &dyn Crypto) -> Result<()> {
+        let fingerprint = self
+            .get::<Fingerprint>()
+            .ok_or_else(|| anyhow!("not found Fingerprint"))??;
+
+        // the fingerprint is the last attribute, 
+        // the message size includes the attribute.
+        let size = util::as_u16(&self.raw[2..4]) as usize + 20;
+        ensure!(size >= 28 && self.raw.len() >= size, "buf is too short");
+        ensure!(
+            util::as_u16(&self.raw[size - 8..size - 6]) == AttrKind::Fingerprint as u16,
+            "Fingerprint is not the last attribute"
+        );
+
+        if crypto.fingerprint(&self.raw[..size - 8]) != fingerprint {
+            return Err(anyhow!("assert fail!"))
+        }
+
+        Ok(())
+    }
+
+    /// decode the message with the given limits.
+    ///
+    /// the message is rejected if it is larger than the maximum 
+    /// size, or if it carries more attributes than the maximum 
+    /// attribute count, this bounds the work done for a packet 
+    /// stuffed with many tiny attributes.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use stun::*;
+    /// use std::convert::TryFrom;
+    ///
+    /// let mut buffer = vec![
+    ///     0x00, 0x01, 0x01, 0x04,
+    ///     0x21, 0x12, 0xa4, 0x42,
+    ///     0x72, 0x6d, 0x49, 0x42,
+    ///     0x72, 0x52, 0x64, 0x48,
+    ///     0x57, 0x62, 0x4b, 0x2b
+    /// ];
+    ///
+    /// // 65 zero-length USERNAME attributes.
+    /// for _ in 0..65 {
+    ///     buffer.extend_from_slice(&[0x00, 0x06, 0x00, 0x00]);
+    /// }
+    ///
+    /// let err = MessageReader::try_from(&buffer[..]).err().unwrap();
+    /// assert_eq!(err, StunError::TooManyAttributes);
+    ///
+    /// let limits = Limits {
+    ///     max_attributes: 100,
+    ///     ..Limits::default()
+    /// };
+    ///
+    /// assert!(MessageReader::try_from_with(&buffer[..], &limits).is_ok());
+    ///
+    /// let limits = Limits {
+    ///     max_size: 100,
+    ///     max_attributes: 100
+    /// };
+    ///
+    /// let err = MessageReader::try_from_with(&buffer[..], &limits).err().unwrap();
+    /// assert_eq!(err, StunError::TooLarge);
+    /// ```
+    pub fn try_from_with(buf: &'a [u8], limits: &Limits) -> Result<Self, StunError> {
+        let (kind, token) = header(buf, limits)?;
+        let mut iter = Attributes::new(buf, limits);
+        let mut attributes = Vec::with_capacity(limits.max_attributes.min(8));
+        for attribute in &mut iter {
+            attributes.push(attribute?);
+        }
+
+        Ok(Self {
+            kind,
+            token,
+            raw: buf,
+            attributes,
+            valid_offset: iter.valid_offset(),
+        })
+    }
 }
 
 impl<'a> TryFrom<&'a [u8]> for MessageReader<'a> {
-    type Error = anyhow::Error;
+    type Error = StunError;
     /// # Unit Test
     ///
     /// ```
@@ -415,92 +902,181 @@ impl<'a> TryFrom<&'a [u8]> for MessageReader<'a> {
     /// let message = MessageReader::try_from(&buffer[..]).unwrap();
     /// assert_eq!(message.kind, Kind::BindingRequest);
     /// assert!(message.get::<UserName>().is_none());
+    ///
+    /// // USERNAME after MESSAGE-INTEGRITY.
+    /// let buffer: [u8; 56] = [
+    ///     0x00, 0x01, 0x00, 0x24,
+    ///     0x21, 0x12, 0xa4, 0x42,
+    ///     0x72, 0x6d, 0x49, 0x42,
+    ///     0x72, 0x52, 0x64, 0x48,
+    ///     0x57, 0x62, 0x4b, 0x2b,
+    ///     0x00, 0x08, 0x00, 0x14,
+    ///     0x45, 0x0e, 0x6e, 0x44,
+    ///     0x52, 0x1e, 0xe8, 0xde,
+    ///     0x2c, 0xf0, 0xfa, 0xb6,
+    ///     0x9c, 0x5c, 0x19, 0x17,
+    ///     0x98, 0xc6, 0xd9, 0xde,
+    ///     0x00, 0x06, 0x00, 0x05,
+    ///     0x70, 0x61, 0x6e, 0x64,
+    ///     0x61, 0x00, 0x00, 0x00
+    /// ];
+    ///
+    /// assert!(MessageReader::try_from(&buffer[..]).is_err());
+    ///
+    /// // SOFTWARE after MESSAGE-INTEGRITY is ignored.
+    /// let buffer: [u8; 56] = [
+    ///     0x00, 0x01, 0x00, 0x24,
+    ///     0x21, 0x12, 0xa4, 0x42,
+    ///     0x72, 0x6d, 0x49, 0x42,
+    ///     0x72, 0x52, 0x64, 0x48,
+    ///     0x57, 0x62, 0x4b, 0x2b,
+    ///     0x00, 0x08, 0x00, 0x14,
+    ///     0x45, 0x0e, 0x6e, 0x44,
+    ///     0x52, 0x1e, 0xe8, 0xde,
+    ///     0x2c, 0xf0, 0xfa, 0xb6,
+    ///     0x9c, 0x5c, 0x19, 0x17,
+    ///     0x98, 0xc6, 0xd9, 0xde,
+    ///     0x80, 0x22, 0x00, 0x05,
+    ///     0x70, 0x61, 0x6e, 0x64,
+    ///     0x61, 0x00, 0x00, 0x00
+    /// ];
+    ///
+    /// let message = MessageReader::try_from(&buffer[..]).unwrap();
+    /// assert!(message.get::<Software>().is_none());
+    /// assert!(message.get::<MessageIntegrity>().is_some());
+    ///
+    /// // MESSAGE-INTEGRITY-SHA256 and FINGERPRINT after MESSAGE-INTEGRITY.
+    /// let buffer: [u8; 88] = [
+    ///     0x00, 0x01, 0x00, 0x44,
+    ///     0x21, 0x12, 0xa4, 0x42,
+    ///     0x72, 0x6d, 0x49, 0x42,
+    ///     0x72, 0x52, 0x64, 0x48,
+    ///     0x57, 0x62, 0x4b, 0x2b,
+    ///     0x00, 0x08, 0x00, 0x14,
+    ///     0x45, 0x0e, 0x6e, 0x44,
+    ///     0x52, 0x1e, 0xe8, 0xde,
+    ///     0x2c, 0xf0, 0xfa, 0xb6,
+    ///     0x9c, 0x5c, 0x19, 0x17,
+    ///     0x98, 0xc6, 0xd9, 0xde,
+    ///     0x00, 0x1c, 0x00, 0x20,
+    ///     0x01, 0x02, 0x03, 0x04,
+    ///     0x05, 0x06, 0x07, 0x08,
+    ///     0x09, 0x0a, 0x0b, 0x0c,
+    ///     0x0d, 0x0e, 0x0f, 0x10,
+    ///     0x11, 0x12, 0x13, 0x14,
+    ///     0x15, 0x16, 0x17, 0x18,
+    ///     0x19, 0x1a, 0x1b, 0x1c,
+    ///     0x1d, 0x1e, 0x1f, 0x20,
+    ///     0x80, 0x28, 0x00, 0x04,
+    ///     0xe5, 0x7a, 0x3b, 0xcf
+    /// ];
+    ///
+    /// let message = MessageReader::try_from(&buffer[..]).unwrap();
+    /// assert!(message.get::<MessageIntegrity>().is_some());
+    /// assert_eq!(message.get::<Fingerprint>().unwrap().unwrap(), 0xe57a3bcf);
+    ///
+    /// // unknown attributes are skipped.
+    /// let buffer: [u8; 36] = [
+    ///     0x00, 0x01, 0x00, 0x10,
+    ///     0x21, 0x12, 0xa4, 0x42,
+    ///     0x72, 0x6d, 0x49, 0x42,
+    ///     0x72, 0x52, 0x64, 0x48,
+    ///     0x57, 0x62, 0x4b, 0x2b,
+    ///     0x00, 0x24, 0x00, 0x04,
+    ///     0x6e, 0x00, 0x01, 0xff,
+    ///     0x80, 0x28, 0x00, 0x04,
+    ///     0x00, 0x00, 0x00, 0x00
+    /// ];
+    ///
+    /// let message = MessageReader::try_from(&buffer[..]).unwrap();
+    /// assert!(message.get::<Fingerprint>().is_some());
     /// ```
     fn try_from(buf: &'a [u8]) -> Result<Self, Self::Error> {
-        ensure!(buf.len() >= 20, "message len < 20");
-        let mut attributes = Vec::with_capacity(6);
-        let mut find_valid_offset = false;
-        let mut valid_offset = 0;
-        let count_size = buf.len();
-
-        // message type
-        // message size
-        // check fixed magic cookie
-        // check if the message size is overflow
-        let kind = Kind::try_from(util::as_u16(&buf[..2]))?;
-        let size = util::as_u16(&buf[2..4]) as usize;
-        ensure!(buf[4..8] == COOKIE[..], "missing cookie");
-        ensure!(count_size >= size + 20, "missing len");
-
-        // get transaction id
-        let token = &buf[8..20];
-        let mut offset = 20;
-        
-    loop {
-
-        // if the buf length is not long enough to continue, 
-        // jump out of the loop.
-        if count_size - offset < 4 {
-            break;
-        }
-
-        // get attribute type
-        let key = u16::from_be_bytes([
-            buf[offset],
-            buf[offset + 1]
-        ]);
+        Self::try_from_with(buf, &Limits::default())
+    }
+}
 
-        // whether the MessageIntegrity attribute has been found, 
-        // if found, record the current offset position.
-        if !find_valid_offset {
-            valid_offset = offset as u16;
-        }
+impl fmt::Display for MessageReader<'_> {
+    /// human-readable dump of the message.
+    ///
+    /// the method, class and transaction id of the message, 
+    /// followed by every attribute with its decoded value, 
+    /// an attribute that fails to decode is written as an error.
+    ///
+    /// # Unit Test
+    ///
+    /// ```
+    /// use stun::*;
+    /// use std::convert::TryFrom;
+    ///
+    /// let buffer = [
+    ///     0x00u8, 0x03, 0x00, 0x14, 
+    ///     0x21, 0x12, 0xa4, 0x42, 
+    ///     0x64, 0x4f, 0x5a, 0x78, 
+    ///     0x6a, 0x56, 0x33, 0x62, 
+    ///     0x4b, 0x52, 0x33, 0x31, 
+    ///     0x00, 0x19, 0x00, 0x04, 
+    ///     0x11, 0x00, 0x00, 0x00, 
+    ///     0x00, 0x06, 0x00, 0x05, 
+    ///     0x70, 0x61, 0x6e, 0x64, 
+    ///     0x61, 0x00, 0x00, 0x00
+    /// ];
+    ///
+    /// let message = MessageReader::try_from(&buffer[..]).unwrap();
+    /// let dump = format!("{}", message);
+    ///
+    /// assert!(dump.starts_with("AllocateRequest class=Request method=0x003"));
+    /// assert!(dump.contains("tid=644f5a786a5633624b523331"));
+    /// assert!(dump.contains("ReqeestedTransport=17"));
+    /// assert!(dump.contains("UserName=\"panda\""));
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} class={:?} method={:#05x} tid=",
+            self.kind,
+            self.kind.class(),
+            self.kind.method()
+        )?;
 
-        // check whether the current attribute is MessageIntegrity, 
-        // if it is, mark this attribute has been found.
-        if key == AttrKind::MessageIntegrity as u16 {
-            find_valid_offset = true;
+        for byte in self.token {
+            write!(f, "{:02x}", byte)?;
         }
 
-        // skip the attributes that are not supported.
-        let attrkind = match AttrKind::try_from(key) {
-            Err(_) => continue,
-            Ok(a) => a
-        };
-
-        // get attribute size
-        let size = u16::from_be_bytes([
-            buf[offset + 2],
-            buf[offset + 3]
-        ]) as usize;
-
-        // check if the attribute length has overflowed.
-        offset += 4;
-        if count_size - offset < size {
-            break;
+        for (kind, value) in &self.attributes {
+            write!(f, " {:?}=", kind)?;
+            dump_attribute(f, *kind, value, self.token)?;
         }
 
-        // get attribute body
-        // insert attribute to attributes list.
-        attributes.push((attrkind, &buf[
-            offset..
-            offset + size
-        ]));
-
-        // if there are padding bytes, 
-        // skip padding size.
-        let psize = util::pad_size(size);
-        if size > 0 {
-            offset += size + psize;
-        }
+        Ok(())
     }
+}
 
-        Ok(Self {
-            kind,
-            token,
-            raw: buf,
-            attributes,
-            valid_offset,
-        })
+/// write the decoded value of the attribute.
+#[rustfmt::skip]
+fn dump_attribute(f: &mut fmt::Formatter<'_>, kind: AttrKind, v: &[u8], t: &[u8]) -> fmt::Result {
+    let value = match kind {
+        AttrKind::UserName => <UserName as Property>::try_from(v, t).map(|x| format!("{:?}", x)),
+        AttrKind::Realm => <Realm as Property>::try_from(v, t).map(|x| format!("{:?}", x)),
+        AttrKind::Nonce => <Nonce as Property>::try_from(v, t).map(|x| format!("{:?}", x)),
+        AttrKind::Software => <Software as Property>::try_from(v, t).map(|x| format!("{:?}", x)),
+        AttrKind::XorPeerAddress => <XorPeerAddress as Property>::try_from(v, t).map(|x| x.to_string()),
+        AttrKind::XorRelayedAddress => <XorRelayedAddress as Property>::try_from(v, t).map(|x| x.to_string()),
+        AttrKind::XorMappedAddress => <XorMappedAddress as Property>::try_from(v, t).map(|x| x.to_string()),
+        AttrKind::MappedAddress => <MappedAddress as Property>::try_from(v, t).map(|x| x.to_string()),
+        AttrKind::ResponseOrigin => <ResponseOrigin as Property>::try_from(v, t).map(|x| x.to_string()),
+        AttrKind::ErrorCode => <ErrorCode as Property>::try_from(v, t).map(|x| format!("{} {:?}", x.code, x.message)),
+        AttrKind::Lifetime => <Lifetime as Property>::try_from(v, t).map(|x| x.to_string()),
+        AttrKind::ReqeestedTransport => <ReqeestedTransport as Property>::try_from(v, t).map(|x| x.to_string()),
+        AttrKind::Fingerprint => <Fingerprint as Property>::try_from(v, t).map(|x| format!("{:#010x}", x)),
+        AttrKind::ChannelNumber => <ChannelNumber as Property>::try_from(v, t).map(|x| format!("{:#06x}", x)),
+        AttrKind::EvenPort => <EvenPort as Property>::try_from(v, t).map(|x| x.to_string()),
+        AttrKind::ReservationToken => <ReservationToken as Property>::try_from(v, t).map(|x| format!("{:#018x}", x)),
+        AttrKind::Data | AttrKind::MessageIntegrity => Ok(format!("<{} bytes>", v.len())),
+    };
+
+    match value {
+        Ok(value) => write!(f, "{}", value),
+        Err(e) => write!(f, "<{}>", e)
     }
 }
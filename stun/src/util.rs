@@ -52,6 +52,85 @@ pub fn long_key(username: &str, key: &str, realm: &str) -> [u8; 16] {
     md5::compute([username, realm, key].join(":")).0
 }
 
+/// create short key.
+///
+/// > key = OpaqueString(password)
+///
+/// for short-term credentials the password is used as 
+/// the key directly, there is no realm.
+///
+/// ```
+/// assert_eq!(stun::util::short_key("password"), b"password");
+/// ```
+pub fn short_key(password: &str) -> &[u8] {
+    password.as_bytes()
+}
+
+/// credential mechanism.
+///
+/// the long-term credential mechanism is used by TURN, the 
+/// short-term credential mechanism is used by ICE connectivity 
+/// checks, which have no REALM and use the password directly as 
+/// the message integrity key.
+///
+/// # Unit Test
+///
+/// ```
+/// use stun::util::*;
+///
+/// let short = Credential::new("evtj:h6vY", "VOkJxbRl1RmTxUk/WvJxBt", None);
+/// let long = Credential::new("panda", "panda", Some("raspberry"));
+///
+/// assert_eq!(short.key(&RustCrypto), b"VOkJxbRl1RmTxUk/WvJxBt".to_vec());
+/// assert_eq!(long.key(&RustCrypto), long_key("panda", "panda", "raspberry").to_vec());
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub enum Credential<'a> {
+    /// short-term credential, the password is the key.
+    ShortTerm {
+        username: &'a str,
+        password: &'a str,
+    },
+    /// long-term credential, the key is derived 
+    /// from the username, password and realm.
+    LongTerm {
+        username: &'a str,
+        password: &'a str,
+        realm: &'a str,
+    },
+}
+
+impl<'a> Credential<'a> {
+    /// create credential.
+    ///
+    /// the short-term credential mechanism is 
+    /// selected by the absence of the realm.
+    pub fn new(username: &'a str, password: &'a str, realm: Option<&'a str>) -> Self {
+        match realm {
+            None => Self::ShortTerm { username, password },
+            Some(realm) => Self::LongTerm { username, password, realm },
+        }
+    }
+
+    /// the username of the credential.
+    pub fn username(&self) -> &'a str {
+        match self {
+            Self::ShortTerm { username, .. } => username,
+            Self::LongTerm { username, .. } => username,
+        }
+    }
+
+    /// the message integrity key of the credential.
+    pub fn key(&self, crypto: &dyn Crypto) -> Vec<u8> {
+        match self {
+            Self::ShortTerm { password, .. } => short_key(password).to_vec(),
+            Self::LongTerm { username, password, realm } => {
+                crypto.long_key(username, password, realm).to_vec()
+            }
+        }
+    }
+}
+
 /// HMAC SHA1 digest.
 ///
 /// # Unit Test
@@ -95,7 +174,7 @@ pub fn long_key(username: &str, key: &str, realm: &str) -> [u8; 16] {
 /// ];
 /// 
 /// let hmac_output = stun::util::hmac_sha1(&key, vec![&buffer]).unwrap().into_bytes();
-/// assert_eq!(hmac_output.as_slice(), &sign); 
+/// assert_eq!(&hmac_output[..], &sign); 
 /// ```
 pub fn hmac_sha1(key: &[u8], source: Vec<&[u8]>) -> Result<Output<Hmac<sha1::Sha1>>> {
     match Hmac::<sha1::Sha1>::new_varkey(key) {
@@ -121,6 +200,152 @@ pub fn fingerprint(buffer: &[u8]) -> u32 {
     crc32::checksum_ieee(buffer) ^ 0x5354_554e
 }
 
+/// hashing backend.
+///
+/// the long-term key, the message integrity and the fingerprint 
+/// depend on these algorithms, implement this trait to use 
+/// another crypto library, the default is `RustCrypto`.
+///
+/// # Unit Test
+///
+/// ```
+/// use stun::util::*;
+/// use sha1::{Sha1, Digest};
+///
+/// struct Manual;
+/// impl Crypto for Manual {
+///     fn long_key(&self, username: &str, key: &str, realm: &str) -> [u8; 16] {
+///         long_key(username, key, realm)
+///     }
+///
+///     fn hmac_sha1(&self, key: &[u8], source: Vec<&[u8]>) -> anyhow::Result<[u8; 20]> {
+///         let mut block = [0u8; 64];
+///         block[..key.len()].copy_from_slice(key);
+///
+///         let mut inner = Sha1::new();
+///         inner.update(&block.iter().map(|b| b ^ 0x36).collect::<Vec<u8>>());
+///         for buf in source {
+///             inner.update(buf);
+///         }
+///
+///         let mut outer = Sha1::new();
+///         outer.update(&block.iter().map(|b| b ^ 0x5c).collect::<Vec<u8>>());
+///         outer.update(&inner.finalize());
+///
+///         let mut output = [0u8; 20];
+///         output.copy_from_slice(&outer.finalize());
+///         Ok(output)
+///     }
+///
+///     fn fingerprint(&self, buffer: &[u8]) -> u32 {
+///         fingerprint(buffer)
+///     }
+/// }
+///
+/// let key = long_key("panda", "panda", "raspberry");
+/// let source: [&[u8]; 2] = [b"hello", b"world"];
+/// let default = RustCrypto.hmac_sha1(&key, source.to_vec()).unwrap();
+/// let manual = Manual.hmac_sha1(&key, source.to_vec()).unwrap();
+/// assert_eq!(default, manual);
+/// ```
+pub trait Crypto: Send + Sync {
+    /// create long key.
+    fn long_key(&self, username: &str, key: &str, realm: &str) -> [u8; 16];
+    /// HMAC SHA1 digest.
+    fn hmac_sha1(&self, key: &[u8], source: Vec<&[u8]>) -> Result<[u8; 20]>;
+    /// CRC32 Fingerprint.
+    fn fingerprint(&self, buffer: &[u8]) -> u32;
+}
+
+/// the default hashing backend, 
+/// implemented with RustCrypto.
+pub struct RustCrypto;
+impl Crypto for RustCrypto {
+    fn long_key(&self, username: &str, key: &str, realm: &str) -> [u8; 16] {
+        long_key(username, key, realm)
+    }
+
+    fn hmac_sha1(&self, key: &[u8], source: Vec<&[u8]>) -> Result<[u8; 20]> {
+        Ok(hmac_sha1(key, source)?.into_bytes().into())
+    }
+
+    fn fingerprint(&self, buffer: &[u8]) -> u32 {
+        fingerprint(buffer)
+    }
+}
+
+/// transaction id source.
+///
+/// the transaction id of a new request is taken from the 
+/// source, the default is `RandomToken`, a seeded source 
+/// gives predictable ids for request and response tests.
+pub trait TokenSource {
+    /// create transaction id.
+    fn token(&mut self) -> [u8; 12];
+}
+
+/// the default transaction id source, 
+/// the ids are random.
+pub struct RandomToken;
+impl TokenSource for RandomToken {
+    fn token(&mut self) -> [u8; 12] {
+        rand::random()
+    }
+}
+
+/// deterministic transaction id source.
+///
+/// the ids are generated by splitmix64 from the seed, 
+/// the same seed always gives the same sequence of ids, 
+/// it is intended for tests only.
+///
+/// # Unit Test
+///
+/// ```
+/// use stun::util::*;
+///
+/// let mut source = SeededToken::new(42);
+/// assert_eq!(source.token(), [
+///     0xbd, 0xd7, 0x32, 0x26, 
+///     0x2f, 0xeb, 0x6e, 0x95, 
+///     0x28, 0xef, 0xe3, 0x33
+/// ]);
+///
+/// assert_eq!(source.token(), [
+///     0x47, 0x52, 0x67, 0x57, 
+///     0x13, 0x0f, 0x9f, 0x52, 
+///     0x58, 0x1c, 0xe1, 0xff
+/// ]);
+///
+/// assert_eq!(SeededToken::new(7).token(), SeededToken::new(7).token());
+/// ```
+pub struct SeededToken(u64);
+impl SeededToken {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+impl TokenSource for SeededToken {
+    fn token(&mut self) -> [u8; 12] {
+        let mut token = [0u8; 12];
+        for chunk in token.chunks_mut(8) {
+            let value = self.next().to_be_bytes();
+            chunk.copy_from_slice(&value[..chunk.len()]);
+        }
+
+        token
+    }
+}
+
 /// slice as u16.
 ///
 /// # Unit Test